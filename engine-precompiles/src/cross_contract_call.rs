@@ -0,0 +1,241 @@
+use super::{EvmPrecompileResult, Precompile};
+#[cfg(feature = "contract")]
+use crate::prelude::{
+    parameters::{EvmCallbackArgs, PromiseArgs, PromiseCreateArgs, PromiseWithCallbackArgs},
+    sdk,
+    storage::{address_to_key, KeyPrefix},
+    vec, AccountId, BorshDeserialize, BorshSerialize, Cow, ToString, TryFrom, TryInto, Vec, U256,
+};
+
+use crate::prelude::Address;
+use crate::PrecompileOutput;
+#[cfg(feature = "contract")]
+use evm::backend::Log;
+use evm::{Context, ExitError};
+
+mod costs {
+    use crate::prelude::types::Gas;
+
+    /// Base EVM gas cost of scheduling a NEAR cross-contract call.
+    pub(super) const CROSS_CONTRACT_CALL_BASE: Gas = 343_650;
+
+    /// Additional EVM gas cost per byte of the precompile input.
+    pub(super) const CROSS_CONTRACT_CALL_BYTE: Gas = 4;
+
+    /// Amount of NEAR gas attached to the promise which is covered by one unit
+    /// of EVM gas.
+    pub(super) const CROSS_CONTRACT_CALL_NEAR_GAS: Gas = 175_000_000;
+
+    /// NEAR gas of the `evm_callback` call besides its EVM execution, which
+    /// the NEAR gas attached to the callback must cover on top of its EVM gas.
+    pub(super) const EVM_CALLBACK_BASE_NEAR_GAS: Gas = 5_000_000_000_000;
+}
+
+/// Schedules an arbitrary NEAR function call on behalf of the engine.
+///
+/// Only EVM addresses which were explicitly allowed by the engine owner
+/// (see `KeyPrefix::CrossContractCallPermission`) may use this precompile,
+/// because the promise is executed with the engine as predecessor and may
+/// attach NEAR from the engine's balance.
+pub struct CrossContractCall;
+
+impl CrossContractCall {
+    /// NEAR cross-contract call precompile address
+    ///
+    /// Address: `0x516cded1d16af10cad47d6d49128e2eb7d27b372`
+    /// This address is computed as: `&keccak("nearCrossContractCall")[12..]`
+    pub const ADDRESS: Address =
+        super::make_address(0x516cded1, 0xd16af10cad47d6d49128e2eb7d27b372);
+}
+
+#[cfg(feature = "contract")]
+fn parse_promise(input: &[u8]) -> Result<PromiseCreateArgs, ExitError> {
+    let promise = PromiseCreateArgs::try_from_slice(input)
+        .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_PROMISE")))?;
+
+    // Promises originating from the engine itself would bypass
+    // `assert_private_call` and the owner checks.
+    if promise.target_account_id.as_bytes() == sdk::current_account_id().as_slice() {
+        return Err(ExitError::Other(Cow::from("ERR_XCC_TARGET_IS_ENGINE")));
+    }
+    if promise.method.is_empty() {
+        return Err(ExitError::Other(Cow::from("ERR_XCC_INVALID_METHOD")));
+    }
+
+    Ok(promise)
+}
+
+#[cfg(feature = "contract")]
+fn has_permission(address: &Address) -> bool {
+    sdk::storage_has_key(&address_to_key(
+        KeyPrefix::CrossContractCallPermission,
+        address,
+    ))
+}
+
+impl Precompile for CrossContractCall {
+    fn required_gas(input: &[u8]) -> Result<u64, ExitError> {
+        (input.len() as u64)
+            .checked_mul(costs::CROSS_CONTRACT_CALL_BYTE)
+            .and_then(|cost| cost.checked_add(costs::CROSS_CONTRACT_CALL_BASE))
+            .ok_or(ExitError::OutOfGas)
+    }
+
+    #[cfg(not(feature = "contract"))]
+    fn run(
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        if let Some(target_gas) = target_gas {
+            if Self::required_gas(input)? > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        Ok(PrecompileOutput::default().into())
+    }
+
+    #[cfg(feature = "contract")]
+    fn run(
+        input: &[u8],
+        target_gas: Option<u64>,
+        context: &Context,
+        is_static: bool,
+    ) -> EvmPrecompileResult {
+        // It's not allowed to schedule promises in static mode
+        if is_static {
+            return Err(ExitError::Other(Cow::from("ERR_INVALID_IN_STATIC")));
+        }
+
+        if context.apparent_value != U256::zero() {
+            return Err(ExitError::Other(Cow::from("ERR_ETH_ATTACHED_FOR_XCC")));
+        }
+
+        if !has_permission(&context.caller) {
+            return Err(ExitError::Other(Cow::from("ERR_XCC_NOT_ALLOWED")));
+        }
+
+        // First byte of the input is a flag, selecting the kind of promise to create:
+        //      0x0 -> Single function call
+        //      0x1 -> Function call followed by a callback into the EVM
+        let (flag, args) = input
+            .split_first()
+            .ok_or_else(|| ExitError::Other(Cow::from("ERR_INVALID_FLAG")))?;
+
+        let (promise, attached_gas, callback_gas_limit) = match *flag {
+            0x0 => {
+                // Input slice format:
+                //      promise (borsh-serialized `PromiseCreateArgs`) - the NEAR call to schedule
+                let promise = parse_promise(args)?;
+                let attached_gas = promise.attached_gas;
+                (PromiseArgs::Create(promise), attached_gas, 0)
+            }
+            0x1 => {
+                // Input slice format:
+                //      callback_address (20 bytes) - the EVM contract receiving the result
+                //      selector (4 bytes) - the callback function, taking `(uint8 status, bytes result)`
+                //      callback_gas (u64 big-endian bytes) - the NEAR gas attached to the callback
+                //      callback_gas_limit (u64 big-endian bytes) - the EVM gas limit of the callback
+                //      promise (borsh-serialized `PromiseCreateArgs`) - the NEAR call to schedule
+                if args.len() < 40 {
+                    return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+                }
+                let (callback_address, args) = args.split_at(20);
+                let (selector, args) = args.split_at(4);
+                let (callback_gas, args) = args.split_at(8);
+                let (callback_gas_limit, args) = args.split_at(8);
+                // unwraps cannot fail since we checked the length already
+                let callback_gas = u64::from_be_bytes(callback_gas.try_into().unwrap());
+                let callback_gas_limit = u64::from_be_bytes(callback_gas_limit.try_into().unwrap());
+                // The NEAR gas of the callback must cover its EVM gas, otherwise it
+                // could run out of NEAR gas and the promise result would be lost.
+                let required_callback_gas = callback_gas_limit
+                    .checked_mul(costs::CROSS_CONTRACT_CALL_NEAR_GAS)
+                    .and_then(|gas| gas.checked_add(costs::EVM_CALLBACK_BASE_NEAR_GAS))
+                    .ok_or(ExitError::OutOfGas)?;
+                if callback_gas < required_callback_gas {
+                    return Err(ExitError::Other(Cow::from("ERR_XCC_CALLBACK_GAS_TOO_LOW")));
+                }
+                let base = parse_promise(args)?;
+                let attached_gas = base
+                    .attached_gas
+                    .checked_add(callback_gas)
+                    .ok_or(ExitError::OutOfGas)?;
+                let callback = PromiseCreateArgs {
+                    target_account_id: AccountId::try_from(sdk::current_account_id()).unwrap(),
+                    method: "evm_callback".to_string(),
+                    args: EvmCallbackArgs {
+                        sender: context.caller.0,
+                        callback_address: callback_address.try_into().unwrap(),
+                        selector: selector.try_into().unwrap(),
+                        gas_limit: callback_gas_limit,
+                    }
+                    .try_to_vec()
+                    .unwrap(),
+                    attached_balance: 0,
+                    attached_gas: callback_gas,
+                };
+                (
+                    PromiseArgs::Callback(PromiseWithCallbackArgs { base, callback }),
+                    attached_gas,
+                    callback_gas_limit,
+                )
+            }
+            _ => return Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG"))),
+        };
+
+        // The attached NEAR gas is paid for with EVM gas, on top of the input based
+        // cost, and so is the EVM gas of the callback, which is executed for free.
+        let cost = Self::required_gas(input)?
+            .checked_add(attached_gas / costs::CROSS_CONTRACT_CALL_NEAR_GAS)
+            .and_then(|cost| cost.checked_add(callback_gas_limit))
+            .ok_or(ExitError::OutOfGas)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        let promise_log = Log {
+            address: Self::ADDRESS,
+            topics: Vec::new(),
+            data: promise.try_to_vec().unwrap(),
+        };
+
+        Ok(PrecompileOutput {
+            cost,
+            logs: vec![promise_log],
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CrossContractCall;
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::Precompile;
+
+    #[test]
+    fn test_precompile_id() {
+        assert_eq!(
+            CrossContractCall::ADDRESS,
+            near_account_to_evm_address("nearCrossContractCall".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_required_gas() {
+        assert_eq!(
+            CrossContractCall::required_gas(&[]).unwrap(),
+            super::costs::CROSS_CONTRACT_CALL_BASE
+        );
+        assert_eq!(
+            CrossContractCall::required_gas(&[0u8; 100]).unwrap(),
+            super::costs::CROSS_CONTRACT_CALL_BASE + 100 * super::costs::CROSS_CONTRACT_CALL_BYTE
+        );
+    }
+}
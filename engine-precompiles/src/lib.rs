@@ -6,21 +6,25 @@
 
 pub mod blake2;
 pub mod bn128;
+pub mod cross_contract_call;
 pub mod hash;
 pub mod identity;
 pub mod modexp;
 pub mod native;
 mod prelude;
+pub mod promise_result;
 pub mod secp256k1;
 #[cfg(test)]
 mod utils;
 
 use crate::blake2::Blake2F;
 use crate::bn128::{Bn128Add, Bn128Mul, Bn128Pair};
+use crate::cross_contract_call::CrossContractCall;
 use crate::hash::{RIPEMD160, SHA256};
 use crate::identity::Identity;
 use crate::modexp::ModExp;
 use crate::native::{ExitToEthereum, ExitToNear};
+use crate::promise_result::PromiseResult;
 use crate::secp256k1::ECRecover;
 use evm::backend::Log;
 use evm::{Context, ExitError, ExitSucceed};
@@ -105,6 +109,8 @@ impl Precompiles {
             RIPEMD160::ADDRESS,
             ExitToNear::ADDRESS,
             ExitToEthereum::ADDRESS,
+            CrossContractCall::ADDRESS,
+            PromiseResult::ADDRESS,
         ];
         let fun: prelude::Vec<PrecompileFn> = prelude::vec![
             ECRecover::run,
@@ -112,6 +118,8 @@ impl Precompiles {
             RIPEMD160::run,
             ExitToNear::run,
             ExitToEthereum::run,
+            CrossContractCall::run,
+            PromiseResult::run,
         ];
         let map = addresses.into_iter().zip(fun).collect();
 
@@ -131,6 +139,8 @@ impl Precompiles {
             Bn128Pair::<Byzantium>::ADDRESS,
             ExitToNear::ADDRESS,
             ExitToEthereum::ADDRESS,
+            CrossContractCall::ADDRESS,
+            PromiseResult::ADDRESS,
         ];
         let fun: prelude::Vec<PrecompileFn> = prelude::vec![
             ECRecover::run,
@@ -143,6 +153,8 @@ impl Precompiles {
             Bn128Pair::<Byzantium>::run,
             ExitToNear::run,
             ExitToEthereum::run,
+            CrossContractCall::run,
+            PromiseResult::run,
         ];
         let mut map = prelude::BTreeMap::new();
         for (address, fun) in addresses.into_iter().zip(fun) {
@@ -165,6 +177,8 @@ impl Precompiles {
             Blake2F::ADDRESS,
             ExitToNear::ADDRESS,
             ExitToEthereum::ADDRESS,
+            CrossContractCall::ADDRESS,
+            PromiseResult::ADDRESS,
         ];
         let fun: prelude::Vec<PrecompileFn> = prelude::vec![
             ECRecover::run,
@@ -178,6 +192,8 @@ impl Precompiles {
             Blake2F::run,
             ExitToNear::run,
             ExitToEthereum::run,
+            CrossContractCall::run,
+            PromiseResult::run,
         ];
         let mut map = prelude::BTreeMap::new();
         for (address, fun) in addresses.into_iter().zip(fun) {
@@ -1,10 +1,16 @@
 use super::{EvmPrecompileResult, Precompile};
 #[cfg(feature = "contract")]
 use crate::prelude::{
-    format,
-    parameters::{PromiseCreateArgs, WithdrawCallArgs},
+    json::JsonValue,
+    parameters::{
+        PromiseArgs, PromiseBatchArgs, PromiseBatchWithCallbackArgs, PromiseCreateArgs,
+        PromiseFunctionCallAction, PromiseWithCallbackArgs, RefundCallArgs, WithdrawCallArgs,
+    },
     sdk,
-    storage::{bytes_to_key, KeyPrefix},
+    storage::{
+        bytes_to_key, eth_connector_key, EthConnectorStorageId, KeyPrefix, NATIVE_ERC20_ESCROW_KEY,
+    },
+    types::{u256_to_arr, Balance},
     vec, AccountId, BorshSerialize, Cow, String, ToString, TryFrom, TryInto, Vec, H160, U256,
 };
 
@@ -30,10 +36,60 @@ mod costs {
 
     // TODO(#51): Determine the correct amount of gas
     pub(super) const WITHDRAWAL_GAS: Gas = 100_000_000_000_000;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const REFUND_ON_ERROR_GAS: Gas = 60_000_000_000_000;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const STORAGE_DEPOSIT_GAS: Gas = 10_000_000_000_000;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const FT_TRANSFER_CALL_GAS: Gas = 120_000_000_000_000;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const NFT_TRANSFER_GAS: Gas = 100_000_000_000_000;
+
+    // TODO(#51): Determine the correct amount of gas
+    pub(super) const FT_MINT_GAS: Gas = 100_000_000_000_000;
+}
+
+/// Maximum amount of yoctoNEAR (0.0125 NEAR) the engine attaches to a NEP-145
+/// `storage_deposit` on behalf of an exit. It must be attached by the caller to
+/// the NEAR transaction, since the engine never pays it from its own balance,
+/// and anything above the token's `storage_balance_bounds().min` is kept by the
+/// engine because the deposit is made with `registration_only`.
+const MAX_STORAGE_DEPOSIT: crate::prelude::types::Balance = 12_500_000_000_000_000_000_000;
+
+/// Bits of the flag byte which starts the input of the exit precompiles.
+mod flags {
+    /// Set when ERC-20 tokens are exited, otherwise ETH is exited.
+    pub(super) const ERC20: u8 = 0x1;
+    /// Set when the EVM address to refund in case the NEAR promise fails
+    /// follows the flag (20 bytes). ETH exits are refunded to the caller
+    /// by default, ERC-20 exits are not refunded without it.
+    pub(super) const REFUND: u8 = 0x2;
+    /// Set when the recipient should be registered on the NEP-141 token before
+    /// the transfer. The yoctoNEAR to attach to `storage_deposit` follows the
+    /// refund address (u128 big-endian, 16 bytes), and must be attached to the
+    /// NEAR transaction. Only valid for `ExitToNear`.
+    pub(super) const STORAGE_DEPOSIT: u8 = 0x4;
+    /// Set when the tokens should be sent with `ft_transfer_call`. The `msg`
+    /// and `memo` follow the storage deposit, each prefixed by its length
+    /// (u32 big-endian, 4 bytes); an empty memo is sent as `null`. The amount
+    /// left unused by the receiver is refunded, so ERC-20 exits must carry
+    /// the refund address. Only valid for `ExitToNear`.
+    pub(super) const TRANSFER_CALL: u8 = 0x8;
+    /// Set when an ERC-721 token is exited back to its NEP-171 contract.
+    /// Only `REFUND` may be combined with it. Only valid for `ExitToNear`.
+    pub(super) const ERC721: u8 = 0x10;
+    /// Set when EVM-native ERC-20 tokens locked in the escrow are exited, by
+    /// minting them on their NEP-141 token. Only `REFUND` may be combined with
+    /// it. Only valid for `ExitToNear`.
+    pub(super) const NATIVE_ERC20: u8 = 0x20;
 }
 
 pub mod events {
-    use crate::prelude::{vec, Address, String, ToString, H256, U256};
+    use crate::prelude::{vec, Address, String, ToString, Vec, H256, U256};
 
     /// Derived from event signature (see tests::test_exit_signatures)
     pub const EXIT_TO_NEAR_SIGNATURE: H256 = crate::make_h256(
@@ -45,6 +101,11 @@ pub mod events {
         0xd046c2bb01a5622bc4b9696332391d87,
         0x491373762eeac0831c48400e2d5a5f07,
     );
+    /// Derived from event signature (see tests::test_exit_signatures)
+    pub const EXIT_TO_ETH_WITH_FEE_SIGNATURE: H256 = crate::make_h256(
+        0xd75c94ca1baf08e50dec2ccce1588da0,
+        0x4196bd33a22804f4a15103a35af5dc9c,
+    );
 
     /// The exit precompile events have an `erc20_address` field to indicate
     /// which ERC-20 token is being withdrawn. However, ETH is not an ERC-20 token
@@ -62,6 +123,8 @@ pub mod events {
     /// the ERC-20 contract which calls the exit precompile. However in the case
     /// of ETH exit the sender will give the true sender (and the `erc20_address`
     /// will not be meaningful because ETH is not an ERC-20 token).
+    /// ERC-721 exits use `erc20_address` for the ERC-721 contract and `amount`
+    /// for the id of the exited token.
     pub struct ExitToNear {
         pub sender: Address,
         pub erc20_address: Address,
@@ -114,6 +177,41 @@ pub mod events {
         }
     }
 
+    /// ExitToEthWithFee(
+    ///    Address indexed sender,
+    ///    Address indexed erc20_address,
+    ///    string indexed dest,
+    ///    uint amount,
+    ///    uint fee
+    /// )
+    /// Emitted instead of `ExitToEth` by the ETH exits paying a fee, which is
+    /// the part of `amount` paid to the relayer finalizing the withdrawal on
+    /// Ethereum.
+    pub struct ExitToEthWithFee {
+        pub sender: Address,
+        pub erc20_address: Address,
+        pub dest: Address,
+        pub amount: U256,
+        pub fee: U256,
+    }
+
+    impl ExitToEthWithFee {
+        pub fn encode(self) -> ethabi::RawLog {
+            let data = ethabi::encode(&[
+                ethabi::Token::Int(self.amount),
+                ethabi::Token::Int(self.fee),
+            ]);
+            let topics = vec![
+                EXIT_TO_ETH_WITH_FEE_SIGNATURE,
+                encode_address(self.sender),
+                encode_address(self.erc20_address),
+                encode_address(self.dest),
+            ];
+
+            ethabi::RawLog { topics, data }
+        }
+    }
+
     fn encode_address(a: Address) -> H256 {
         let mut result = [0u8; 32];
         result[12..].copy_from_slice(a.as_ref());
@@ -152,31 +250,49 @@ pub mod events {
     pub fn exit_to_eth_schema() -> ethabi::Event {
         ethabi::Event {
             name: "ExitToEth".to_string(),
-            inputs: vec![
-                ethabi::EventParam {
-                    name: "sender".to_string(),
-                    kind: ethabi::ParamType::Address,
-                    indexed: true,
-                },
-                ethabi::EventParam {
-                    name: "erc20_address".to_string(),
-                    kind: ethabi::ParamType::Address,
-                    indexed: true,
-                },
-                ethabi::EventParam {
-                    name: "dest".to_string(),
-                    kind: ethabi::ParamType::Address,
-                    indexed: true,
-                },
-                ethabi::EventParam {
-                    name: "amount".to_string(),
-                    kind: ethabi::ParamType::Uint(256),
-                    indexed: false,
-                },
-            ],
+            inputs: exit_to_eth_params(),
+            anonymous: false,
+        }
+    }
+
+    pub fn exit_to_eth_with_fee_schema() -> ethabi::Event {
+        let mut inputs = exit_to_eth_params();
+        inputs.push(ethabi::EventParam {
+            name: "fee".to_string(),
+            kind: ethabi::ParamType::Uint(256),
+            indexed: false,
+        });
+        ethabi::Event {
+            name: "ExitToEthWithFee".to_string(),
+            inputs,
             anonymous: false,
         }
     }
+
+    fn exit_to_eth_params() -> Vec<ethabi::EventParam> {
+        vec![
+            ethabi::EventParam {
+                name: "sender".to_string(),
+                kind: ethabi::ParamType::Address,
+                indexed: true,
+            },
+            ethabi::EventParam {
+                name: "erc20_address".to_string(),
+                kind: ethabi::ParamType::Address,
+                indexed: true,
+            },
+            ethabi::EventParam {
+                name: "dest".to_string(),
+                kind: ethabi::ParamType::Address,
+                indexed: true,
+            },
+            ethabi::EventParam {
+                name: "amount".to_string(),
+                kind: ethabi::ParamType::Uint(256),
+                indexed: false,
+            },
+        ]
+    }
 }
 
 pub struct ExitToNear; //TransferEthToNear
@@ -199,6 +315,375 @@ fn get_nep141_from_erc20(erc20_token: &[u8]) -> AccountId {
     .unwrap()
 }
 
+#[cfg(feature = "contract")]
+fn get_nep171_from_erc721(erc721_token: &[u8]) -> AccountId {
+    AccountId::try_from(
+        &sdk::read_storage(bytes_to_key(KeyPrefix::Erc721Nep171Map, erc721_token).as_slice())
+            .expect(ERR_TARGET_TOKEN_NOT_FOUND)[..],
+    )
+    .unwrap()
+}
+
+#[cfg(feature = "contract")]
+fn get_nep141_from_native_erc20(erc20_token: &[u8]) -> Result<AccountId, ExitError> {
+    sdk::read_storage(bytes_to_key(KeyPrefix::NativeErc20Nep141Map, erc20_token).as_slice())
+        .and_then(|nep141| AccountId::try_from(nep141.as_slice()).ok())
+        .ok_or_else(|| ExitError::Other(Cow::from("ERR_NATIVE_ERC20_NOT_FOUND")))
+}
+
+#[cfg(feature = "contract")]
+fn get_native_erc20_escrow() -> Option<Address> {
+    sdk::read_storage(bytes_to_key(KeyPrefix::Config, NATIVE_ERC20_ESCROW_KEY).as_slice())
+        .filter(|escrow| escrow.len() == 20)
+        .map(|escrow| H160::from_slice(&escrow))
+}
+
+/// Returns the NEP-171 id of the bridged token `token_id` of `erc721_token`.
+#[cfg(feature = "contract")]
+fn get_nep171_token_id(erc721_token: &Address, token_id: &U256) -> Result<String, ExitError> {
+    let key = [erc721_token.as_bytes(), &u256_to_arr(token_id)].concat();
+    sdk::read_storage(bytes_to_key(KeyPrefix::Erc721TokenIdMap, &key).as_slice())
+        .and_then(|token_id| String::from_utf8(token_id).ok())
+        .ok_or_else(|| ExitError::Other(Cow::from("ERR_NFT_TOKEN_ID_NOT_FOUND")))
+}
+
+/// Fails if the address was frozen in the eth-connector.
+#[cfg(feature = "contract")]
+fn check_not_frozen(address: &Address) -> Result<(), ExitError> {
+    let key = eth_connector_key(
+        EthConnectorStorageId::FrozenEvmAddresses,
+        address.as_bytes(),
+    );
+    if sdk::storage_has_key(&key) {
+        return Err(ExitError::Other(Cow::from("ERR_ADDRESS_FROZEN")));
+    }
+    Ok(())
+}
+
+/// Fails if either the caller of the exit precompile, or the address the
+/// exit is refunded to, which is the sender of ERC-20 exits, is frozen.
+#[cfg(feature = "contract")]
+fn check_exit_not_frozen(
+    context: &Context,
+    refund_address: Option<Address>,
+) -> Result<(), ExitError> {
+    check_not_frozen(&context.caller)?;
+    match refund_address {
+        Some(refund_address) => check_not_frozen(&refund_address),
+        None => Ok(()),
+    }
+}
+
+#[cfg(feature = "contract")]
+fn parse_flag(input: &[u8], allowed_flags: u8) -> Result<(u8, &[u8]), ExitError> {
+    match input.split_first() {
+        Some((flag, input)) if flag & !allowed_flags == 0 => Ok((*flag, input)),
+        _ => Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG"))),
+    }
+}
+
+#[cfg(feature = "contract")]
+fn parse_refund_address(flag: u8, input: &[u8]) -> Result<(Option<Address>, &[u8]), ExitError> {
+    if flag & flags::REFUND == 0 {
+        return Ok((None, input));
+    }
+    if input.len() < 20 {
+        return Err(ExitError::Other(Cow::from("ERR_INVALID_REFUND_ADDRESS")));
+    }
+    let (refund_address, input) = input.split_at(20);
+    Ok((Some(H160::from_slice(refund_address)), input))
+}
+
+#[cfg(feature = "contract")]
+fn parse_storage_deposit(flag: u8, input: &[u8]) -> Result<(Option<Balance>, &[u8]), ExitError> {
+    if flag & flags::STORAGE_DEPOSIT == 0 {
+        return Ok((None, input));
+    }
+    if input.len() < 16 {
+        return Err(ExitError::Other(Cow::from("ERR_INVALID_STORAGE_DEPOSIT")));
+    }
+    let (amount, input) = input.split_at(16);
+    // unwrap cannot fail since we checked the length already
+    let amount = u128::from_be_bytes(amount.try_into().unwrap());
+    if amount == 0 || amount > MAX_STORAGE_DEPOSIT {
+        return Err(ExitError::Other(Cow::from("ERR_INVALID_STORAGE_DEPOSIT")));
+    }
+    // The total of all the exits of the transaction is checked by the engine
+    if amount > sdk::attached_deposit() {
+        return Err(ExitError::Other(Cow::from(
+            "ERR_STORAGE_DEPOSIT_NOT_ATTACHED",
+        )));
+    }
+    Ok((Some(amount), input))
+}
+
+/// `msg` and `memo` of an exit done with `ft_transfer_call`.
+#[cfg(feature = "contract")]
+struct TransferCallData {
+    msg: String,
+    memo: Option<String>,
+}
+
+#[cfg(feature = "contract")]
+fn parse_transfer_call(
+    flag: u8,
+    input: &[u8],
+) -> Result<(Option<TransferCallData>, &[u8]), ExitError> {
+    if flag & flags::TRANSFER_CALL == 0 {
+        return Ok((None, input));
+    }
+    let (msg, input) = parse_length_prefixed_string(input)?;
+    let (memo, input) = parse_length_prefixed_string(input)?;
+    let memo = if memo.is_empty() { None } else { Some(memo) };
+    Ok((Some(TransferCallData { msg, memo }), input))
+}
+
+#[cfg(feature = "contract")]
+fn parse_length_prefixed_string(input: &[u8]) -> Result<(String, &[u8]), ExitError> {
+    if input.len() < 4 {
+        return Err(ExitError::Other(Cow::from(
+            "ERR_INVALID_TRANSFER_CALL_DATA",
+        )));
+    }
+    let (len, input) = input.split_at(4);
+    // unwrap cannot fail since we checked the length already
+    let len = u32::from_be_bytes(len.try_into().unwrap()) as usize;
+    if input.len() < len {
+        return Err(ExitError::Other(Cow::from(
+            "ERR_INVALID_TRANSFER_CALL_DATA",
+        )));
+    }
+    let (value, input) = input.split_at(len);
+    let value = String::from_utf8(value.to_vec())
+        .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_TRANSFER_CALL_DATA")))?;
+    Ok((value, input))
+}
+
+/// Builds the JSON arguments of `ft_transfer` or, if `transfer_call` is set,
+/// of `ft_transfer_call`.
+#[cfg(feature = "contract")]
+fn ft_transfer_args(
+    receiver_id: &AccountId,
+    amount: u128,
+    transfer_call: Option<&TransferCallData>,
+) -> Vec<u8> {
+    let mut fields = vec![
+        ("receiver_id", receiver_id.into()),
+        ("amount", amount.into()),
+    ];
+    match transfer_call {
+        None => fields.push(("memo", JsonValue::Null)),
+        Some(data) => {
+            fields.push(("memo", data.memo.as_deref().into()));
+            fields.push(("msg", data.msg.as_str().into()));
+        }
+    }
+    JsonValue::object(fields).to_bytes()
+}
+
+#[cfg(feature = "contract")]
+impl ExitToNear {
+    /// Exits the ERC-721 token burned by the calling contract with
+    /// `nft_transfer` on the NEP-171 contract it is mapped to.
+    ///
+    /// Input slice format:
+    ///      token_id (U256 big-endian bytes) - the id of the token that was burned
+    ///      recipient_account_id (bytes) - the NEAR recipient account which will receive the NEP-171 token
+    fn exit_erc721(
+        refund_address: Option<Address>,
+        input: &[u8],
+        context: &Context,
+    ) -> EvmPrecompileResult {
+        if context.apparent_value != U256::from(0) {
+            return Err(ExitError::Other(Cow::from(
+                "ERR_ETH_ATTACHED_FOR_ERC721_EXIT",
+            )));
+        }
+        if input.len() < 32 {
+            return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+        }
+
+        let erc721_address = context.caller;
+        let nep171_address = get_nep171_from_erc721(erc721_address.as_bytes());
+
+        let (token_id, input) = input.split_at(32);
+        let token_id = U256::from_big_endian(token_id);
+        let nep171_token_id = get_nep171_token_id(&erc721_address, &token_id)?;
+        let receiver_account_id = AccountId::try_from(input)
+            .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_RECEIVER_ACCOUNT_ID")))?;
+
+        let transfer_promise = PromiseCreateArgs {
+            target_account_id: nep171_address,
+            method: "nft_transfer".to_string(),
+            args: JsonValue::object(vec![
+                ("receiver_id", (&receiver_account_id).into()),
+                ("token_id", nep171_token_id.into()),
+                ("approval_id", JsonValue::Null),
+                ("memo", JsonValue::Null),
+            ])
+            .to_bytes(),
+            attached_balance: 1,
+            attached_gas: costs::NFT_TRANSFER_GAS,
+        };
+        // The refund mints the token back, the same way it mints ERC-20 tokens.
+        let refund = refund_address.map(|refund_address| RefundCallArgs {
+            recipient_address: refund_address.0,
+            erc20_address: Some(erc721_address.0),
+            exit_address: Self::ADDRESS.0,
+            amount: u256_to_arr(&token_id),
+            transfer_call: false,
+            native_erc20: false,
+        });
+        let promise = with_refund(transfer_promise, refund);
+
+        let promise_log = Log {
+            address: Self::ADDRESS,
+            topics: Vec::new(),
+            data: promise.try_to_vec().unwrap(),
+        };
+        let exit_event_log = events::ExitToNear {
+            sender: erc721_address,
+            erc20_address: erc721_address,
+            dest: receiver_account_id.to_string(),
+            amount: token_id,
+        }
+        .encode();
+        let exit_event_log = Log {
+            address: Self::ADDRESS,
+            topics: exit_event_log.topics,
+            data: exit_event_log.data,
+        };
+
+        Ok(PrecompileOutput {
+            logs: vec![promise_log, exit_event_log],
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+#[cfg(feature = "contract")]
+impl ExitToNear {
+    /// Exits the EVM-native ERC-20 tokens locked by the escrow contract, the
+    /// only allowed caller, by minting them on the NEP-141 token they are
+    /// mapped to.
+    ///
+    /// Input slice format:
+    ///      erc20_address (20 bytes) - the EVM-native ERC-20 token that was locked
+    ///      amount (U256 big-endian bytes) - the amount that was locked
+    ///      recipient_account_id (bytes) - the NEAR recipient account which will receive NEP-141 tokens
+    fn exit_native_erc20(
+        refund_address: Option<Address>,
+        input: &[u8],
+        context: &Context,
+    ) -> EvmPrecompileResult {
+        if context.apparent_value != U256::from(0) {
+            return Err(ExitError::Other(Cow::from(
+                "ERR_ETH_ATTACHED_FOR_ERC20_EXIT",
+            )));
+        }
+        if get_native_erc20_escrow() != Some(context.caller) {
+            return Err(ExitError::Other(Cow::from("ERR_NOT_NATIVE_ERC20_ESCROW")));
+        }
+        if input.len() < 52 {
+            return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT")));
+        }
+
+        let (erc20_address, input) = input.split_at(20);
+        let erc20_address = H160::from_slice(erc20_address);
+        let nep141_address = get_nep141_from_native_erc20(erc20_address.as_bytes())?;
+        let (amount, input) = input.split_at(32);
+        let amount = U256::from_big_endian(amount);
+        if amount > U256::from(u128::MAX) {
+            return Err(ExitError::Other(Cow::from("ERR_INVALID_AMOUNT")));
+        }
+        let receiver_account_id = AccountId::try_from(input)
+            .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_RECEIVER_ACCOUNT_ID")))?;
+
+        let mint_promise = PromiseCreateArgs {
+            target_account_id: nep141_address,
+            method: "mint".to_string(),
+            args: JsonValue::object(vec![
+                ("account_id", (&receiver_account_id).into()),
+                ("amount", amount.as_u128().into()),
+            ])
+            .to_bytes(),
+            attached_balance: 0,
+            attached_gas: costs::FT_MINT_GAS,
+        };
+        // The refund unlocks the tokens from the escrow.
+        let refund = refund_address.map(|refund_address| RefundCallArgs {
+            recipient_address: refund_address.0,
+            erc20_address: Some(erc20_address.0),
+            exit_address: Self::ADDRESS.0,
+            amount: u256_to_arr(&amount),
+            transfer_call: false,
+            native_erc20: true,
+        });
+        let promise = with_refund(mint_promise, refund);
+
+        let promise_log = Log {
+            address: Self::ADDRESS,
+            topics: Vec::new(),
+            data: promise.try_to_vec().unwrap(),
+        };
+        let exit_event_log = events::ExitToNear {
+            sender: refund_address.unwrap_or(context.caller),
+            erc20_address,
+            dest: receiver_account_id.to_string(),
+            amount,
+        }
+        .encode();
+        let exit_event_log = Log {
+            address: Self::ADDRESS,
+            topics: exit_event_log.topics,
+            data: exit_event_log.data,
+        };
+
+        Ok(PrecompileOutput {
+            logs: vec![promise_log, exit_event_log],
+            ..Default::default()
+        }
+        .into())
+    }
+}
+
+#[cfg(feature = "contract")]
+fn refund_callback(refund: RefundCallArgs) -> PromiseCreateArgs {
+    PromiseCreateArgs {
+        target_account_id: AccountId::try_from(sdk::current_account_id()).unwrap(),
+        method: "refund_on_error".to_string(),
+        args: refund.try_to_vec().unwrap(),
+        attached_balance: 0,
+        attached_gas: costs::REFUND_ON_ERROR_GAS,
+    }
+}
+
+/// Chains the private `refund_on_error` engine method to the exit promise, if
+/// there is anybody to refund.
+#[cfg(feature = "contract")]
+fn with_refund(base: PromiseCreateArgs, refund: Option<RefundCallArgs>) -> PromiseArgs {
+    match refund {
+        Some(refund) => PromiseArgs::Callback(PromiseWithCallbackArgs {
+            base,
+            callback: refund_callback(refund),
+        }),
+        None => PromiseArgs::Create(base),
+    }
+}
+
+/// Same as `with_refund`, for a batch of function calls.
+#[cfg(feature = "contract")]
+fn with_refund_batch(base: PromiseBatchArgs, refund: Option<RefundCallArgs>) -> PromiseArgs {
+    match refund {
+        Some(refund) => PromiseArgs::BatchCallback(PromiseBatchWithCallbackArgs {
+            base,
+            callback: refund_callback(refund),
+        }),
+        None => PromiseArgs::Batch(base),
+    }
+}
+
 impl Precompile for ExitToNear {
     fn required_gas(_input: &[u8]) -> Result<u64, ExitError> {
         Ok(costs::EXIT_TO_NEAR_GAS)
@@ -241,11 +726,48 @@ impl Precompile for ExitToNear {
         // First byte of the input is a flag, selecting the behavior to be triggered:
         //      0x0 -> Eth transfer
         //      0x1 -> Erc20 transfer
-        let mut input = input;
-        let flag = input[0];
-        input = &input[1..];
+        // The `flags::REFUND` bit (0x2) may be set on top of it, in which case
+        // the address to refund on failure follows the flag (20 bytes), as well
+        // as the `flags::STORAGE_DEPOSIT` bit (0x4), in which case the amount to
+        // deposit for the recipient's storage follows (16 bytes), as well as
+        // the `flags::TRANSFER_CALL` bit (0x8), in which case the tokens are
+        // sent with `ft_transfer_call` using the `msg` and `memo` that follow.
+        // ERC-721 tokens are exited instead when the `flags::ERC721` bit (0x10)
+        // is set, which may only be combined with `flags::REFUND`, and so are
+        // EVM-native ERC-20 tokens when the `flags::NATIVE_ERC20` bit (0x20) is.
+        let (flag, input) = parse_flag(
+            input,
+            flags::ERC20
+                | flags::REFUND
+                | flags::STORAGE_DEPOSIT
+                | flags::TRANSFER_CALL
+                | flags::ERC721
+                | flags::NATIVE_ERC20,
+        )?;
+        let (refund_address, input) = parse_refund_address(flag, input)?;
+        check_exit_not_frozen(context, refund_address)?;
+        if flag & flags::ERC721 != 0 {
+            if flag & !(flags::ERC721 | flags::REFUND) != 0 {
+                return Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG")));
+            }
+            return Self::exit_erc721(refund_address, input, context);
+        }
+        if flag & flags::NATIVE_ERC20 != 0 {
+            if flag & !(flags::NATIVE_ERC20 | flags::REFUND) != 0 {
+                return Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG")));
+            }
+            return Self::exit_native_erc20(refund_address, input, context);
+        }
+        let (storage_deposit, input) = parse_storage_deposit(flag, input)?;
+        let (transfer_call, mut input) = parse_transfer_call(flag, input)?;
+        // Unlike exited ETH, burned ERC-20 tokens have no sender to refund by default
+        if transfer_call.is_some() && flag & flags::ERC20 != 0 && refund_address.is_none() {
+            return Err(ExitError::Other(Cow::from(
+                "ERR_TRANSFER_CALL_WITHOUT_REFUND",
+            )));
+        }
 
-        let (nep141_address, args, exit_event) = match flag {
+        let (nep141_address, receiver_id, amount, exit_event, refund) = match flag & flags::ERC20 {
             0x0 => {
                 // ETH transfer
                 //
@@ -255,19 +777,22 @@ impl Precompile for ExitToNear {
                 if let Ok(dest_account) = AccountId::try_from(input) {
                     (
                         AccountId::try_from(sdk::current_account_id()).unwrap(),
-                        // There is no way to inject json, given the encoding of both arguments
-                        // as decimal and valid account id respectively.
-                        format!(
-                            r#"{{"receiver_id": "{}", "amount": "{}", "memo": null}}"#,
-                            dest_account,
-                            context.apparent_value.as_u128()
-                        ),
+                        dest_account.clone(),
+                        context.apparent_value.as_u128(),
                         events::ExitToNear {
                             sender: context.caller,
                             erc20_address: events::ETH_ADDRESS,
                             dest: dest_account.to_string(),
                             amount: context.apparent_value,
                         },
+                        Some(RefundCallArgs {
+                            recipient_address: refund_address.unwrap_or(context.caller).0,
+                            erc20_address: None,
+                            exit_address: Self::ADDRESS.0,
+                            amount: u256_to_arr(&context.apparent_value),
+                            transfer_call: transfer_call.is_some(),
+                            native_erc20: false,
+                        }),
                     )
                 } else {
                     return Err(ExitError::Other(Cow::from(
@@ -275,7 +800,7 @@ impl Precompile for ExitToNear {
                     )));
                 }
             }
-            0x1 => {
+            _ => {
                 // ERC20 transfer
                 //
                 // This precompile branch is expected to be called from the ERC20 burn function\
@@ -299,19 +824,22 @@ impl Precompile for ExitToNear {
                 if let Ok(receiver_account_id) = AccountId::try_from(input) {
                     (
                         nep141_address,
-                        // There is no way to inject json, given the encoding of both arguments
-                        // as decimal and valid account id respectively.
-                        format!(
-                            r#"{{"receiver_id": "{}", "amount": "{}", "memo": null}}"#,
-                            receiver_account_id,
-                            amount.as_u128()
-                        ),
+                        receiver_account_id.clone(),
+                        amount.as_u128(),
                         events::ExitToNear {
                             sender: erc20_address,
                             erc20_address,
                             dest: receiver_account_id.to_string(),
                             amount,
                         },
+                        refund_address.map(|refund_address| RefundCallArgs {
+                            recipient_address: refund_address.0,
+                            erc20_address: Some(erc20_address.0),
+                            exit_address: Self::ADDRESS.0,
+                            amount: u256_to_arr(&amount),
+                            transfer_call: transfer_call.is_some(),
+                            native_erc20: false,
+                        }),
                     )
                 } else {
                     return Err(ExitError::Other(Cow::from(
@@ -319,18 +847,51 @@ impl Precompile for ExitToNear {
                     )));
                 }
             }
-            _ => return Err(ExitError::Other(Cow::from("ERR_INVALID_FLAG"))),
         };
 
-        let promise: Vec<u8> = PromiseCreateArgs {
-            target_account_id: nep141_address,
-            method: "ft_transfer".to_string(),
-            args: args.as_bytes().to_vec(),
-            attached_balance: 1,
-            attached_gas: costs::FT_TRANSFER_GAS,
-        }
-        .try_to_vec()
-        .unwrap();
+        let args = ft_transfer_args(&receiver_id, amount, transfer_call.as_ref());
+        let (method, attached_gas) = match transfer_call {
+            None => ("ft_transfer", costs::FT_TRANSFER_GAS),
+            Some(_) => ("ft_transfer_call", costs::FT_TRANSFER_CALL_GAS),
+        };
+
+        let promise = match storage_deposit {
+            None => with_refund(
+                PromiseCreateArgs {
+                    target_account_id: nep141_address,
+                    method: method.to_string(),
+                    args,
+                    attached_balance: 1,
+                    attached_gas,
+                },
+                refund,
+            ),
+            Some(storage_deposit) => with_refund_batch(
+                PromiseBatchArgs {
+                    target_account_id: nep141_address,
+                    actions: vec![
+                        PromiseFunctionCallAction {
+                            method: "storage_deposit".to_string(),
+                            args: JsonValue::object(vec![
+                                ("account_id", (&receiver_id).into()),
+                                ("registration_only", true.into()),
+                            ])
+                            .to_bytes(),
+                            attached_balance: storage_deposit,
+                            attached_gas: costs::STORAGE_DEPOSIT_GAS,
+                        },
+                        PromiseFunctionCallAction {
+                            method: method.to_string(),
+                            args,
+                            attached_balance: 1,
+                            attached_gas,
+                        },
+                    ],
+                },
+                refund,
+            ),
+        };
+        let promise: Vec<u8> = promise.try_to_vec().unwrap();
         let promise_log = Log {
             address: Self::ADDRESS,
             topics: Vec::new(),
@@ -404,19 +965,35 @@ impl Precompile for ExitToEthereum {
         // First byte of the input is a flag, selecting the behavior to be triggered:
         //      0x0 -> Eth transfer
         //      0x1 -> Erc20 transfer
-        let mut input = input;
-        let flag = input[0];
-        input = &input[1..];
+        // The `flags::REFUND` bit (0x2) may be set on top of it, in which case
+        // the address to refund on failure follows the flag (20 bytes).
+        let (flag, input) = parse_flag(input, flags::ERC20 | flags::REFUND)?;
+        let (refund_address, mut input) = parse_refund_address(flag, input)?;
+        check_exit_not_frozen(context, refund_address)?;
 
-        let (nep141_address, serialized_args, exit_event) = match flag {
+        let (nep141_address, serialized_args, exit_event, refund) = match flag & flags::ERC20 {
             0x0 => {
                 // ETH transfer
                 //
                 // Input slice format:
                 //      eth_recipient (20 bytes) - the address of recipient which will receive ETH on Ethereum
-                let recipient_address = input
-                    .try_into()
-                    .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_RECIPIENT_ADDRESS")))?;
+                //      fee (optional, U256 big-endian bytes) - the part of the amount paid to the
+                //          relayer which finalizes the withdrawal on Ethereum
+                if input.len() != 20 && input.len() != 52 {
+                    return Err(ExitError::Other(Cow::from("ERR_INVALID_RECIPIENT_ADDRESS")));
+                }
+                let (recipient_address, fee) = input.split_at(20);
+                // unwrap cannot fail since we checked the length already
+                let recipient_address = recipient_address.try_into().unwrap();
+                let fee = if fee.is_empty() {
+                    None
+                } else {
+                    let fee = U256::from_big_endian(fee);
+                    if fee >= context.apparent_value {
+                        return Err(ExitError::Other(Cow::from("ERR_INVALID_FEE")));
+                    }
+                    Some(fee.as_u128())
+                };
                 (
                     AccountId::try_from(sdk::current_account_id()).unwrap(),
                     // There is no way to inject json, given the encoding of both arguments
@@ -424,18 +1001,39 @@ impl Precompile for ExitToEthereum {
                     WithdrawCallArgs {
                         recipient_address,
                         amount: context.apparent_value.as_u128(),
+                        fee,
+                        sender: Some(context.caller.0),
                     }
                     .try_to_vec()
                     .map_err(|_| ExitError::Other(Cow::from("ERR_INVALID_AMOUNT")))?,
-                    events::ExitToEth {
-                        sender: context.caller,
-                        erc20_address: events::ETH_ADDRESS,
-                        dest: H160(recipient_address),
-                        amount: context.apparent_value,
+                    match fee {
+                        None => events::ExitToEth {
+                            sender: context.caller,
+                            erc20_address: events::ETH_ADDRESS,
+                            dest: H160(recipient_address),
+                            amount: context.apparent_value,
+                        }
+                        .encode(),
+                        Some(fee) => events::ExitToEthWithFee {
+                            sender: context.caller,
+                            erc20_address: events::ETH_ADDRESS,
+                            dest: H160(recipient_address),
+                            amount: context.apparent_value,
+                            fee: U256::from(fee),
+                        }
+                        .encode(),
                     },
+                    Some(RefundCallArgs {
+                        recipient_address: refund_address.unwrap_or(context.caller).0,
+                        erc20_address: None,
+                        exit_address: Self::ADDRESS.0,
+                        amount: u256_to_arr(&context.apparent_value),
+                        transfer_call: false,
+                        native_erc20: false,
+                    }),
                 )
             }
-            0x1 => {
+            _ => {
                 // ERC-20 transfer
                 //
                 // This precompile branch is expected to be called from the ERC20 withdraw function
@@ -465,48 +1063,46 @@ impl Precompile for ExitToEthereum {
 
                     (
                         nep141_address,
-                        // There is no way to inject json, given the encoding of both arguments
-                        // as decimal and hexadecimal respectively.
-                        format!(
-                            r#"{{"amount": "{}", "recipient": "{}"}}"#,
-                            amount.as_u128(),
-                            eth_recipient
-                        )
-                        .as_bytes()
-                        .to_vec(),
+                        JsonValue::object(vec![
+                            ("amount", amount.as_u128().into()),
+                            ("recipient", eth_recipient.into()),
+                        ])
+                        .to_bytes(),
                         events::ExitToEth {
                             sender: erc20_address,
                             erc20_address,
                             dest: H160(recipient_address),
                             amount,
-                        },
+                        }
+                        .encode(),
+                        refund_address.map(|refund_address| RefundCallArgs {
+                            recipient_address: refund_address.0,
+                            erc20_address: Some(erc20_address.0),
+                            exit_address: Self::ADDRESS.0,
+                            amount: u256_to_arr(&amount),
+                            transfer_call: false,
+                            native_erc20: false,
+                        }),
                     )
                 } else {
                     return Err(ExitError::Other(Cow::from("ERR_INVALID_RECIPIENT_ADDRESS")));
                 }
             }
-            _ => {
-                return Err(ExitError::Other(Cow::from(
-                    "ERR_INVALID_RECEIVER_ACCOUNT_ID",
-                )));
-            }
         };
 
-        let promise = PromiseCreateArgs {
+        let withdraw_promise = PromiseCreateArgs {
             target_account_id: nep141_address,
             method: "withdraw".to_string(),
             args: serialized_args,
             attached_balance: 1,
             attached_gas: costs::WITHDRAWAL_GAS,
-        }
-        .try_to_vec()
-        .unwrap();
+        };
+        let promise = with_refund(withdraw_promise, refund).try_to_vec().unwrap();
         let promise_log = Log {
             address: Self::ADDRESS,
             topics: Vec::new(),
             data: promise,
         };
-        let exit_event_log = exit_event.encode();
         let exit_event_log = Log {
             address: Self::ADDRESS,
             topics: exit_event_log.topics,
@@ -542,6 +1138,7 @@ mod tests {
     fn test_exit_signatures() {
         let exit_to_near = super::events::exit_to_near_schema();
         let exit_to_eth = super::events::exit_to_eth_schema();
+        let exit_to_eth_with_fee = super::events::exit_to_eth_with_fee_schema();
 
         assert_eq!(
             exit_to_near.signature(),
@@ -551,5 +1148,9 @@ mod tests {
             exit_to_eth.signature(),
             super::events::EXIT_TO_ETH_SIGNATURE
         );
+        assert_eq!(
+            exit_to_eth_with_fee.signature(),
+            super::events::EXIT_TO_ETH_WITH_FEE_SIGNATURE
+        );
     }
 }
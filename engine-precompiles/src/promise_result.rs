@@ -0,0 +1,155 @@
+use super::{EvmPrecompileResult, Precompile};
+#[cfg(feature = "contract")]
+use crate::prelude::{sdk, Cow};
+use crate::prelude::{types, Address, Vec, U256};
+use crate::PrecompileOutput;
+use evm::{Context, ExitError};
+
+mod costs {
+    use crate::prelude::types::Gas;
+
+    /// Base EVM gas cost of reading a promise result.
+    pub(super) const PROMISE_RESULT_BASE: Gas = 125;
+
+    /// Additional EVM gas cost per byte of the returned promise result.
+    pub(super) const PROMISE_RESULT_BYTE: Gas = 1;
+}
+
+/// Status codes of a promise result, as seen by EVM contracts.
+pub const STATUS_NOT_READY: u8 = 0;
+pub const STATUS_SUCCESSFUL: u8 = 1;
+pub const STATUS_FAILED: u8 = 2;
+
+/// ABI-encodes a promise result as `(uint8 status, bytes result)`.
+pub fn abi_encode(result: types::PromiseResult) -> Vec<u8> {
+    let (status, data) = match result {
+        types::PromiseResult::NotReady => (STATUS_NOT_READY, Vec::new()),
+        types::PromiseResult::Successful(data) => (STATUS_SUCCESSFUL, data),
+        types::PromiseResult::Failed => (STATUS_FAILED, Vec::new()),
+    };
+    ethabi::encode(&[
+        ethabi::Token::Uint(U256::from(status)),
+        ethabi::Token::Bytes(data),
+    ])
+}
+
+/// Gives EVM contracts access to the results of the promises the current
+/// NEAR call depends on (for example within an EVM callback).
+///
+/// Input format:
+///      empty -> returns the number of promise results as `uint256`
+///      index (uint256) -> returns the result at `index` as `(uint8 status, bytes result)`
+pub struct PromiseResult;
+
+impl PromiseResult {
+    /// Promise result precompile address
+    ///
+    /// Address: `0x719e1ba5bde14eb12516757d2409fad9bf8fe050`
+    /// This address is computed as: `&keccak("promiseResult")[12..]`
+    pub const ADDRESS: Address =
+        super::make_address(0x719e1ba5, 0xbde14eb12516757d2409fad9bf8fe050);
+}
+
+impl Precompile for PromiseResult {
+    fn required_gas(_input: &[u8]) -> Result<u64, ExitError> {
+        Ok(costs::PROMISE_RESULT_BASE)
+    }
+
+    #[cfg(not(feature = "contract"))]
+    fn run(
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        if let Some(target_gas) = target_gas {
+            if Self::required_gas(input)? > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        Ok(PrecompileOutput::default().into())
+    }
+
+    #[cfg(feature = "contract")]
+    fn run(
+        input: &[u8],
+        target_gas: Option<u64>,
+        _context: &Context,
+        _is_static: bool,
+    ) -> EvmPrecompileResult {
+        let output = match input.len() {
+            0 => {
+                let mut count = [0u8; 32];
+                U256::from(sdk::promise_results_count()).to_big_endian(&mut count);
+                count.to_vec()
+            }
+            32 => {
+                let index = U256::from_big_endian(input);
+                if index >= U256::from(sdk::promise_results_count()) {
+                    return Err(ExitError::Other(Cow::from("ERR_PROMISE_INDEX")));
+                }
+                abi_encode(sdk::promise_result(index.as_u64()))
+            }
+            _ => return Err(ExitError::Other(Cow::from("ERR_INVALID_INPUT"))),
+        };
+
+        let cost = (output.len() as u64)
+            .checked_mul(costs::PROMISE_RESULT_BYTE)
+            .and_then(|cost| cost.checked_add(Self::required_gas(input)?))
+            .ok_or(ExitError::OutOfGas)?;
+        if let Some(target_gas) = target_gas {
+            if cost > target_gas {
+                return Err(ExitError::OutOfGas);
+            }
+        }
+
+        Ok(PrecompileOutput::without_logs(cost, output).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PromiseResult;
+    use crate::prelude::sdk::types::near_account_to_evm_address;
+    use crate::prelude::{types, vec, U256};
+
+    #[test]
+    fn test_precompile_id() {
+        assert_eq!(
+            PromiseResult::ADDRESS,
+            near_account_to_evm_address("promiseResult".as_bytes())
+        );
+    }
+
+    #[test]
+    fn test_abi_encode() {
+        let encoded = super::abi_encode(types::PromiseResult::Successful(vec![1, 2, 3]));
+        let decoded = ethabi::decode(
+            &[ethabi::ParamType::Uint(8), ethabi::ParamType::Bytes],
+            &encoded,
+        )
+        .unwrap();
+        assert_eq!(
+            decoded,
+            vec![
+                ethabi::Token::Uint(U256::from(super::STATUS_SUCCESSFUL)),
+                ethabi::Token::Bytes(vec![1, 2, 3]),
+            ]
+        );
+
+        let encoded = super::abi_encode(types::PromiseResult::Failed);
+        let decoded = ethabi::decode(
+            &[ethabi::ParamType::Uint(8), ethabi::ParamType::Bytes],
+            &encoded,
+        )
+        .unwrap();
+        assert_eq!(
+            decoded,
+            vec![
+                ethabi::Token::Uint(U256::from(super::STATUS_FAILED)),
+                ethabi::Token::Bytes(vec![]),
+            ]
+        );
+    }
+}
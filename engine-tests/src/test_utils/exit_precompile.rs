@@ -127,6 +127,21 @@ impl Tester {
         )
     }
 
+    pub fn withdraw_and_call(
+        &self,
+        runner: &mut AuroraRunner,
+        signer: &mut Signer,
+        message: &str,
+    ) -> Result<SubmitResult, Revert> {
+        self.call_function(
+            runner,
+            signer,
+            "withdrawAndCall",
+            Wei::zero(),
+            &[ethabi::Token::String(message.to_string())],
+        )
+    }
+
     pub fn withdraw_and_fail(
         &self,
         runner: &mut AuroraRunner,
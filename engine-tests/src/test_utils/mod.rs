@@ -5,7 +5,7 @@ use near_primitives_core::contract::ContractCode;
 use near_primitives_core::profile::ProfileData;
 use near_primitives_core::runtime::fees::RuntimeFeesConfig;
 use near_vm_logic::mocks::mock_external::MockedExternal;
-use near_vm_logic::types::ReturnData;
+use near_vm_logic::types::{PromiseResult, ReturnData};
 use near_vm_logic::{VMContext, VMOutcome};
 use near_vm_runner::{MockCompiledContractCache, VMError};
 use rlp::RlpStream;
@@ -76,6 +76,8 @@ pub(crate) struct AuroraRunner {
     pub fees_config: RuntimeFeesConfig,
     pub current_protocol_version: u32,
     pub previous_logs: Vec<String>,
+    /// Results of the promises the calls are made as a callback of
+    pub promise_results: Vec<PromiseResult>,
 }
 
 /// Same as `AuroraRunner`, but consumes `self` on execution (thus preventing building on
@@ -122,7 +124,7 @@ impl<'a> OneShotAuroraRunner<'a> {
             self.context.clone(),
             &self.base.wasm_config,
             &self.base.fees_config,
-            &[],
+            &self.base.promise_results,
             self.base.current_protocol_version,
             Some(&self.base.cache),
         )
@@ -181,7 +183,7 @@ impl AuroraRunner {
             self.context.clone(),
             &self.wasm_config,
             &self.fees_config,
-            &[],
+            &self.promise_results,
             self.current_protocol_version,
             Some(&self.cache),
         );
@@ -406,6 +408,7 @@ impl Default for AuroraRunner {
             fees_config: RuntimeFeesConfig::test(),
             current_protocol_version: u32::MAX,
             previous_logs: Default::default(),
+            promise_results: Vec::new(),
         }
     }
 }
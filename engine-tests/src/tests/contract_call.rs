@@ -1,9 +1,18 @@
-use crate::prelude::{parameters::SubmitResult, vec, Address, Wei, H256, U256};
+use crate::prelude::{
+    parameters::SubmitResult, u256_to_arr, vec, Address, RefundCallArgs, Wei, H256, U256,
+};
 use crate::test_utils::{origin, AuroraRunner, Signer};
+use aurora_engine::parameters::{FreezeArgs, FreezeTarget};
+use aurora_engine::transaction::LegacyEthTransaction;
+use aurora_engine_precompiles::native::{ExitToEthereum, ExitToNear};
+use borsh::BorshSerialize;
+use near_vm_logic::types::PromiseResult;
 
 use crate::test_utils;
 use crate::test_utils::exit_precompile::{Tester, TesterConstructor, DEST_ACCOUNT, DEST_ADDRESS};
 
+const REFUND_CALLBACK: &str = "Callback contract: aurora.refund_on_error";
+
 fn setup_test() -> (AuroraRunner, Signer, [u8; 20], Tester) {
     let mut runner = AuroraRunner::new();
     let token = runner.deploy_erc20_token(&"tt.testnet".to_string());
@@ -111,9 +120,207 @@ fn withdraw() {
 
         // One promise is scheduled
         assert!(runner.previous_logs.contains(&expected.to_string()));
+        // The promise refunds the sender if it fails
+        assert!(runner.previous_logs.contains(&REFUND_CALLBACK.to_string()));
     }
 }
 
+#[test]
+fn refund_on_error_is_private() {
+    let (mut runner, _signer, token, tester) = setup_test();
+    let args = RefundCallArgs {
+        recipient_address: tester.contract.address.0,
+        erc20_address: Some(token),
+        exit_address: ExitToNear::ADDRESS.0,
+        amount: u256_to_arr(&U256::one()),
+        transfer_call: false,
+        native_erc20: false,
+    };
+
+    let (_, maybe_error) = runner.call(
+        "refund_on_error",
+        "some-account.near",
+        args.try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_some());
+}
+
+#[test]
+fn refund_failed_eth_exit() {
+    let (mut runner, mut signer, _token, _tester) = setup_test();
+    let sender = test_utils::address_from_secret_key(&signer.secret_key);
+    let sender_balance = runner.get_balance(sender);
+    let exit_amount = Wei::new_u64(10);
+
+    let input = [&[0x0][..], DEST_ACCOUNT.as_bytes()].concat();
+    let result = runner
+        .submit_with_signer(&mut signer, |nonce| LegacyEthTransaction {
+            nonce,
+            gas_price: U256::zero(),
+            gas: u64::MAX.into(),
+            to: Some(ExitToNear::ADDRESS),
+            value: exit_amount,
+            data: input,
+        })
+        .unwrap();
+    test_utils::unwrap_success(result);
+    assert_eq!(runner.get_balance(sender), sender_balance - exit_amount);
+    assert_eq!(runner.get_balance(ExitToNear::ADDRESS), exit_amount);
+
+    // The `ft_transfer` promise of the exit failed
+    runner.promise_results = vec![PromiseResult::Failed];
+    let args = RefundCallArgs {
+        recipient_address: sender.0,
+        erc20_address: None,
+        exit_address: ExitToNear::ADDRESS.0,
+        amount: u256_to_arr(&exit_amount.raw()),
+        transfer_call: false,
+        native_erc20: false,
+    };
+    let aurora = runner.aurora_account_id.clone();
+    let (_, maybe_error) = runner.call("refund_on_error", &aurora, args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+
+    // The exited ETH is moved back to the sender
+    assert_eq!(runner.get_balance(sender), sender_balance);
+    assert_eq!(runner.get_balance(ExitToNear::ADDRESS), Wei::zero());
+    let event = runner
+        .previous_logs
+        .iter()
+        .find_map(|log| log.strip_prefix("EVENT_JSON:"))
+        .map(|json| serde_json::from_str::<serde_json::Value>(json).unwrap())
+        .unwrap();
+    assert_eq!(event["event"], "exit_refunded");
+    assert_eq!(event["data"][0]["recipient"], hex::encode(sender));
+    assert_eq!(event["data"][0]["erc20_address"], serde_json::Value::Null);
+    assert_eq!(event["data"][0]["amount"], "10");
+}
+
+#[test]
+fn withdraw_eth_with_storage_deposit() {
+    let (mut runner, mut signer, _token, _tester) = setup_test();
+    let storage_deposit: u128 = 1_250_000_000_000_000_000_000;
+
+    let input = [
+        &[0x4][..],
+        &storage_deposit.to_be_bytes(),
+        DEST_ACCOUNT.as_bytes(),
+    ]
+    .concat();
+    let make_tx = |nonce| LegacyEthTransaction {
+        nonce,
+        gas_price: U256::zero(),
+        gas: u64::MAX.into(),
+        to: Some(ExitToNear::ADDRESS),
+        value: Wei::new_u64(10),
+        data: input.clone(),
+    };
+
+    // The storage deposit is not paid by the engine
+    let result = runner.submit_with_signer(&mut signer, make_tx);
+    assert!(result.is_err());
+    // The nonce of the failed transaction was not used
+    signer.nonce -= 1;
+
+    // The storage deposit is paid with the NEAR attached to the transaction
+    runner.context.attached_deposit = storage_deposit;
+    let result = runner.submit_with_signer(&mut signer, make_tx).unwrap();
+    test_utils::unwrap_success(result);
+
+    // Storage is registered for the recipient before the transfer, in the same batch
+    let aurora = runner.aurora_account_id.clone();
+    let storage_deposit_index = runner
+        .previous_logs
+        .iter()
+        .position(|log| log == &format!("Call contract: {}.storage_deposit", aurora))
+        .unwrap();
+    let transfer_index = runner
+        .previous_logs
+        .iter()
+        .position(|log| log == &format!("Call contract: {}.ft_transfer", aurora))
+        .unwrap();
+    assert!(storage_deposit_index < transfer_index);
+    assert!(runner.previous_logs.contains(&REFUND_CALLBACK.to_string()));
+
+    // The storage deposit is capped
+    let input = [
+        &[0x4][..],
+        &u128::MAX.to_be_bytes(),
+        DEST_ACCOUNT.as_bytes(),
+    ]
+    .concat();
+    let result = runner.submit_with_signer(&mut signer, |nonce| LegacyEthTransaction {
+        nonce,
+        gas_price: U256::zero(),
+        gas: u64::MAX.into(),
+        to: Some(ExitToNear::ADDRESS),
+        value: Wei::new_u64(10),
+        data: input,
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn withdraw_eth_with_transfer_call() {
+    let (mut runner, mut signer, _token, _tester) = setup_test();
+    let msg = r#"{"action": "swap", "note": "a \"quoted\" value"}"#;
+
+    let input = [
+        &[0x8][..],
+        &(msg.len() as u32).to_be_bytes(),
+        msg.as_bytes(),
+        &0u32.to_be_bytes(),
+        DEST_ACCOUNT.as_bytes(),
+    ]
+    .concat();
+    let result = runner
+        .submit_with_signer(&mut signer, |nonce| LegacyEthTransaction {
+            nonce,
+            gas_price: U256::zero(),
+            gas: u64::MAX.into(),
+            to: Some(ExitToNear::ADDRESS),
+            value: Wei::new_u64(10),
+            data: input,
+        })
+        .unwrap();
+    test_utils::unwrap_success(result);
+
+    // The tokens are sent with `ft_transfer_call`, refunding what is left unused
+    let aurora = runner.aurora_account_id.clone();
+    let expected = format!("Call contract: {}.ft_transfer_call", aurora);
+    assert!(runner.previous_logs.contains(&expected));
+    assert!(runner.previous_logs.contains(&REFUND_CALLBACK.to_string()));
+
+    // The message must be complete
+    let input = [&[0x8][..], &100u32.to_be_bytes(), msg.as_bytes()].concat();
+    let result = runner.submit_with_signer(&mut signer, |nonce| LegacyEthTransaction {
+        nonce,
+        gas_price: U256::zero(),
+        gas: u64::MAX.into(),
+        to: Some(ExitToNear::ADDRESS),
+        value: Wei::new_u64(10),
+        data: input,
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn withdraw_erc20_with_transfer_call() {
+    let (mut runner, mut signer, _token, tester) = setup_test();
+
+    let withdraw_result = tester
+        .withdraw_and_call(&mut runner, &mut signer, r#"{"action": "swap"}"#)
+        .unwrap();
+    let schema = aurora_engine_precompiles::native::events::exit_to_near_schema();
+    assert_eq!(parse_exit_events(withdraw_result, &schema).len(), 1);
+
+    // The tokens are sent with `ft_transfer_call`, refunding what is left unused
+    assert!(runner
+        .previous_logs
+        .contains(&"Call contract: tt.testnet.ft_transfer_call".to_string()));
+    assert!(runner.previous_logs.contains(&REFUND_CALLBACK.to_string()));
+}
+
 #[test]
 fn withdraw_and_fail() {
     let (mut runner, mut signer, _token, tester) = setup_test();
@@ -228,6 +435,110 @@ fn withdraw_eth() {
     assert_eq!(&expected_event, &exit_events[0].params);
 }
 
+#[test]
+fn withdraw_eth_with_fee() {
+    let (mut runner, mut signer, _token, _tester) = setup_test();
+    let amount = Wei::new_u64(10);
+    let exit_to_ethereum = |fee: u64| {
+        let mut fee_bytes = [0u8; 32];
+        U256::from(fee).to_big_endian(&mut fee_bytes);
+        let input = [&[0x0][..], DEST_ADDRESS.as_bytes(), &fee_bytes].concat();
+        move |nonce: U256| LegacyEthTransaction {
+            nonce,
+            gas_price: U256::zero(),
+            gas: u64::MAX.into(),
+            to: Some(ExitToEthereum::ADDRESS),
+            value: amount,
+            data: input,
+        }
+    };
+
+    let result = runner
+        .submit_with_signer(&mut signer, exit_to_ethereum(3))
+        .unwrap();
+    test_utils::unwrap_success_slice(&result);
+    let schema = aurora_engine_precompiles::native::events::exit_to_eth_with_fee_schema();
+    let exit_events = parse_exit_events(result, &schema);
+    assert_eq!(exit_events.len(), 1);
+    let fee = exit_events[0]
+        .params
+        .iter()
+        .find(|param| param.name == "fee")
+        .unwrap();
+    assert_eq!(fee.value, ethabi::Token::Uint(3.into()));
+
+    // The fee must be below the amount
+    let result = runner.submit_with_signer(&mut signer, exit_to_ethereum(10));
+    assert!(result.is_err());
+}
+
+#[test]
+fn frozen_address_cannot_exit() {
+    let (mut runner, mut signer, _token, _tester) = setup_test();
+    let address = test_utils::address_from_secret_key(&signer.secret_key);
+    let exit_to_ethereum = |nonce| LegacyEthTransaction {
+        nonce,
+        gas_price: U256::zero(),
+        gas: u64::MAX.into(),
+        to: Some(ExitToEthereum::ADDRESS),
+        value: Wei::new_u64(10),
+        data: [&[0x0][..], DEST_ADDRESS.as_bytes()].concat(),
+    };
+    let target = FreezeTarget::EvmAddress(address.0);
+    let aurora_account_id = runner.aurora_account_id.clone();
+
+    let args = FreezeArgs {
+        target: target.clone(),
+        reason: 1,
+    };
+    let (_, maybe_error) = runner.call("freeze", &aurora_account_id, args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let result = runner.submit_with_signer(&mut signer, exit_to_ethereum);
+    assert!(result.is_err());
+
+    let (_, maybe_error) =
+        runner.call("unfreeze", &aurora_account_id, target.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let result = runner
+        .submit_with_signer(&mut signer, exit_to_ethereum)
+        .unwrap();
+    test_utils::unwrap_success_slice(&result);
+}
+
+#[test]
+fn frozen_address_cannot_transact() {
+    let (mut runner, mut signer, _token, _tester) = setup_test();
+    let address = test_utils::address_from_secret_key(&signer.secret_key);
+    let recipient = Address([1u8; 20]);
+    let transfer = |nonce| LegacyEthTransaction {
+        nonce,
+        gas_price: U256::zero(),
+        gas: u64::MAX.into(),
+        to: Some(recipient),
+        value: Wei::new_u64(10),
+        data: Vec::new(),
+    };
+    let target = FreezeTarget::EvmAddress(address.0);
+    let aurora_account_id = runner.aurora_account_id.clone();
+
+    let args = FreezeArgs {
+        target: target.clone(),
+        reason: 1,
+    };
+    let (_, maybe_error) = runner.call("freeze", &aurora_account_id, args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let result = runner.submit_with_signer(&mut signer, transfer);
+    assert!(result.is_err());
+    assert_eq!(runner.get_balance(recipient), Wei::zero());
+
+    let (_, maybe_error) =
+        runner.call("unfreeze", &aurora_account_id, target.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let result = runner.submit_with_signer(&mut signer, transfer).unwrap();
+    test_utils::unwrap_success_slice(&result);
+    assert_eq!(runner.get_balance(recipient), Wei::new_u64(10));
+}
+
 fn parse_exit_events(result: SubmitResult, schema: &ethabi::Event) -> Vec<ethabi::Log> {
     let signature = schema.signature();
     result
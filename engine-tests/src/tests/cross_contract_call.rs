@@ -0,0 +1,259 @@
+use crate::prelude::{Address, EvmCallbackArgs, PromiseCreateArgs, Wei, U256};
+use crate::test_utils::{self, AuroraRunner, Signer};
+use aurora_engine::parameters::{CrossContractCallPermissionArgs, GetStorageAtArgs, SubmitResult};
+use aurora_engine::transaction::LegacyEthTransaction;
+use aurora_engine_precompiles::cross_contract_call::CrossContractCall;
+use borsh::{BorshDeserialize, BorshSerialize};
+use near_vm_logic::types::PromiseResult;
+use near_vm_runner::VMError;
+
+const TARGET_ACCOUNT: &str = "target.near";
+const TARGET_METHOD: &str = "some_method";
+const CALLBACK_SELECTOR: [u8; 4] = [0xde, 0xad, 0xbe, 0xef];
+const CALLBACK_GAS: u64 = 50_000_000_000_000;
+const CALLBACK_GAS_LIMIT: u64 = 200_000;
+
+fn setup_test() -> (AuroraRunner, Signer, Address) {
+    let mut runner = test_utils::deploy_evm();
+    let signer = Signer::random();
+    let address = test_utils::address_from_secret_key(&signer.secret_key);
+    runner.create_address(address, Wei::from_eth(1.into()).unwrap(), U256::zero());
+
+    (runner, signer, address)
+}
+
+fn promise(target_account_id: &str) -> PromiseCreateArgs {
+    PromiseCreateArgs {
+        target_account_id: test_utils::str_to_account_id(target_account_id),
+        method: TARGET_METHOD.to_string(),
+        args: br#"{"hello": "world"}"#.to_vec(),
+        attached_balance: 0,
+        attached_gas: 10_000_000_000_000,
+    }
+}
+
+fn call_precompile(
+    runner: &mut AuroraRunner,
+    signer: &Signer,
+    promise: PromiseCreateArgs,
+) -> Result<SubmitResult, VMError> {
+    let input = [&[0u8][..], &promise.try_to_vec().unwrap()].concat();
+    submit_input(runner, signer, input)
+}
+
+fn call_precompile_with_callback(
+    runner: &mut AuroraRunner,
+    signer: &Signer,
+    promise: PromiseCreateArgs,
+    callback_address: Address,
+    callback_gas_limit: u64,
+) -> Result<SubmitResult, VMError> {
+    let input = [
+        &[1u8][..],
+        callback_address.as_bytes(),
+        &CALLBACK_SELECTOR,
+        &CALLBACK_GAS.to_be_bytes(),
+        &callback_gas_limit.to_be_bytes(),
+        &promise.try_to_vec().unwrap(),
+    ]
+    .concat();
+    submit_input(runner, signer, input)
+}
+
+fn submit_input(
+    runner: &mut AuroraRunner,
+    signer: &Signer,
+    input: Vec<u8>,
+) -> Result<SubmitResult, VMError> {
+    let address = test_utils::address_from_secret_key(&signer.secret_key);
+    let tx = LegacyEthTransaction {
+        nonce: runner.get_nonce(address),
+        gas_price: U256::zero(),
+        gas: u64::MAX.into(),
+        to: Some(CrossContractCall::ADDRESS),
+        value: Wei::zero(),
+        data: input,
+    };
+    runner.submit_transaction(&signer.secret_key, tx)
+}
+
+fn set_permission(
+    runner: &mut AuroraRunner,
+    caller: &str,
+    address: Address,
+    allowed: bool,
+) -> Option<VMError> {
+    let args = CrossContractCallPermissionArgs {
+        address: address.0,
+        allowed,
+    };
+    let (_, maybe_error) = runner.call(
+        "set_cross_contract_call_permission",
+        caller,
+        args.try_to_vec().unwrap(),
+    );
+    maybe_error
+}
+
+fn get_permission(runner: &AuroraRunner, address: Address) -> bool {
+    let (outcome, maybe_error) = runner.one_shot().call(
+        "get_cross_contract_call_permission",
+        "getter",
+        address.as_bytes().to_vec(),
+    );
+    assert!(maybe_error.is_none());
+    let bytes = outcome.unwrap().return_data.as_value().unwrap();
+    bool::try_from_slice(&bytes).unwrap()
+}
+
+#[test]
+fn test_cross_contract_call_requires_permission() {
+    let (mut runner, signer, address) = setup_test();
+    let expected_log = format!("Call contract: {}.{}", TARGET_ACCOUNT, TARGET_METHOD);
+
+    assert!(!get_permission(&runner, address));
+    assert!(call_precompile(&mut runner, &signer, promise(TARGET_ACCOUNT)).is_err());
+    assert!(!runner.previous_logs.contains(&expected_log));
+
+    let owner = runner.aurora_account_id.clone();
+    assert!(set_permission(&mut runner, &owner, address, true).is_none());
+    assert!(get_permission(&runner, address));
+
+    let result = call_precompile(&mut runner, &signer, promise(TARGET_ACCOUNT)).unwrap();
+    test_utils::unwrap_success(result);
+    // One promise is scheduled
+    assert!(runner.previous_logs.contains(&expected_log));
+
+    assert!(set_permission(&mut runner, &owner, address, false).is_none());
+    assert!(!get_permission(&runner, address));
+    assert!(call_precompile(&mut runner, &signer, promise(TARGET_ACCOUNT)).is_err());
+}
+
+#[test]
+fn test_cross_contract_call_permission_owner_only() {
+    let (mut runner, _signer, address) = setup_test();
+
+    assert!(set_permission(&mut runner, "not-owner.near", address, true).is_some());
+    assert!(!get_permission(&runner, address));
+}
+
+#[test]
+fn test_cross_contract_call_cannot_target_engine() {
+    let (mut runner, signer, address) = setup_test();
+    let owner = runner.aurora_account_id.clone();
+    assert!(set_permission(&mut runner, &owner, address, true).is_none());
+
+    assert!(call_precompile(&mut runner, &signer, promise(&owner)).is_err());
+}
+
+#[test]
+fn test_cross_contract_call_with_callback() {
+    let (mut runner, signer, address) = setup_test();
+    let owner = runner.aurora_account_id.clone();
+    assert!(set_permission(&mut runner, &owner, address, true).is_none());
+
+    let result = call_precompile_with_callback(
+        &mut runner,
+        &signer,
+        promise(TARGET_ACCOUNT),
+        address,
+        CALLBACK_GAS_LIMIT,
+    )
+    .unwrap();
+    let gas_used = result.gas_used;
+    test_utils::unwrap_success(result);
+
+    // The promise is scheduled together with the callback into the engine
+    let expected_call = format!("Call contract: {}.{}", TARGET_ACCOUNT, TARGET_METHOD);
+    let expected_callback = format!("Callback contract: {}.evm_callback", owner);
+    assert!(runner.previous_logs.contains(&expected_call));
+    assert!(runner.previous_logs.contains(&expected_callback));
+
+    // The EVM gas of the callback is paid for by the caller
+    let result = call_precompile_with_callback(
+        &mut runner,
+        &signer,
+        promise(TARGET_ACCOUNT),
+        address,
+        2 * CALLBACK_GAS_LIMIT,
+    )
+    .unwrap();
+    assert_eq!(result.gas_used, gas_used + CALLBACK_GAS_LIMIT);
+    test_utils::unwrap_success(result);
+
+    // The NEAR gas attached to the callback must cover its EVM gas
+    let result = call_precompile_with_callback(
+        &mut runner,
+        &signer,
+        promise(TARGET_ACCOUNT),
+        address,
+        300_000,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_evm_callback_is_private() {
+    let (mut runner, _signer, address) = setup_test();
+    let args = EvmCallbackArgs {
+        sender: address.0,
+        callback_address: address.0,
+        selector: CALLBACK_SELECTOR,
+        gas_limit: CALLBACK_GAS_LIMIT,
+    };
+
+    let (_, maybe_error) = runner.call(
+        "evm_callback",
+        "some-account.near",
+        args.try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_some());
+}
+
+#[test]
+fn test_evm_callback_reaches_callback_address() {
+    let (mut runner, mut signer, address) = setup_test();
+    // Stores the caller in slot 0 and the first word of the call data in slot 1
+    let code = hex::decode("33600055600035600155").unwrap();
+    let result = runner
+        .submit_with_signer(&mut signer, |nonce| {
+            test_utils::create_deploy_transaction(code, nonce)
+        })
+        .unwrap();
+    let callback_address = Address::from_slice(test_utils::unwrap_success_slice(&result));
+
+    runner.promise_results = vec![PromiseResult::Successful(b"result".to_vec())];
+    let args = EvmCallbackArgs {
+        sender: address.0,
+        callback_address: callback_address.0,
+        selector: CALLBACK_SELECTOR,
+        gas_limit: CALLBACK_GAS_LIMIT,
+    };
+    let owner = runner.aurora_account_id.clone();
+    let (outcome, maybe_error) = runner.call("evm_callback", &owner, args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let result =
+        SubmitResult::try_from_slice(&outcome.unwrap().return_data.as_value().unwrap()).unwrap();
+    test_utils::unwrap_success(result);
+
+    // The callback is called by the contract which scheduled the promise
+    let caller = get_storage_at(&runner, callback_address, 0);
+    assert_eq!(&caller[12..], address.as_bytes());
+    let input = get_storage_at(&runner, callback_address, 1);
+    assert_eq!(&input[..4], &CALLBACK_SELECTOR);
+}
+
+fn get_storage_at(runner: &AuroraRunner, address: Address, slot: u8) -> Vec<u8> {
+    let mut key = [0u8; 32];
+    key[31] = slot;
+    let args = GetStorageAtArgs {
+        address: address.0,
+        key,
+    };
+    let (outcome, maybe_error) =
+        runner
+            .one_shot()
+            .call("get_storage_at", "getter", args.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    outcome.unwrap().return_data.as_value().unwrap()
+}
@@ -1,10 +1,15 @@
-use crate::prelude::{Address, Balance, RawAddress, TryInto, Wei, U256};
+use crate::prelude::{storage, Address, Balance, RawAddress, TryInto, Wei, U256};
 use crate::test_utils;
 use crate::test_utils::{create_eth_transaction, origin, AuroraRunner};
-use aurora_engine::parameters::{FunctionCallArgs, SubmitResult};
+use aurora_engine::parameters::{
+    BridgedTokenPair, Erc20AutoDeployConfig, Erc20AutoDeployDenyArgs, FunctionCallArgs,
+    FundErc20AutoDeployArgs, GetBridgedTokensArgs, IndexBridgedTokensArgs, RemapTokenArgs,
+    SubmitResult, UnregisterTokenArgs,
+};
 use aurora_engine::transaction::LegacyEthSignedTransaction;
 use borsh::{BorshDeserialize, BorshSerialize};
 use ethabi::Token;
+use near_vm_logic::types::PromiseResult;
 use near_vm_logic::VMOutcome;
 use near_vm_runner::VMError;
 use secp256k1::SecretKey;
@@ -16,15 +21,15 @@ const INITIAL_NONCE: u64 = 0;
 
 pub struct CallResult {
     outcome: Option<VMOutcome>,
-    error: Option<VMError>,
+    pub(crate) error: Option<VMError>,
 }
 
 impl CallResult {
-    fn check_ok(&self) {
+    pub(crate) fn check_ok(&self) {
         assert!(self.error.is_none());
     }
 
-    fn value(&self) -> Vec<u8> {
+    pub(crate) fn value(&self) -> Vec<u8> {
         self.outcome
             .as_ref()
             .unwrap()
@@ -34,7 +39,7 @@ impl CallResult {
             .unwrap()
     }
 
-    fn submit_result(&self) -> SubmitResult {
+    pub(crate) fn submit_result(&self) -> SubmitResult {
         SubmitResult::try_from_slice(self.value().as_slice()).unwrap()
     }
 }
@@ -47,7 +52,7 @@ fn get_selector(str_selector: &str) -> Vec<u8> {
     keccak256(str_selector.as_bytes())[..4].to_vec()
 }
 
-fn build_input(str_selector: &str, inputs: &[Token]) -> Vec<u8> {
+pub(crate) fn build_input(str_selector: &str, inputs: &[Token]) -> Vec<u8> {
     let sel = get_selector(str_selector);
     let inputs = ethabi::encode(inputs);
     [sel.as_slice(), inputs.as_slice()].concat().to_vec()
@@ -133,6 +138,25 @@ impl test_utils::AuroraRunner {
         U256::from_big_endian(output.as_slice())
     }
 
+    pub fn erc20_metadata(&mut self, token: RawAddress) -> (String, String, u8) {
+        let mut call = |selector: &str, output: ethabi::ParamType| {
+            let result = self.evm_call(token, build_input(selector, &[]), origin());
+            result.check_ok();
+            let output_bytes = test_utils::unwrap_success(result.submit_result());
+            ethabi::decode(&[output], &output_bytes).unwrap().remove(0)
+        };
+        match (
+            call("name()", ethabi::ParamType::String),
+            call("symbol()", ethabi::ParamType::String),
+            call("decimals()", ethabi::ParamType::Uint(8)),
+        ) {
+            (Token::String(name), Token::String(symbol), Token::Uint(decimals)) => {
+                (name, symbol, decimals.as_u32() as u8)
+            }
+            _ => unreachable!(),
+        }
+    }
+
     pub fn mint(
         &mut self,
         token: RawAddress,
@@ -208,6 +232,92 @@ impl test_utils::AuroraRunner {
         String::from_utf8(res.value()).unwrap()
     }
 
+    pub fn set_erc20_auto_deploy_config(&mut self, config: Erc20AutoDeployConfig) -> CallResult {
+        let owner = self.aurora_account_id.clone();
+        self.make_call(
+            "set_erc20_auto_deploy_config",
+            owner,
+            config.try_to_vec().unwrap(),
+        )
+    }
+
+    pub fn set_erc20_auto_deploy_denied(&mut self, nep141: &str, denied: bool) -> CallResult {
+        let owner = self.aurora_account_id.clone();
+        let args = Erc20AutoDeployDenyArgs {
+            nep141: test_utils::str_to_account_id(nep141),
+            denied,
+        };
+        self.make_call(
+            "set_erc20_auto_deploy_denied",
+            owner,
+            args.try_to_vec().unwrap(),
+        )
+    }
+
+    pub fn fund_erc20_auto_deploy(&mut self, nep141: &str, deposit: Balance) -> CallResult {
+        let args = FundErc20AutoDeployArgs {
+            nep141: test_utils::str_to_account_id(nep141),
+        };
+        self.context.attached_deposit = deposit;
+        let result = self.make_call(
+            "fund_erc20_auto_deploy",
+            origin(),
+            args.try_to_vec().unwrap(),
+        );
+        self.context.attached_deposit = 0;
+        result
+    }
+
+    pub fn get_erc20_from_nep141(&mut self, nep141: &str) -> Option<RawAddress> {
+        let result = self.make_call(
+            "get_erc20_from_nep141",
+            origin(),
+            test_utils::str_to_account_id(nep141).try_to_vec().unwrap(),
+        );
+        result.error.as_ref()?;
+        Some(result.value().as_slice().try_into().unwrap())
+    }
+
+    pub fn unregister_token(&mut self, caller: String, nep141: &str, force: bool) -> CallResult {
+        let args = UnregisterTokenArgs {
+            nep141: test_utils::str_to_account_id(nep141),
+            force,
+        };
+        self.make_call("unregister_token", caller, args.try_to_vec().unwrap())
+    }
+
+    pub fn remap_token(
+        &mut self,
+        caller: String,
+        nep141: &str,
+        erc20: RawAddress,
+        force: bool,
+    ) -> CallResult {
+        let args = RemapTokenArgs {
+            nep141: test_utils::str_to_account_id(nep141),
+            erc20,
+            force,
+        };
+        self.make_call("remap_token", caller, args.try_to_vec().unwrap())
+    }
+
+    pub fn index_bridged_tokens(&mut self, caller: String, nep141s: &[&str]) -> CallResult {
+        let args = IndexBridgedTokensArgs {
+            nep141s: nep141s
+                .iter()
+                .map(|nep141| test_utils::str_to_account_id(nep141))
+                .collect(),
+        };
+        self.make_call("index_bridged_tokens", caller, args.try_to_vec().unwrap())
+    }
+
+    pub fn get_bridged_tokens(&mut self, from_index: u64, limit: u64) -> Vec<BridgedTokenPair> {
+        let args = GetBridgedTokensArgs { from_index, limit };
+        let result = self.make_call("get_bridged_tokens", origin(), args.try_to_vec().unwrap());
+        result.check_ok();
+        Vec::<BridgedTokenPair>::try_from_slice(&result.value()).unwrap()
+    }
+
     pub fn register_relayer(
         &mut self,
         relayer_account_id: String,
@@ -227,6 +337,77 @@ fn test_deploy_erc20_token() {
     runner.deploy_erc20_token(&"tt.testnet".to_string());
 }
 
+#[test]
+fn test_deploy_erc20_token_fetches_metadata() {
+    let mut runner = AuroraRunner::new();
+    let owner = runner.aurora_account_id.clone();
+    let nep141 = test_utils::str_to_account_id("tt.testnet");
+    let token = runner.deploy_erc20_token(&"tt.testnet".to_string());
+    let initial_metadata = runner.erc20_metadata(token);
+
+    // The metadata is left as is if `ft_metadata` failed
+    runner.promise_results = vec![PromiseResult::Failed];
+    let result = runner.make_call(
+        "set_erc20_metadata_callback",
+        owner.clone(),
+        nep141.try_to_vec().unwrap(),
+    );
+    assert!(result.error.is_some());
+    assert_eq!(runner.erc20_metadata(token), initial_metadata);
+
+    // The metadata of the NEP-141 is set on the ERC-20 in the callback
+    let ft_metadata = json!({
+        "spec": "ft-1.0.0",
+        "name": "Token",
+        "symbol": "TT",
+        "decimals": 18,
+    });
+    runner.promise_results = vec![PromiseResult::Successful(
+        ft_metadata.to_string().into_bytes(),
+    )];
+    runner
+        .make_call(
+            "set_erc20_metadata_callback",
+            owner,
+            nep141.try_to_vec().unwrap(),
+        )
+        .check_ok();
+    assert_eq!(
+        runner.erc20_metadata(token),
+        ("Token".to_string(), "TT".to_string(), 18)
+    );
+}
+
+#[test]
+fn test_sync_erc20_metadata() {
+    let mut runner = AuroraRunner::new();
+    let nep141 = test_utils::str_to_account_id("tt.testnet");
+
+    // The token must be mapped
+    let result = runner.make_call(
+        "sync_erc20_metadata",
+        "anyone.near".to_string(),
+        nep141.try_to_vec().unwrap(),
+    );
+    assert!(result.error.is_some());
+
+    runner.deploy_erc20_token(&"tt.testnet".to_string());
+    let result = runner.make_call(
+        "sync_erc20_metadata",
+        "anyone.near".to_string(),
+        nep141.try_to_vec().unwrap(),
+    );
+    result.check_ok();
+
+    // Only the engine can set the metadata
+    let result = runner.make_call(
+        "set_erc20_metadata_callback",
+        "anyone.near".to_string(),
+        nep141.try_to_vec().unwrap(),
+    );
+    assert!(result.error.is_some());
+}
+
 #[test]
 fn test_mint() {
     let mut runner = AuroraRunner::new();
@@ -292,7 +473,7 @@ fn test_ft_on_transfer_fail() {
 fn test_relayer_charge_fee() {
     let mut runner = AuroraRunner::new();
     let amount = 10;
-    let fee = 51;
+    let fee = 3;
     let nep141 = "tt.testnet".to_string();
     let alice = "alice".to_string();
     let token = runner.deploy_erc20_token(&nep141);
@@ -320,16 +501,149 @@ fn test_relayer_charge_fee() {
         hex::encode(recipient) + &hex::encode(fee_encoded),
     );
 
+    // The fee of the legacy message is taken from the deposited tokens, and
+    // the ETH of the recipient is left untouched
     let recipient_balance_end = runner.get_balance(recipient.into());
-    assert_eq!(
-        recipient_balance_end,
-        Wei::new_u64(INITIAL_BALANCE.raw().as_u64() - fee)
-    );
+    assert_eq!(recipient_balance_end, INITIAL_BALANCE);
     let relayer_balance = runner.get_balance(relayer);
-    assert_eq!(relayer_balance, Wei::new_u64(fee));
+    assert_eq!(relayer_balance, Wei::zero());
 
     let balance = runner.balance_of(token, recipient, origin());
-    assert_eq!(balance, U256::from(amount));
+    assert_eq!(balance, U256::from(amount - fee));
+    let balance = runner.balance_of(token, relayer.0, origin());
+    assert_eq!(balance, U256::from(fee));
+}
+
+#[test]
+fn test_ft_on_transfer_versioned_message() {
+    let mut runner = AuroraRunner::new();
+    let amount = 10;
+    let fee = 3;
+    let nep141 = "tt.testnet".to_string();
+    let alice = "alice".to_string();
+    let token = runner.deploy_erc20_token(&nep141);
+    let recipient = runner.create_account().address;
+    let relayer = create_ethereum_address();
+    runner.register_relayer(alice.clone(), relayer);
+
+    let msg = json!({
+        "version": 1,
+        "recipient": hex::encode(recipient),
+        "fee": fee.to_string(),
+    });
+    let res = runner.ft_on_transfer(nep141, alice.clone(), alice, amount, msg.to_string());
+    assert_eq!(res, "\"0\"");
+
+    // The fee is taken from the deposited tokens
+    assert_eq!(
+        runner.balance_of(token, recipient, origin()),
+        U256::from(amount - fee)
+    );
+    assert_eq!(
+        runner.balance_of(token, relayer.0, origin()),
+        U256::from(fee)
+    );
+}
+
+#[test]
+fn test_ft_on_transfer_with_call() {
+    let mut runner = AuroraRunner::new();
+    let amount = 10;
+    let nep141 = "tt.testnet".to_string();
+    let alice = "alice".to_string();
+    let token = runner.deploy_erc20_token(&nep141);
+    // The call is made by the address of the NEP-141 token account, since the
+    // sender of the tokens is not authenticated
+    let sender = aurora_engine_sdk::types::near_account_to_evm_address(nep141.as_bytes());
+    let receiver = create_ethereum_address();
+
+    let input = build_input(
+        "transfer(address,uint256)",
+        &[Token::Address(receiver), Token::Uint(U256::from(amount))],
+    );
+    let msg = json!({
+        "version": 1,
+        "recipient": hex::encode(sender),
+        "call": {"contract": hex::encode(token), "input": hex::encode(input)},
+    });
+    let res = runner.ft_on_transfer(nep141, alice.clone(), alice, amount, msg.to_string());
+    assert_eq!(res, "\"0\"");
+
+    assert_eq!(runner.balance_of(token, sender.0, origin()), U256::zero());
+    assert_eq!(
+        runner.balance_of(token, receiver.0, origin()),
+        U256::from(amount)
+    );
+}
+
+#[test]
+fn test_ft_on_transfer_invalid_versioned_message() {
+    let mut runner = AuroraRunner::new();
+    let amount = 10;
+    let nep141 = "tt.testnet".to_string();
+    let alice = "alice".to_string();
+    runner.deploy_erc20_token(&nep141);
+    let recipient = runner.create_account().address;
+
+    let msg = json!({"version": 2, "recipient": hex::encode(recipient)});
+    let res = runner.ft_on_transfer(nep141, alice.clone(), alice, amount, msg.to_string());
+    // The deposit is refunded
+    assert_eq!(res, format!("\"{}\"", amount));
+}
+
+#[test]
+fn test_ft_on_transfer_fee_exceeds_deposit() {
+    let mut runner = AuroraRunner::new();
+    let amount = 10;
+    let nep141 = "tt.testnet".to_string();
+    let alice = "alice".to_string();
+    let token = runner.deploy_erc20_token(&nep141);
+    let recipient = runner.create_account().address;
+    let relayer = create_ethereum_address();
+    runner.register_relayer(alice.clone(), relayer);
+
+    // A fee beyond `u128` is not truncated
+    let fee = U256::from(u128::MAX) + 1;
+    let msg = json!({
+        "version": 1,
+        "recipient": hex::encode(recipient),
+        "fee": fee.to_string(),
+    });
+    let res = runner.ft_on_transfer(nep141, alice.clone(), alice, amount, msg.to_string());
+    // The deposit is refunded
+    assert_eq!(res, format!("\"{}\"", amount));
+    assert_eq!(runner.balance_of(token, recipient, origin()), U256::zero());
+    assert_eq!(runner.balance_of(token, relayer.0, origin()), U256::zero());
+}
+
+#[test]
+fn test_ft_on_transfer_eth_fee_rejected() {
+    let mut runner = AuroraRunner::new();
+    let amount = 10;
+    let nep141 = "tt.testnet".to_string();
+    let alice = "alice".to_string();
+    let token = runner.deploy_erc20_token(&nep141);
+    let recipient = create_ethereum_address();
+    let relayer = create_ethereum_address();
+    runner.register_relayer(alice.clone(), relayer);
+
+    let fee = 5;
+    runner.create_address(recipient, Wei::new_u64(fee), U256::zero());
+    let msg = json!({
+        "version": 1,
+        "recipient": hex::encode(recipient),
+        "fee": fee.to_string(),
+        "fee_token": "eth",
+    });
+    let res = runner.ft_on_transfer(nep141, alice.clone(), alice, amount, msg.to_string());
+    // The recipient cannot be made to pay the fee, so the deposit is refunded
+    assert_eq!(res, format!("\"{}\"", amount));
+    assert_eq!(runner.get_balance(recipient), Wei::new_u64(fee));
+    assert_eq!(runner.get_balance(relayer), Wei::zero());
+    assert_eq!(
+        runner.balance_of(token, recipient.0, origin()),
+        U256::zero()
+    );
 }
 
 #[test]
@@ -375,3 +689,257 @@ fn test_transfer_erc20_token() {
         U256::from(to_transfer)
     );
 }
+
+#[test]
+fn test_ft_on_transfer_auto_deploy() {
+    let mut runner = AuroraRunner::new();
+    let nep141 = "new.testnet".to_string();
+    let alice = "alice".to_string();
+    let amount = 10;
+    let recipient = runner.create_account().address;
+
+    // Disabled by default, so the deposit is refunded
+    let res = runner.ft_on_transfer(
+        nep141.clone(),
+        alice.clone(),
+        alice.clone(),
+        amount,
+        hex::encode(recipient),
+    );
+    assert_eq!(res, format!("\"{}\"", amount));
+    assert!(runner.get_erc20_from_nep141(&nep141).is_none());
+
+    let deploy_fee = 10_u128.pow(24);
+    runner
+        .set_erc20_auto_deploy_config(Erc20AutoDeployConfig {
+            enabled: true,
+            deploy_fee,
+        })
+        .check_ok();
+
+    // The deployment is not funded yet
+    let res = runner.ft_on_transfer(
+        nep141.clone(),
+        alice.clone(),
+        alice.clone(),
+        amount,
+        hex::encode(recipient),
+    );
+    assert_eq!(res, format!("\"{}\"", amount));
+    assert!(runner.get_erc20_from_nep141(&nep141).is_none());
+
+    // The deployment is paid in NEAR, not with the deposited tokens
+    let result = runner.fund_erc20_auto_deploy(&nep141, deploy_fee - 1);
+    assert!(result
+        .error
+        .unwrap()
+        .to_string()
+        .contains("ERR_AUTO_DEPLOY_INSUFFICIENT_FEE"));
+    runner
+        .fund_erc20_auto_deploy(&nep141, deploy_fee)
+        .check_ok();
+    let result = runner.fund_erc20_auto_deploy(&nep141, deploy_fee);
+    assert!(result
+        .error
+        .unwrap()
+        .to_string()
+        .contains("ERR_AUTO_DEPLOY_ALREADY_FUNDED"));
+
+    // A deposit refunded for its relayer fee does not deploy the token
+    let relayer = create_ethereum_address();
+    runner.register_relayer(alice.clone(), relayer);
+    let res = runner.ft_on_transfer(
+        nep141.clone(),
+        alice.clone(),
+        alice.clone(),
+        amount,
+        json!({
+            "version": 1,
+            "recipient": hex::encode(recipient),
+            "fee": (amount + 1).to_string(),
+        })
+        .to_string(),
+    );
+    assert_eq!(res, format!("\"{}\"", amount));
+    assert!(runner.get_erc20_from_nep141(&nep141).is_none());
+
+    // Denied tokens are not deployed
+    runner
+        .set_erc20_auto_deploy_denied(&nep141, true)
+        .check_ok();
+    let res = runner.ft_on_transfer(
+        nep141.clone(),
+        alice.clone(),
+        alice.clone(),
+        amount,
+        hex::encode(recipient),
+    );
+    assert_eq!(res, format!("\"{}\"", amount));
+    assert!(runner.get_erc20_from_nep141(&nep141).is_none());
+
+    // The first deposit deploys the token and mints all of it, whatever the
+    // ETH balance of the recipient
+    let recipient_balance = Wei::new_u64(10_000);
+    runner.create_address(recipient.into(), recipient_balance, U256::zero());
+    runner
+        .set_erc20_auto_deploy_denied(&nep141, false)
+        .check_ok();
+    let res = runner.ft_on_transfer(
+        nep141.clone(),
+        alice.clone(),
+        alice,
+        amount,
+        hex::encode(recipient),
+    );
+    assert_eq!(res, "\"0\"");
+    let token = runner.get_erc20_from_nep141(&nep141).unwrap();
+    assert_eq!(
+        runner.balance_of(token, recipient, origin()),
+        U256::from(amount)
+    );
+    assert_eq!(runner.get_balance(recipient.into()), recipient_balance);
+
+    // The funding is consumed by the deployment
+    let funded = runner.make_call(
+        "is_erc20_auto_deploy_funded",
+        origin(),
+        nep141.as_bytes().to_vec(),
+    );
+    assert_eq!(funded.value(), false.try_to_vec().unwrap());
+}
+
+#[test]
+fn test_erc20_auto_deploy_config_owner_only() {
+    let mut runner = AuroraRunner::new();
+    let result = runner.make_call(
+        "set_erc20_auto_deploy_config",
+        "not-owner.near".to_string(),
+        Erc20AutoDeployConfig::default().try_to_vec().unwrap(),
+    );
+    assert!(result.error.is_some());
+}
+
+#[test]
+fn test_get_bridged_tokens() {
+    let mut runner = AuroraRunner::new();
+    let owner = runner.aurora_account_id.clone();
+    let tokens: Vec<(String, RawAddress)> = ["a.testnet", "b.testnet", "c.testnet"]
+        .iter()
+        .map(|nep141| {
+            (
+                nep141.to_string(),
+                runner.deploy_erc20_token(&nep141.to_string()),
+            )
+        })
+        .collect();
+    let pair = |(nep141, erc20): &(String, RawAddress)| BridgedTokenPair {
+        nep141: test_utils::str_to_account_id(nep141),
+        erc20: *erc20,
+    };
+
+    assert_eq!(
+        runner.get_bridged_tokens(0, 10),
+        tokens.iter().map(pair).collect::<Vec<_>>()
+    );
+    assert_eq!(runner.get_bridged_tokens(1, 1), vec![pair(&tokens[1])]);
+    assert!(runner.get_bridged_tokens(3, 10).is_empty());
+
+    // The last token takes the place of the removed one
+    runner
+        .unregister_token(owner, "a.testnet", false)
+        .check_ok();
+    assert_eq!(
+        runner.get_bridged_tokens(0, 10),
+        vec![pair(&tokens[2]), pair(&tokens[1])]
+    );
+    assert!(runner.get_erc20_from_nep141("a.testnet").is_none());
+}
+
+#[test]
+fn test_index_bridged_tokens() {
+    let mut runner = AuroraRunner::new();
+    let owner = runner.aurora_account_id.clone();
+    let erc20 = runner.deploy_erc20_token(&"a.testnet".to_string());
+    // Tokens registered before the index existed are not listed
+    let index_prefix = storage::bytes_to_key(storage::KeyPrefix::Erc20TokenIndex, &[]);
+    let count_key = storage::bytes_to_key(storage::KeyPrefix::Config, b"ERC20_TOKEN_COUNT");
+    runner
+        .ext
+        .fake_trie
+        .retain(|key, _| !key.starts_with(&index_prefix) && key != &count_key);
+    assert!(runner.get_bridged_tokens(0, 10).is_empty());
+
+    // Only the owner can backfill the index, with mapped tokens only
+    let result = runner.index_bridged_tokens("not-owner.near".to_string(), &["a.testnet"]);
+    assert!(result.error.is_some());
+    let result = runner.index_bridged_tokens(owner.clone(), &["b.testnet"]);
+    assert!(result.error.is_some());
+
+    let expected = vec![BridgedTokenPair {
+        nep141: test_utils::str_to_account_id("a.testnet"),
+        erc20,
+    }];
+    runner
+        .index_bridged_tokens(owner.clone(), &["a.testnet"])
+        .check_ok();
+    assert_eq!(runner.get_bridged_tokens(0, 10), expected);
+
+    // Tokens already listed are skipped
+    runner
+        .index_bridged_tokens(owner, &["a.testnet"])
+        .check_ok();
+    assert_eq!(runner.get_bridged_tokens(0, 10), expected);
+}
+
+#[test]
+fn test_unregister_token_with_supply() {
+    let mut runner = AuroraRunner::new();
+    let owner = runner.aurora_account_id.clone();
+    let nep141 = "tt.testnet".to_string();
+    let alice = "alice".to_string();
+    runner.deploy_erc20_token(&nep141);
+    let recipient = runner.create_account().address;
+    runner.ft_on_transfer(
+        nep141.clone(),
+        alice.clone(),
+        alice,
+        10,
+        hex::encode(recipient),
+    );
+
+    // Only the owner can unregister a token
+    let result = runner.unregister_token("not-owner.near".to_string(), &nep141, true);
+    assert!(result.error.is_some());
+
+    // The tokens could not be exited anymore
+    let result = runner.unregister_token(owner.clone(), &nep141, false);
+    assert!(result.error.is_some());
+    assert!(runner.get_erc20_from_nep141(&nep141).is_some());
+
+    runner.unregister_token(owner, &nep141, true).check_ok();
+    assert!(runner.get_erc20_from_nep141(&nep141).is_none());
+}
+
+#[test]
+fn test_remap_token() {
+    let mut runner = AuroraRunner::new();
+    let owner = runner.aurora_account_id.clone();
+    runner.deploy_erc20_token(&"a.testnet".to_string());
+    let other_token = runner.deploy_erc20_token(&"b.testnet".to_string());
+
+    // The new token must not be mapped already
+    let result = runner.remap_token(owner.clone(), "a.testnet", other_token, false);
+    assert!(result.error.is_some());
+
+    // The new token must be deployed
+    let result = runner.remap_token(owner.clone(), "a.testnet", [1u8; 20], false);
+    assert!(result.error.is_some());
+
+    runner
+        .unregister_token(owner.clone(), "b.testnet", false)
+        .check_ok();
+    runner
+        .remap_token(owner, "a.testnet", other_token, false)
+        .check_ok();
+    assert_eq!(runner.get_erc20_from_nep141("a.testnet"), Some(other_token));
+}
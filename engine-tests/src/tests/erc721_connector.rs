@@ -0,0 +1,177 @@
+use crate::prelude::{RawAddress, TryInto, Wei, U256};
+use crate::test_utils::{self, create_eth_transaction, origin, AuroraRunner};
+use crate::tests::erc20_connector::build_input;
+use aurora_engine::parameters::TransactionStatus;
+use borsh::{BorshDeserialize, BorshSerialize};
+use ethabi::Token;
+use serde_json::json;
+use sha3::Digest;
+
+const NEP171: &str = "nft.near";
+const TOKEN_ID: &str = "token-1";
+const DEST_ACCOUNT: &str = "target.aurora";
+
+impl AuroraRunner {
+    pub fn deploy_erc721_token(&mut self, nep171: &String) -> RawAddress {
+        let result = self.make_call(
+            "deploy_erc721_token",
+            origin(),
+            nep171.try_to_vec().unwrap(),
+        );
+
+        result.check_ok();
+
+        Vec::<u8>::try_from_slice(result.value().as_slice())
+            .unwrap()
+            .try_into()
+            .unwrap()
+    }
+
+    pub fn nft_on_transfer(
+        &mut self,
+        nep171: String,
+        sender_id: String,
+        token_id: String,
+        msg: String,
+    ) -> String {
+        let res = self.make_call(
+            "nft_on_transfer",
+            nep171,
+            json!({
+                "sender_id": sender_id,
+                "previous_owner_id": sender_id,
+                "token_id": token_id,
+                "msg": msg
+            })
+            .to_string()
+            .into_bytes(),
+        );
+        res.check_ok();
+        String::from_utf8(res.value()).unwrap()
+    }
+
+    pub fn owner_of(&mut self, token: RawAddress, token_id: U256, origin: String) -> RawAddress {
+        let input = build_input("ownerOf(uint256)", &[Token::Uint(token_id)]);
+        let result = self.evm_call(token, input, origin);
+        result.check_ok();
+        let output = test_utils::unwrap_success(result.submit_result());
+        output[12..].try_into().unwrap()
+    }
+}
+
+fn erc721_token_id(token_id: &str) -> U256 {
+    U256::from_big_endian(&sha3::Keccak256::digest(token_id.as_bytes()))
+}
+
+#[test]
+fn test_deploy_erc721_token() {
+    let mut runner = AuroraRunner::new();
+    runner.deploy_erc721_token(&NEP171.to_string());
+
+    // The NEP-171 token can only be mapped once
+    let (_, maybe_error) = runner.call(
+        "deploy_erc721_token",
+        &origin(),
+        NEP171.to_string().try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_some());
+}
+
+#[test]
+fn test_nft_on_transfer() {
+    let mut runner = AuroraRunner::new();
+    let token = runner.deploy_erc721_token(&NEP171.to_string());
+    let recipient = runner.create_account().address;
+
+    let output = runner.nft_on_transfer(
+        NEP171.to_string(),
+        "sender.near".to_string(),
+        TOKEN_ID.to_string(),
+        hex::encode(recipient),
+    );
+    // The token is kept
+    assert_eq!(output, "false");
+    assert_eq!(
+        runner.owner_of(token, erc721_token_id(TOKEN_ID), origin()),
+        recipient
+    );
+}
+
+#[test]
+fn test_nft_on_transfer_returns_token_on_error() {
+    let mut runner = AuroraRunner::new();
+    runner.deploy_erc721_token(&NEP171.to_string());
+    let recipient = runner.create_account().address;
+
+    // Invalid recipient
+    let output = runner.nft_on_transfer(
+        NEP171.to_string(),
+        "sender.near".to_string(),
+        TOKEN_ID.to_string(),
+        "not an address".to_string(),
+    );
+    assert_eq!(output, "true");
+
+    // NEP-171 token which is not mapped
+    let output = runner.nft_on_transfer(
+        "other-nft.near".to_string(),
+        "sender.near".to_string(),
+        TOKEN_ID.to_string(),
+        hex::encode(recipient),
+    );
+    assert_eq!(output, "true");
+}
+
+#[test]
+fn test_erc721_withdraw_to_near() {
+    let mut runner = AuroraRunner::new();
+    let token = runner.deploy_erc721_token(&NEP171.to_string());
+    let owner = runner.create_account();
+
+    runner.nft_on_transfer(
+        NEP171.to_string(),
+        "sender.near".to_string(),
+        TOKEN_ID.to_string(),
+        hex::encode(owner.address),
+    );
+
+    let input = build_input(
+        "withdrawToNear(bytes,uint256)",
+        &[
+            Token::Bytes(DEST_ACCOUNT.as_bytes().to_vec()),
+            Token::Uint(erc721_token_id(TOKEN_ID)),
+        ],
+    );
+    let input = create_eth_transaction(
+        Some(token.into()),
+        Wei::zero(),
+        input,
+        None,
+        &owner.secret_key,
+    );
+    let result = runner.evm_submit(input, origin());
+    result.check_ok();
+    test_utils::unwrap_success(result.submit_result());
+
+    // The token is transferred back on NEAR, and minted back if the transfer fails
+    let expected = format!("Call contract: {}.nft_transfer", NEP171);
+    assert!(runner.previous_logs.contains(&expected));
+    assert!(runner
+        .previous_logs
+        .contains(&"Callback contract: aurora.refund_on_error".to_string()));
+
+    // The token was burned
+    let result = runner.evm_call(
+        token,
+        build_input(
+            "ownerOf(uint256)",
+            &[Token::Uint(erc721_token_id(TOKEN_ID))],
+        ),
+        origin(),
+    );
+    result.check_ok();
+    assert!(matches!(
+        result.submit_result().status,
+        TransactionStatus::Revert(_)
+    ));
+}
@@ -1,20 +1,30 @@
 use crate::prelude::EthAddress;
 use crate::prelude::WithdrawCallArgs;
 use crate::prelude::U256;
-use crate::test_utils::str_to_account_id;
+use crate::test_utils::{self, str_to_account_id};
 use aurora_engine::admin_controlled::{PausedMask, ERR_PAUSED};
 use aurora_engine::connector::{
-    ERR_NOT_ENOUGH_BALANCE_FOR_FEE, PAUSE_DEPOSIT, PAUSE_WITHDRAW, UNPAUSE_ALL,
+    EthCustodian, ERR_ACCOUNT_FROZEN, ERR_DEPOSIT_ABOVE_MAX, ERR_DEPOSIT_CALL_NOT_BY_RECIPIENT,
+    ERR_DEPRECATION_BELOW_ACTIVATION, ERR_DEPRECATION_BELOW_LAST_DEPOSIT,
+    ERR_NOT_ENOUGH_BALANCE_FOR_FEE, ERR_SUPPLY_DRIFT_ABOVE_TOLERANCE, ERR_WITHDRAW_CAP_EXCEEDED,
+    PAUSE_DEPOSIT, PAUSE_WITHDRAW, UNPAUSE_ALL,
 };
+use aurora_engine::deposit_event::DepositedEventSchema;
 use aurora_engine::fungible_token::FungibleTokenMetadata;
 use aurora_engine::parameters::{
-    InitCallArgs, NewCallArgs, RegisterRelayerCallArgs, WithdrawResult,
+    BridgeLimits, CancelWithdrawalArgs, FinishDepositCallArgs, Freeze, FreezeArgs, FreezeTarget,
+    GetStorageAtArgs, InitCallArgs, LegacyFinishDepositCallArgs, MigrateUsedProofArgs, NewCallArgs,
+    ReconcileSupplyArgs, RegisterRelayerCallArgs, ReleaseDepositArgs, ReleaseWithdrawalArgs,
+    RotateEthCustodianArgs, SetContractDataCallArgs, SetDepositEventSchemaArgs, SubmitResult,
+    SupplyAudit, TransactionStatus, UsedProof, WithdrawCaps, WithdrawResult, WithdrawWithFeeResult,
 };
+use aurora_engine::proof::legacy_proof_key;
 use borsh::{BorshDeserialize, BorshSerialize};
 use byte_slice_cast::AsByteSlice;
 use near_sdk::test_utils::accounts;
 use near_sdk_sim::transaction::ExecutionStatus;
 use near_sdk_sim::{to_yocto, ExecutionResult, UserAccount, DEFAULT_GAS, STORAGE_AMOUNT};
+use near_vm_logic::types::PromiseResult;
 use serde_json::json;
 
 const CONTRACT_ACC: &'static str = "eth_connector.root";
@@ -29,6 +39,9 @@ const RECIPIENT_ETH_ADDRESS: &'static str = "891b2749238b27ff58e951088e55b04de71
 const EVM_CUSTODIAN_ADDRESS: &'static str = "096DE9C2B8A5B8c22cEe3289B101f6960d68E51E";
 const DEPOSITED_EVM_AMOUNT: u128 = 10200;
 const DEPOSITED_EVM_FEE: u128 = 200;
+/// Ethereum block of the `PROOF_DATA_NEAR` deposit.
+const PROOF_DATA_NEAR_BLOCK: u64 = 10_310_926;
+const NEW_CUSTODIAN_ADDRESS: &'static str = "0000000000000000000000000000000000000002";
 
 #[derive(
     Default, BorshDeserialize, BorshSerialize, Clone, serde::Deserialize, serde::Serialize,
@@ -313,6 +326,8 @@ fn test_withdraw_eth_from_near() {
         &WithdrawCallArgs {
             recipient_address: recipient_addr,
             amount: withdraw_amount,
+            fee: None,
+            sender: None,
         }
         .try_to_vec()
         .unwrap(),
@@ -384,6 +399,126 @@ fn test_ft_transfer() {
     assert_eq!(balance, DEPOSITED_AMOUNT);
 }
 
+#[test]
+fn test_withdraw_with_fee() {
+    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+
+    let call_withdraw_with_fee = |fee| {
+        contract.call(
+            CONTRACT_ACC.parse().unwrap(),
+            "withdraw",
+            &WithdrawCallArgs {
+                recipient_address: validate_eth_address(RECIPIENT_ETH_ADDRESS),
+                amount: 100,
+                fee: Some(fee),
+                sender: None,
+            }
+            .try_to_vec()
+            .unwrap(),
+            DEFAULT_GAS,
+            1,
+        )
+    };
+
+    let res = call_withdraw_with_fee(100);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        ERR_NOT_ENOUGH_BALANCE_FOR_FEE,
+        "Expected failure as the fee isn't below the amount, but withdraw succeeded",
+    );
+
+    let res = call_withdraw_with_fee(10);
+    res.assert_success();
+    let result: WithdrawWithFeeResult = res.unwrap_borsh();
+    assert_eq!(result.result.amount, 100);
+    assert_eq!(result.fee, 10);
+}
+
+#[test]
+fn test_nep297_event_logs() {
+    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "ft_transfer",
+        json!({
+            "receiver_id": DEPOSITED_RECIPIENT,
+            "amount": "70",
+            "memo": "transfer memo"
+        })
+        .to_string()
+        .as_bytes(),
+        DEFAULT_GAS,
+        1,
+    );
+    res.assert_success();
+    let expected = json!({
+        "standard": "nep141",
+        "version": "1.0.0",
+        "event": "ft_transfer",
+        "data": [{
+            "old_owner_id": CONTRACT_ACC,
+            "new_owner_id": DEPOSITED_RECIPIENT,
+            "amount": "70",
+            "memo": "transfer memo"
+        }]
+    });
+    assert_eq!(event_logs(&res), vec![expected]);
+
+    let recipient_addr = validate_eth_address(RECIPIENT_ETH_ADDRESS);
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "withdraw",
+        &WithdrawCallArgs {
+            recipient_address: recipient_addr,
+            amount: 100,
+            fee: Some(10),
+            sender: None,
+        }
+        .try_to_vec()
+        .unwrap(),
+        DEFAULT_GAS,
+        1,
+    );
+    res.assert_success();
+    let expected = vec![
+        json!({
+            "standard": "nep141",
+            "version": "1.0.0",
+            "event": "ft_burn",
+            "data": [{ "owner_id": CONTRACT_ACC, "amount": "100" }]
+        }),
+        json!({
+            "standard": "aurora-eth-connector",
+            "version": "1.0.0",
+            "event": "withdrawal_requested",
+            "data": [{
+                "sender_id": CONTRACT_ACC,
+                "recipient_address": RECIPIENT_ETH_ADDRESS,
+                "amount": "100",
+                "fee": "10",
+                "eth_custodian_address": CUSTODIAN_ADDRESS.to_lowercase()
+            }]
+        }),
+    ];
+    assert_eq!(event_logs(&res), expected);
+}
+
+fn event_logs(res: &ExecutionResult) -> Vec<serde_json::Value> {
+    res.promise_results()
+        .into_iter()
+        .flatten()
+        .flat_map(|result| result.logs().clone())
+        .filter_map(|log| {
+            log.strip_prefix("EVENT_JSON:")
+                .map(|json| serde_json::from_str(json).unwrap())
+        })
+        .collect()
+}
+
 #[test]
 fn test_ft_transfer_call_eth() {
     let (master_account, contract) = init(CUSTODIAN_ADDRESS);
@@ -458,6 +593,29 @@ fn test_ft_transfer_call_eth() {
     assert_eq!(balance, transfer_amount);
 }
 
+#[test]
+fn test_ft_on_transfer_invalid_versioned_message() {
+    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
+
+    // The whole amount is returned to be refunded, rather than panicking
+    let amount: u128 = 50;
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "ft_on_transfer",
+        json!({
+            "sender_id": CONTRACT_ACC,
+            "amount": amount.to_string(),
+            "msg": r#"{"version": 2}"#,
+        })
+        .to_string()
+        .as_bytes(),
+        DEFAULT_GAS,
+        0,
+    );
+    res.assert_success();
+    assert_eq!(res.unwrap_json_value(), json!(amount.to_string()));
+}
+
 #[test]
 fn test_ft_transfer_call_without_message() {
     let (master_account, contract) = init(CUSTODIAN_ADDRESS);
@@ -467,223 +625,1556 @@ fn test_ft_transfer_call_without_message() {
     let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
     assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE);
 
-    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_FEE);
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE);
+
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "register_relayer",
+        &RegisterRelayerCallArgs {
+            address: validate_eth_address(CUSTODIAN_ADDRESS),
+        }
+        .try_to_vec()
+        .unwrap(),
+        DEFAULT_GAS,
+        0,
+    );
+    res.assert_success();
+
+    let transfer_amount = 50;
+    // Send to Aurora contract with wrong message should failed
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "ft_transfer_call",
+        json!({
+            "receiver_id": CONTRACT_ACC,
+            "amount": transfer_amount.to_string(),
+            "msg": "",
+        })
+        .to_string()
+        .as_bytes(),
+        DEFAULT_GAS,
+        1,
+    );
+    match res.outcome().status {
+        ExecutionStatus::Failure(_) => {}
+        _ => panic!("Expected execution failure"),
+    }
+
+    // Assert balances remain unchanged
+    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE);
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE);
+
+    // Sending to random account should not change balances
+    let transfer_amount = 22;
+    let res = recipient_account.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "ft_transfer_call",
+        json!({
+            "receiver_id": "some-test-acc",
+            "amount": transfer_amount.to_string(),
+            "msg": "",
+        })
+        .to_string()
+        .as_bytes(),
+        DEFAULT_GAS,
+        1,
+    );
+    res.assert_success();
+
+    // some-test-acc does not implement `ft_on_transfer` therefore the call fails and the transfer is reverted.
+    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE);
+    let balance = get_eth_on_near_balance(&master_account, "some-test-acc", CONTRACT_ACC);
+    assert_eq!(balance, 0);
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE);
+
+    // Sending to external receiver with empty message should be success
+    let dummy_ft_receiver = master_account.deploy(
+        &dummy_ft_receiver_bytes(),
+        "ft-rec".parse().unwrap(),
+        near_sdk_sim::STORAGE_AMOUNT,
+    );
+    let res = recipient_account.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "ft_transfer_call",
+        json!({
+            "receiver_id": dummy_ft_receiver.account_id(),
+            "amount": transfer_amount.to_string(),
+            "msg": "",
+        })
+        .to_string()
+        .as_bytes(),
+        DEFAULT_GAS,
+        1,
+    );
+    res.assert_success();
+
+    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE - transfer_amount);
+    let balance = get_eth_on_near_balance(
+        &master_account,
+        dummy_ft_receiver.account_id().as_ref(),
+        CONTRACT_ACC,
+    );
+    assert_eq!(balance, transfer_amount);
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE);
+
+    let balance = get_eth_balance(
+        &master_account,
+        validate_eth_address(RECIPIENT_ETH_ADDRESS),
+        CONTRACT_ACC,
+    );
+    assert_eq!(balance, 0);
+
+    let balance = get_eth_balance(
+        &master_account,
+        validate_eth_address(CUSTODIAN_ADDRESS),
+        CONTRACT_ACC,
+    );
+    assert_eq!(balance, 0);
+
+    let balance = total_supply(&master_account, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_AMOUNT);
+
+    let balance = total_eth_supply_on_near(&master_account, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_AMOUNT);
+
+    let balance = total_eth_supply_on_aurora(&master_account, CONTRACT_ACC);
+    assert_eq!(balance, 0);
+}
+
+/// Builds the proof of a log entry in the only receipt of a block.
+fn build_proof(log_entry: &aurora_engine::log_entry::LogEntry) -> Proof {
+    let mut receipt = rlp::RlpStream::new_list(4);
+    receipt.append(&1u8);
+    receipt.append(&21_000u64);
+    receipt.append(&vec![0u8; 256]);
+    receipt.begin_list(1);
+    receipt.append(log_entry);
+    let receipt_data = receipt.out().to_vec();
+
+    // The receipts trie is a single leaf, at the key `rlp(0)`
+    let mut leaf = rlp::RlpStream::new_list(2);
+    leaf.append(&vec![0x20u8, 0x80]);
+    leaf.append(&receipt_data);
+    let leaf = leaf.out().to_vec();
+
+    // Only the receipts root of the header is verified by the engine
+    let mut header = rlp::RlpStream::new_list(15);
+    for i in 0..15 {
+        match i {
+            5 => header.append(&aurora_engine_sdk::keccak(&leaf)),
+            _ => header.append_empty_data(),
+        };
+    }
+
+    Proof {
+        log_index: 0,
+        log_entry_data: rlp::encode(log_entry).to_vec(),
+        receipt_index: 0,
+        receipt_data,
+        header_data: header.out().to_vec(),
+        proof: vec![leaf],
+    }
+}
+
+#[test]
+fn test_deposit_with_0x_prefix() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+
+    let eth_custodian_address: [u8; 20] = {
+        let mut buf = [0u8; 20];
+        let bytes = hex::decode(CUSTODIAN_ADDRESS).unwrap();
+        buf.copy_from_slice(&bytes);
+        buf
+    };
+    let recipient_address = [10u8; 20];
+    let deposit_amount = U256::from(17);
+    let deposit_event = aurora_engine::deposit_event::DepositedEvent {
+        eth_custodian_address,
+        sender: [0u8; 20],
+        // Note the 0x prefix before the deposit address.
+        recipient: [
+            CONTRACT_ACC,
+            ":",
+            "0x",
+            hex::encode(&recipient_address).as_str(),
+        ]
+        .concat(),
+        amount: deposit_amount,
+        fee: U256::zero(),
+        aurora_recipient: None,
+    };
+
+    let event_schema = ethabi::Event {
+        name: aurora_engine::deposit_event::DEPOSITED_EVENT.into(),
+        inputs: aurora_engine::deposit_event::DepositedEvent::event_params(),
+        anonymous: false,
+    };
+    let log_entry = aurora_engine::log_entry::LogEntry {
+        address: eth_custodian_address.into(),
+        topics: vec![
+            event_schema.signature(),
+            // the sender is not important
+            crate::prelude::H256::zero(),
+        ],
+        data: ethabi::encode(&[
+            ethabi::Token::String(deposit_event.recipient),
+            ethabi::Token::Uint(deposit_event.amount),
+            ethabi::Token::Uint(deposit_event.fee),
+        ]),
+    };
+    let proof = build_proof(&log_entry);
+
+    let res = master_account.call(
+        contract.account_id(),
+        "deposit",
+        &proof.try_to_vec().unwrap(),
+        DEFAULT_GAS,
+        0,
+    );
+    res.assert_success();
+
+    let aurora_balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(aurora_balance, deposit_amount.low_u128());
+    let address_balance = get_eth_balance(&master_account, recipient_address, CONTRACT_ACC);
+    assert_eq!(address_balance, deposit_amount.low_u128());
+}
+
+#[test]
+fn test_deposit_with_new_event_schema() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+
+    let schema = DepositedEventSchema::WithNonce;
+    let log_entry = aurora_engine::log_entry::LogEntry {
+        address: validate_eth_address(CUSTODIAN_ADDRESS).into(),
+        topics: vec![schema.topic0().into(), crate::prelude::H256::zero()],
+        data: ethabi::encode(&[
+            ethabi::Token::String(DEPOSITED_RECIPIENT.to_string()),
+            ethabi::Token::Uint(U256::from(17)),
+            ethabi::Token::Uint(U256::zero()),
+            ethabi::Token::Uint(U256::from(1)),
+        ]),
+    };
+    let proof = build_proof(&log_entry);
+    let call_deposit = || {
+        master_account.call(
+            contract.account_id(),
+            "deposit",
+            &proof.try_to_vec().unwrap(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+
+    // The schema isn't accepted yet for the custodian
+    let res = call_deposit();
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        "ERR_DEPOSIT_EVENT_UNKNOWN_SCHEMA",
+        "Expected failure as the event schema isn't accepted, but deposit succeeded",
+    );
+
+    call_set_deposit_event_schema(&contract, schema, true);
+    call_deposit().assert_success();
+
+    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
+    assert_eq!(balance, 17);
+
+    // The basic schema is accepted by default, until it's rejected for the custodian
+    call_set_deposit_event_schema(&contract, DepositedEventSchema::Basic, false);
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        "ERR_DEPOSIT_EVENT_UNKNOWN_SCHEMA",
+        "Expected failure as the basic event schema is rejected, but deposit succeeded",
+    );
+    assert_proof_was_not_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+
+    call_set_deposit_event_schema(&contract, DepositedEventSchema::Basic, true);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    assert_proof_was_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+}
+
+fn call_set_deposit_event_schema(
+    contract: &UserAccount,
+    schema: DepositedEventSchema,
+    accepted: bool,
+) {
+    contract
+        .call(
+            CONTRACT_ACC.parse().unwrap(),
+            "set_deposit_event_schema",
+            &SetDepositEventSchemaArgs {
+                eth_custodian_address: CUSTODIAN_ADDRESS.to_string(),
+                schema,
+                accepted,
+            }
+            .try_to_vec()
+            .unwrap(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+}
+
+#[test]
+fn test_deposit_and_call() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    let recipient_address = [10u8; 20];
+    // The exit precompile fails on an empty input
+    let exit_to_ethereum = aurora_engine_precompiles::native::ExitToEthereum::ADDRESS.0;
+    let call_deposit = |sender: EthAddress, contract_address: EthAddress| {
+        let message = json!({
+            "version": 1,
+            "recipient": hex::encode(recipient_address),
+            "call": {
+                "contract": hex::encode(contract_address),
+                "input": "",
+            },
+        });
+        let mut sender_topic = [0u8; 32];
+        sender_topic[12..].copy_from_slice(&sender);
+        let log_entry = aurora_engine::log_entry::LogEntry {
+            address: validate_eth_address(CUSTODIAN_ADDRESS).into(),
+            topics: vec![
+                DepositedEventSchema::Basic.topic0().into(),
+                sender_topic.into(),
+            ],
+            data: ethabi::encode(&[
+                ethabi::Token::String(format!("{}:{}", CONTRACT_ACC, message)),
+                ethabi::Token::Uint(U256::from(17)),
+                ethabi::Token::Uint(U256::zero()),
+            ]),
+        };
+        master_account.call(
+            contract.account_id(),
+            "deposit",
+            &build_proof(&log_entry).try_to_vec().unwrap(),
+            DEFAULT_GAS,
+            0,
+        )
+    };
+
+    // Only the recipient may deposit into a call made on its behalf
+    let res = call_deposit([11u8; 20], exit_to_ethereum);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        ERR_DEPOSIT_CALL_NOT_BY_RECIPIENT,
+        "Expected failure as the sender isn't the recipient, but deposit succeeded",
+    );
+
+    // The deposit is kept when the call fails
+    call_deposit(recipient_address, exit_to_ethereum).assert_success();
+    let aurora_balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(aurora_balance, 17);
+    let address_balance = get_eth_balance(&master_account, recipient_address, CONTRACT_ACC);
+    assert_eq!(address_balance, 17);
+
+    // Contract storing its caller in the slot 0: CALLER PUSH1 0 SSTORE STOP
+    let init_code = hex::decode("6433600055006000526005601bf3").unwrap();
+    let res = master_account.call(
+        contract.account_id(),
+        "deploy_code",
+        &init_code,
+        DEFAULT_GAS,
+        0,
+    );
+    let result: SubmitResult = res.unwrap_borsh();
+    let mut contract_address = EthAddress::default();
+    match result.status {
+        TransactionStatus::Succeed(address) => contract_address.copy_from_slice(&address),
+        _ => panic!("Failed to deploy the contract"),
+    }
+
+    // The call succeeds, made by the recipient once the deposit is minted
+    call_deposit(recipient_address, contract_address).assert_success();
+    let address_balance = get_eth_balance(&master_account, recipient_address, CONTRACT_ACC);
+    assert_eq!(address_balance, 34);
+    let caller = master_account.view(
+        contract.account_id(),
+        "get_storage_at",
+        &GetStorageAtArgs {
+            address: contract_address,
+            key: [0u8; 32],
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+    assert_eq!(&caller.unwrap()[12..], &recipient_address);
+}
+
+#[test]
+fn test_deposit_with_same_proof() {
+    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
+
+    assert_proof_was_not_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    for p in promises.iter() {
+        assert!(p.is_some());
+        let p = p.as_ref().unwrap();
+        p.assert_success()
+    }
+
+    assert_proof_was_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    let promise = &promises[promises.len() - 2];
+    assert_execution_status_failure(
+        promise.as_ref().unwrap().outcome().clone().status,
+        "ERR_PROOF_EXIST",
+        "Expected failure as the provided proof already exists, but deposit succeeded",
+    );
+}
+
+#[test]
+fn test_deposit_with_invalid_proof() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    let mut proof: Proof = serde_json::from_str(PROOF_DATA_NEAR).unwrap();
+    // Tamper with the gas used of the receipt, which is no longer in the receipts trie
+    proof.receipt_data[5] ^= 1;
+
+    let res = master_account.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "deposit",
+        &proof.try_to_vec().unwrap(),
+        DEFAULT_GAS,
+        0,
+    );
+    let promises = res.promise_results();
+    let promise = &promises[promises.len() - 2];
+    assert_execution_status_failure(
+        promise.as_ref().unwrap().outcome().clone().status,
+        "ERR_PROOF_INVALID_TRIE_PROOF",
+        "Expected failure as the receipt is not in the receipts trie, but deposit succeeded",
+    );
+
+    assert_proof_was_not_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+}
+
+fn call_rotate_eth_custodian(contract: &UserAccount, deprecation_height: u64) -> ExecutionResult {
+    contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "rotate_eth_custodian",
+        &RotateEthCustodianArgs {
+            eth_custodian_address: NEW_CUSTODIAN_ADDRESS.into(),
+            activation_height: PROOF_DATA_NEAR_BLOCK,
+            deprecation_height,
+        }
+        .try_to_vec()
+        .unwrap(),
+        DEFAULT_GAS,
+        0,
+    )
+}
+
+#[test]
+fn test_rotate_eth_custodian() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_rotate_eth_custodian(&contract, PROOF_DATA_NEAR_BLOCK + 1).assert_success();
+
+    let custodians = master_account
+        .view(CONTRACT_ACC.parse().unwrap(), "get_eth_custodians", &[])
+        .unwrap();
+    let custodians: Vec<EthCustodian> = BorshDeserialize::try_from_slice(&custodians).unwrap();
+    assert_eq!(
+        custodians,
+        vec![
+            EthCustodian {
+                address: validate_eth_address(CUSTODIAN_ADDRESS),
+                activation_height: 0,
+                deprecation_height: Some(PROOF_DATA_NEAR_BLOCK + 1),
+            },
+            EthCustodian {
+                address: validate_eth_address(NEW_CUSTODIAN_ADDRESS),
+                activation_height: PROOF_DATA_NEAR_BLOCK,
+                deprecation_height: None,
+            },
+        ]
+    );
+
+    // The in-flight deposit to the previous custodian is accepted
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    for p in promises.iter() {
+        p.as_ref().unwrap().assert_success();
+    }
+    assert_proof_was_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+
+    // Withdrawals are made from the new custodian
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "withdraw",
+        &WithdrawCallArgs {
+            recipient_address: validate_eth_address(RECIPIENT_ETH_ADDRESS),
+            amount: 100,
+            fee: None,
+            sender: None,
+        }
+        .try_to_vec()
+        .unwrap(),
+        DEFAULT_GAS,
+        1,
+    );
+    res.assert_success();
+    let result: WithdrawResult = res.unwrap_borsh();
+    assert_eq!(
+        result.eth_custodian_address,
+        validate_eth_address(NEW_CUSTODIAN_ADDRESS)
+    );
+}
+
+#[test]
+fn test_deposit_to_deprecated_custodian() {
+    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_rotate_eth_custodian(&contract, PROOF_DATA_NEAR_BLOCK).assert_success();
+
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    let promise = &promises[promises.len() - 2];
+    assert_execution_status_failure(
+        promise.as_ref().unwrap().outcome().clone().status,
+        "ERR_WRONG_EVENT_ADDRESS",
+        "Expected failure as the custodian of the deposit is deprecated, but deposit succeeded",
+    );
+
+    assert_proof_was_not_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+}
+
+#[test]
+fn test_rotate_eth_custodian_with_inconsistent_heights() {
+    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
+
+    // The deposits between the deprecation and the activation would be rejected
+    let res = call_rotate_eth_custodian(&contract, PROOF_DATA_NEAR_BLOCK - 1);
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        ERR_DEPRECATION_BELOW_ACTIVATION,
+        "Expected failure as the deprecation is below the activation, but rotation succeeded",
+    );
+
+    // The deposits of blocks Ethereum already reached would be rejected
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    for p in promises.iter() {
+        p.as_ref().unwrap().assert_success();
+    }
+    let res = call_rotate_eth_custodian(&contract, PROOF_DATA_NEAR_BLOCK);
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        ERR_DEPRECATION_BELOW_LAST_DEPOSIT,
+        "Expected failure as the deprecation is below the last deposit, but rotation succeeded",
+    );
+
+    call_rotate_eth_custodian(&contract, PROOF_DATA_NEAR_BLOCK + 1).assert_success();
+}
+
+fn call_set_bridge_limits(contract: &UserAccount, limits: BridgeLimits) {
+    contract
+        .call(
+            CONTRACT_ACC.parse().unwrap(),
+            "set_bridge_limits",
+            &limits.try_to_vec().unwrap(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+}
+
+/// Withdraws from Aurora, as the exit precompile does on behalf of the EVM `sender`.
+fn call_withdraw_from_aurora(
+    contract: &UserAccount,
+    sender: EthAddress,
+    amount: u128,
+) -> ExecutionResult {
+    contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "withdraw",
+        &WithdrawCallArgs {
+            recipient_address: validate_eth_address(RECIPIENT_ETH_ADDRESS),
+            amount,
+            fee: None,
+            sender: Some(sender),
+        }
+        .try_to_vec()
+        .unwrap(),
+        DEFAULT_GAS,
+        1,
+    )
+}
+
+fn call_release_withdrawal(contract: &UserAccount, id: u64) -> ExecutionResult {
+    contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "release_withdrawal",
+        &ReleaseWithdrawalArgs { id }.try_to_vec().unwrap(),
+        DEFAULT_GAS,
+        0,
+    )
+}
+
+fn call_withdraw(contract: &UserAccount, amount: u128) -> ExecutionResult {
+    call_withdraw_from_aurora(contract, AURORA_SENDER, amount)
+}
+
+fn call_cancel_withdrawal(contract: &UserAccount, id: u64) -> ExecutionResult {
+    contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "cancel_withdrawal",
+        &CancelWithdrawalArgs { id }.try_to_vec().unwrap(),
+        DEFAULT_GAS,
+        0,
+    )
+}
+
+fn call_release_deposit(contract: &UserAccount, id: u64) -> ExecutionResult {
+    contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "release_deposit",
+        &ReleaseDepositArgs { id }.try_to_vec().unwrap(),
+        DEFAULT_GAS,
+        0,
+    )
+}
+
+/// EVM sender of the withdrawals from Aurora made in the tests.
+const AURORA_SENDER: EthAddress = [7u8; 20];
+
+/// Caps the withdrawals from Aurora, i.e. made by the contract account itself.
+fn aurora_withdraw_limits(global: u128, queue_delay_blocks: Option<u64>) -> BridgeLimits {
+    BridgeLimits {
+        period_blocks: 1000,
+        withdraw_from_aurora: WithdrawCaps {
+            global: Some(global),
+            per_account: None,
+        },
+        queue_delay_blocks,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn test_withdraw_over_cap_is_rejected() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    call_set_bridge_limits(&contract, aurora_withdraw_limits(150, None));
+
+    call_withdraw(&contract, 100).assert_success();
+    let res = call_withdraw(&contract, 100);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        ERR_WITHDRAW_CAP_EXCEEDED,
+        "Expected failure as the withdrawal is over the cap, but withdraw succeeded",
+    );
+
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE - 100);
+}
+
+#[test]
+fn test_withdraw_over_cap_is_queued() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    call_set_bridge_limits(&contract, aurora_withdraw_limits(0, Some(1000)));
+
+    // The withdrawal is burned, but its result is only returned once released
+    let res = call_withdraw(&contract, 100);
+    res.assert_success();
+    assert_eq!(res.unwrap_borsh::<u64>(), 0);
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE - 100);
+
+    let res = call_release_withdrawal(&contract, 0);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        "ERR_WITHDRAWAL_NOT_RELEASED",
+        "Expected failure as the withdrawal is delayed, but it was released",
+    );
+
+    call_set_bridge_limits(&contract, aurora_withdraw_limits(0, Some(0)));
+    let res = call_withdraw(&contract, 50);
+    assert_eq!(res.unwrap_borsh::<u64>(), 1);
+    let res = call_release_withdrawal(&contract, 1);
+    res.assert_success();
+    let result: WithdrawResult = res.unwrap_borsh();
+    assert_eq!(result.amount, 50);
+    assert_eq!(
+        result.recipient_id,
+        validate_eth_address(RECIPIENT_ETH_ADDRESS)
+    );
+
+    // A withdrawal is only released once
+    let res = call_release_withdrawal(&contract, 1);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        "ERR_PENDING_WITHDRAWAL_NOT_FOUND",
+        "Expected failure as the withdrawal was released, but it was released again",
+    );
+}
+
+#[test]
+fn test_withdraw_cap_per_aurora_sender() {
+    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    call_set_bridge_limits(
+        &contract,
+        BridgeLimits {
+            period_blocks: 1000,
+            withdraw_from_aurora: WithdrawCaps {
+                global: None,
+                per_account: Some(150),
+            },
+            ..Default::default()
+        },
+    );
+
+    // The cap applies to each EVM sender, not to the contract account
+    call_withdraw_from_aurora(&contract, [1u8; 20], 100).assert_success();
+    call_withdraw_from_aurora(&contract, [2u8; 20], 100).assert_success();
+    let res = call_withdraw_from_aurora(&contract, [1u8; 20], 100);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        ERR_WITHDRAW_CAP_EXCEEDED,
+        "Expected failure as the withdrawal is over the cap of the sender, but withdraw succeeded",
+    );
+}
+
+#[test]
+fn test_cancel_queued_withdrawal() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    call_set_bridge_limits(&contract, aurora_withdraw_limits(0, Some(1000)));
+
+    let res = call_withdraw(&contract, 100);
+    assert_eq!(res.unwrap_borsh::<u64>(), 0);
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE - 100);
+
+    // Only the contract account may cancel a withdrawal
+    let res = master_account.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "cancel_withdrawal",
+        &CancelWithdrawalArgs { id: 0 }.try_to_vec().unwrap(),
+        DEFAULT_GAS,
+        0,
+    );
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        "ERR_PRIVATE_CALL",
+        "Expected failure as only the owner can cancel withdrawals, but user cancelled one",
+    );
+
+    // The tokens burned on NEAR back the refund of the sender on Aurora
+    call_cancel_withdrawal(&contract, 0).assert_success();
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE);
+    let balance = get_eth_balance(&master_account, AURORA_SENDER, CONTRACT_ACC);
+    assert_eq!(balance, 100);
+    let audit = get_supply_audit(&master_account);
+    assert_eq!(audit.withdrawn, 0);
+
+    // A cancelled withdrawal can't be released
+    let res = call_release_withdrawal(&contract, 0);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        "ERR_PENDING_WITHDRAWAL_NOT_FOUND",
+        "Expected failure as the withdrawal was cancelled, but it was released",
+    );
+}
+
+#[test]
+fn test_deposit_above_max_is_queued() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_set_bridge_limits(
+        &contract,
+        BridgeLimits {
+            max_deposit: Some(DEPOSITED_AMOUNT - 1),
+            queue_delay_blocks: Some(0),
+            ..Default::default()
+        },
+    );
+
+    // The proof is used, but the tokens are only minted once released
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    for p in promises.iter() {
+        p.as_ref().unwrap().assert_success();
+    }
+    assert_proof_was_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
+    assert_eq!(balance, 0);
+
+    call_release_deposit(&master_account, 0).assert_success();
+    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE);
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE);
+
+    // A deposit is only released once
+    let res = call_release_deposit(&master_account, 0);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        "ERR_PENDING_DEPOSIT_NOT_FOUND",
+        "Expected failure as the deposit was released, but it was released again",
+    );
+}
+
+#[test]
+fn test_deposit_above_max() {
+    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_set_bridge_limits(
+        &contract,
+        BridgeLimits {
+            max_deposit: Some(DEPOSITED_AMOUNT - 1),
+            ..Default::default()
+        },
+    );
+
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    let promise = &promises[promises.len() - 2];
+    assert_execution_status_failure(
+        promise.as_ref().unwrap().outcome().clone().status,
+        ERR_DEPOSIT_ABOVE_MAX,
+        "Expected failure as the deposit is above the maximum, but deposit succeeded",
+    );
+
+    // The deposit can be retried once the maximum is raised
+    assert_proof_was_not_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+    call_set_bridge_limits(&contract, BridgeLimits::default());
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    for p in promises.iter() {
+        p.as_ref().unwrap().assert_success();
+    }
+    assert_proof_was_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+}
+
+#[test]
+fn test_supply_audit() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    call_withdraw(&contract, 100).assert_success();
+
+    let audit = get_supply_audit(&master_account);
+    assert_eq!(audit.total_eth_supply_on_near, DEPOSITED_AMOUNT - 100);
+    assert_eq!(audit.deposited, DEPOSITED_AMOUNT);
+    assert_eq!(audit.withdrawn, 100);
+    assert_eq!(audit.evm_drift, 0);
+    assert_eq!(audit.bridge_drift, 0);
+
+    // ETH minted in the EVM without a deposit isn't tracked by the running sum,
+    // but shows up once the sum recomputed from the state is reported
+    call_set_bridge_limits(
+        &contract,
+        BridgeLimits {
+            max_supply_drift: Some(10),
+            ..Default::default()
+        },
+    );
+    contract
+        .call(
+            CONTRACT_ACC.parse().unwrap(),
+            "mint_account",
+            &([1u8; 20], 0u64, 50u64).try_to_vec().unwrap(),
+            DEFAULT_GAS,
+            0,
+        )
+        .assert_success();
+    assert_eq!(get_supply_audit(&master_account).evm_drift, 0);
+
+    let audit = call_reconcile_supply(&contract, 50);
+    assert_eq!(U256::from_big_endian(&audit.evm_balances), U256::from(50));
+    assert_eq!(audit.evm_drift, 50);
+    // The bridged amounts were already recorded, so their baseline is kept
+    assert_eq!(audit.deposited, DEPOSITED_AMOUNT);
+    assert_eq!(audit.bridge_drift, 0);
+    assert_eq!(get_supply_audit(&master_account), audit);
+
+    let res = call_withdraw(&contract, 100);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        ERR_SUPPLY_DRIFT_ABOVE_TOLERANCE,
+        "Expected failure as the supply drift is above the tolerance, but withdraw succeeded",
+    );
+
+    // Once the EVM balances match the supply again the withdrawals resume
+    let audit = call_reconcile_supply(&contract, 0);
+    assert_eq!(audit.evm_drift, 0);
+    assert_eq!(audit.bridge_drift, 0);
+    call_withdraw(&contract, 100).assert_success();
+}
+
+fn call_reconcile_supply(contract: &UserAccount, evm_balances: u64) -> SupplyAudit {
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "reconcile_supply",
+        &ReconcileSupplyArgs {
+            evm_balances: crate::prelude::u256_to_arr(&U256::from(evm_balances)),
+        }
+        .try_to_vec()
+        .unwrap(),
+        DEFAULT_GAS,
+        0,
+    );
+    res.assert_success();
+    res.unwrap_borsh()
+}
+
+fn get_supply_audit(master_account: &UserAccount) -> SupplyAudit {
+    let audit = master_account
+        .view(CONTRACT_ACC.parse().unwrap(), "get_supply_audit", &[])
+        .unwrap();
+    SupplyAudit::try_from_slice(&audit).unwrap()
+}
+
+#[test]
+fn test_get_used_proof() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    let proof_key = promises
+        .into_iter()
+        .flatten()
+        .flat_map(|result| result.logs().clone())
+        .filter_map(|log| {
+            log.strip_prefix("EVENT_JSON:")
+                .map(|json| serde_json::from_str::<serde_json::Value>(json).unwrap())
+        })
+        .find(|event| event["event"] == "deposit_finalized")
+        .map(|event| event["data"][0]["proof_key"].as_str().unwrap().to_string())
+        .unwrap();
+    let mut key = [0u8; 32];
+    key.copy_from_slice(&hex::decode(proof_key).unwrap());
+
+    let used_proof = get_used_proof(&master_account, key).unwrap();
+    assert_eq!(used_proof.block_number, PROOF_DATA_NEAR_BLOCK);
+    assert_eq!(used_proof.recipient, DEPOSITED_RECIPIENT);
+    assert_eq!(used_proof.amount, DEPOSITED_AMOUNT);
+    assert!(used_proof.finalized_at > 0);
+    assert_eq!(get_used_proof(&master_account, [0u8; 32]), None);
+
+    // Only the proofs stored with their legacy key are migrated
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "migrate_used_proofs",
+        &vec![MigrateUsedProofArgs {
+            proof: serde_json::from_str(PROOF_DATA_NEAR).unwrap(),
+            used_proof,
+        }]
+        .try_to_vec()
+        .unwrap(),
+        DEFAULT_GAS,
+        0,
+    );
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        "ERR_PROOF_NOT_FOUND",
+        "Expected failure as the proof isn't stored with its legacy key, but migration succeeded",
+    );
+}
+
+fn get_used_proof(master_account: &UserAccount, key: [u8; 32]) -> Option<UsedProof> {
+    let used_proof = master_account
+        .view(
+            CONTRACT_ACC.parse().unwrap(),
+            "get_used_proof",
+            &key.try_to_vec().unwrap(),
+        )
+        .unwrap();
+    Option::<UsedProof>::try_from_slice(&used_proof).unwrap()
+}
+
+#[test]
+fn test_migrate_legacy_used_proof() {
+    let mut runner = test_utils::deploy_evm();
+    let aurora_account_id = runner.aurora_account_id.clone();
+    // The deposits are finished as the callback of a successful proof verification
+    runner.promise_results = vec![PromiseResult::Successful(true.try_to_vec().unwrap())];
+    let proof: Proof = serde_json::from_str(PROOF_DATA_NEAR).unwrap();
+    let key = proof.get_key();
+
+    // Finish a deposit scheduled before the proofs were keyed by their hash
+    let args = LegacyFinishDepositCallArgs {
+        new_owner_id: str_to_account_id(DEPOSITED_RECIPIENT),
+        amount: DEPOSITED_AMOUNT,
+        proof_key: legacy_proof_key(&key),
+        relayer_id: str_to_account_id(CONTRACT_ACC),
+        fee: DEPOSITED_FEE,
+        msg: None,
+    };
+    let (_, maybe_error) = runner.call(
+        "finish_deposit",
+        &aurora_account_id,
+        args.try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_none());
+    let balance = runner_eth_on_near_balance(&runner, DEPOSITED_RECIPIENT);
+    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE);
+    // The proof is used, but has no deposit recorded
+    assert!(runner_is_used_proof(&runner, &proof));
+    assert_eq!(runner_used_proof(&runner, key), None);
+
+    let used_proof = UsedProof {
+        block_number: PROOF_DATA_NEAR_BLOCK,
+        recipient: DEPOSITED_RECIPIENT.to_string(),
+        amount: DEPOSITED_AMOUNT,
+        finalized_at: 1,
+    };
+    // The used proof must be of the block of the proof
+    let (_, maybe_error) = runner.call(
+        "migrate_used_proofs",
+        &aurora_account_id,
+        vec![MigrateUsedProofArgs {
+            proof: proof.clone(),
+            used_proof: UsedProof {
+                block_number: PROOF_DATA_NEAR_BLOCK + 1,
+                ..used_proof.clone()
+            },
+        }]
+        .try_to_vec()
+        .unwrap(),
+    );
+    assert!(maybe_error.is_some());
+
+    let migrate_args = vec![MigrateUsedProofArgs {
+        proof: proof.clone(),
+        used_proof: used_proof.clone(),
+    }]
+    .try_to_vec()
+    .unwrap();
+    let (_, maybe_error) = runner.call(
+        "migrate_used_proofs",
+        &aurora_account_id,
+        migrate_args.clone(),
+    );
+    assert!(maybe_error.is_none());
+    assert_eq!(runner_used_proof(&runner, key), Some(used_proof));
+
+    // The proof can't be migrated twice, since its legacy key is removed
+    let (_, maybe_error) = runner.call(
+        "migrate_used_proofs",
+        &aurora_account_id,
+        migrate_args.clone(),
+    );
+    assert!(maybe_error.is_some());
+
+    // Once the migration is finished, the legacy keys are no longer used
+    let (_, maybe_error) = runner.call(
+        "finish_used_proofs_migration",
+        &aurora_account_id,
+        Vec::new(),
+    );
+    assert!(maybe_error.is_none());
+    assert!(runner_is_used_proof(&runner, &proof));
+    let (_, maybe_error) = runner.call("migrate_used_proofs", &aurora_account_id, migrate_args);
+    assert!(maybe_error.is_some());
+    let (_, maybe_error) = runner.call(
+        "finish_deposit",
+        &aurora_account_id,
+        LegacyFinishDepositCallArgs {
+            proof_key: legacy_proof_key(&[3u8; 32]),
+            ..args
+        }
+        .try_to_vec()
+        .unwrap(),
+    );
+    assert!(maybe_error.is_some());
+
+    // Depositing the migrated proof again is rejected
+    let args = FinishDepositCallArgs {
+        new_owner_id: str_to_account_id(DEPOSITED_RECIPIENT),
+        amount: DEPOSITED_AMOUNT,
+        proof_key: key,
+        relayer_id: str_to_account_id(CONTRACT_ACC),
+        fee: DEPOSITED_FEE,
+        msg: None,
+        block_number: PROOF_DATA_NEAR_BLOCK,
+        recipient: DEPOSITED_RECIPIENT.to_string(),
+        deposit_call: None,
+    };
+    let (_, maybe_error) = runner.call(
+        "finish_deposit",
+        &aurora_account_id,
+        args.try_to_vec().unwrap(),
+    );
+    assert!(maybe_error.is_some());
+    let balance = runner_eth_on_near_balance(&runner, DEPOSITED_RECIPIENT);
+    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE);
+}
+
+fn runner_is_used_proof(runner: &test_utils::AuroraRunner, proof: &Proof) -> bool {
+    let (outcome, maybe_error) =
+        runner
+            .one_shot()
+            .call("is_used_proof", "getter", proof.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let is_used_proof = outcome.unwrap().return_data.as_value().unwrap();
+    bool::try_from_slice(&is_used_proof).unwrap()
+}
+
+fn runner_used_proof(runner: &test_utils::AuroraRunner, key: [u8; 32]) -> Option<UsedProof> {
+    let (outcome, maybe_error) =
+        runner
+            .one_shot()
+            .call("get_used_proof", "getter", key.try_to_vec().unwrap());
+    assert!(maybe_error.is_none());
+    let used_proof = outcome.unwrap().return_data.as_value().unwrap();
+    Option::<UsedProof>::try_from_slice(&used_proof).unwrap()
+}
+
+fn runner_eth_on_near_balance(runner: &test_utils::AuroraRunner, acc: &str) -> u128 {
+    let (outcome, maybe_error) = runner.one_shot().call(
+        "ft_balance_of",
+        "getter",
+        json!({ "account_id": acc }).to_string().into_bytes(),
+    );
+    assert!(maybe_error.is_none());
+    let val_str = String::from_utf8(outcome.unwrap().return_data.as_value().unwrap()).unwrap();
+    let val = &val_str[1..val_str.len() - 1];
+    val.parse().unwrap()
+}
+
+#[test]
+fn test_freeze_account() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    let user_account = create_user_account(&master_account);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    let target = FreezeTarget::NearAccount(str_to_account_id(DEPOSITED_RECIPIENT));
+
+    // Only the owner can freeze accounts
+    let res = call_freeze(&user_account, &target, 7);
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        "ERR_PRIVATE_CALL",
+        "Expected failure as only the owner can freeze accounts, but user froze an account",
+    );
 
-    let res = contract.call(
+    let res = call_freeze(&contract, &target, 7);
+    res.assert_success();
+    let expected = json!({
+        "standard": "aurora-eth-connector",
+        "version": "1.0.0",
+        "event": "account_frozen",
+        "data": [{
+            "target": DEPOSITED_RECIPIENT,
+            "reason": 7
+        }]
+    });
+    assert_eq!(event_logs(&res), vec![expected]);
+    let freeze = get_freeze(&master_account, &target).unwrap();
+    assert_eq!(freeze.reason, 7);
+    assert!(freeze.frozen_at > 0);
+
+    let call_ft_transfer = || {
+        user_account.call(
+            CONTRACT_ACC.parse().unwrap(),
+            "ft_transfer",
+            json!({
+                "receiver_id": CONTRACT_ACC,
+                "amount": "10",
+            })
+            .to_string()
+            .as_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+    };
+    let res = call_ft_transfer();
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        ERR_ACCOUNT_FROZEN,
+        "Expected failure as the account is frozen, but ft_transfer succeeded",
+    );
+    let res = user_account.call(
         CONTRACT_ACC.parse().unwrap(),
-        "register_relayer",
-        &RegisterRelayerCallArgs {
-            address: validate_eth_address(CUSTODIAN_ADDRESS),
+        "withdraw",
+        &WithdrawCallArgs {
+            recipient_address: validate_eth_address(RECIPIENT_ETH_ADDRESS),
+            amount: 10,
+            fee: None,
+            sender: None,
         }
         .try_to_vec()
         .unwrap(),
         DEFAULT_GAS,
-        0,
+        1,
+    );
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        ERR_ACCOUNT_FROZEN,
+        "Expected failure as the account is frozen, but withdraw succeeded",
+    );
+    assert_eq!(
+        get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC),
+        DEPOSITED_AMOUNT - DEPOSITED_FEE
     );
-    res.assert_success();
 
-    let transfer_amount = 50;
-    // Send to Aurora contract with wrong message should failed
-    let res = contract.call(
-        CONTRACT_ACC.parse().unwrap(),
-        "ft_transfer_call",
-        json!({
-            "receiver_id": CONTRACT_ACC,
-            "amount": transfer_amount.to_string(),
-            "msg": "",
-        })
-        .to_string()
-        .as_bytes(),
-        DEFAULT_GAS,
-        1,
+    let res = call_unfreeze(&contract, &target);
+    res.assert_success();
+    assert_eq!(get_freeze(&master_account, &target), None);
+    call_ft_transfer().assert_success();
+    assert_eq!(
+        get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC),
+        DEPOSITED_AMOUNT - DEPOSITED_FEE - 10
     );
-    match res.outcome().status {
-        ExecutionStatus::Failure(_) => {}
-        _ => panic!("Expected execution failure"),
-    }
 
-    // Assert balances remain unchanged
-    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE);
-    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_FEE);
+    let res = call_unfreeze(&contract, &target);
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        "ERR_NOT_FROZEN",
+        "Expected failure as the account isn't frozen, but unfreeze succeeded",
+    );
+}
 
-    // Sending to random account should not change balances
-    let transfer_amount = 22;
-    let res = recipient_account.call(
+fn call_freeze(account: &UserAccount, target: &FreezeTarget, reason: u32) -> ExecutionResult {
+    account.call(
         CONTRACT_ACC.parse().unwrap(),
-        "ft_transfer_call",
-        json!({
-            "receiver_id": "some-test-acc",
-            "amount": transfer_amount.to_string(),
-            "msg": "",
-        })
-        .to_string()
-        .as_bytes(),
+        "freeze",
+        &FreezeArgs {
+            target: target.clone(),
+            reason,
+        }
+        .try_to_vec()
+        .unwrap(),
         DEFAULT_GAS,
-        1,
-    );
-    res.assert_success();
-
-    // some-test-acc does not implement `ft_on_transfer` therefore the call fails and the transfer is reverted.
-    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE);
-    let balance = get_eth_on_near_balance(&master_account, "some-test-acc", CONTRACT_ACC);
-    assert_eq!(balance, 0);
-    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_FEE);
+        0,
+    )
+}
 
-    // Sending to external receiver with empty message should be success
-    let dummy_ft_receiver = master_account.deploy(
-        &dummy_ft_receiver_bytes(),
-        "ft-rec".parse().unwrap(),
-        near_sdk_sim::STORAGE_AMOUNT,
-    );
-    let res = recipient_account.call(
+fn call_unfreeze(account: &UserAccount, target: &FreezeTarget) -> ExecutionResult {
+    account.call(
         CONTRACT_ACC.parse().unwrap(),
-        "ft_transfer_call",
-        json!({
-            "receiver_id": dummy_ft_receiver.account_id(),
-            "amount": transfer_amount.to_string(),
-            "msg": "",
-        })
-        .to_string()
-        .as_bytes(),
+        "unfreeze",
+        &target.try_to_vec().unwrap(),
         DEFAULT_GAS,
-        1,
-    );
-    res.assert_success();
+        0,
+    )
+}
 
-    let balance = get_eth_on_near_balance(&master_account, DEPOSITED_RECIPIENT, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_AMOUNT - DEPOSITED_FEE - transfer_amount);
-    let balance = get_eth_on_near_balance(
+fn get_freeze(master_account: &UserAccount, target: &FreezeTarget) -> Option<Freeze> {
+    let freeze = master_account
+        .view(
+            CONTRACT_ACC.parse().unwrap(),
+            "get_freeze",
+            &target.try_to_vec().unwrap(),
+        )
+        .unwrap();
+    Option::<Freeze>::try_from_slice(&freeze).unwrap()
+}
+
+#[test]
+fn test_storage_management() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    let user_account = create_user_account(&master_account);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+    let balance = DEPOSITED_AMOUNT - DEPOSITED_FEE;
+
+    let bounds = storage_view(&master_account, "storage_balance_bounds", json!({}));
+    let min = bounds["min"].as_str().unwrap().to_string();
+    assert_eq!(bounds, json!({ "min": min, "max": min }));
+    let storage_balance = storage_view(
         &master_account,
-        dummy_ft_receiver.account_id().as_ref(),
-        CONTRACT_ACC,
+        "storage_balance_of",
+        json!({ "account_id": DEPOSITED_RECIPIENT }),
     );
-    assert_eq!(balance, transfer_amount);
-    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_FEE);
-
-    let balance = get_eth_balance(
+    assert_eq!(storage_balance, json!({ "total": min, "available": "0" }));
+    let storage_balance = storage_view(
         &master_account,
-        validate_eth_address(RECIPIENT_ETH_ADDRESS),
-        CONTRACT_ACC,
+        "storage_balance_of",
+        json!({ "account_id": "unregistered.root" }),
     );
-    assert_eq!(balance, 0);
+    assert_eq!(storage_balance, serde_json::Value::Null);
 
-    let balance = get_eth_balance(
+    let call_storage_unregister = |args: serde_json::Value| {
+        user_account.call(
+            CONTRACT_ACC.parse().unwrap(),
+            "storage_unregister",
+            args.to_string().as_bytes(),
+            DEFAULT_GAS,
+            1,
+        )
+    };
+
+    // The balance is burned only if forced
+    let res = call_storage_unregister(json!({}));
+    assert_execution_status_failure(
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        "ERR_FAILED_UNREGISTER_ACCOUNT_POSITIVE_BALANCE",
+        "Expected failure as the account has a balance, but storage_unregister succeeded",
+    );
+
+    // The recipient was registered by the deposit and paid no storage deposit,
+    // so none is refunded from the balance of the contract
+    let contract_balance = contract.account().unwrap().amount;
+    let res = call_storage_unregister(json!({ "force": true }));
+    res.assert_success();
+    assert_eq!(res.unwrap_json_value(), json!(true));
+    assert!(contract.account().unwrap().amount >= contract_balance);
+    let expected = json!({
+        "standard": "nep141",
+        "version": "1.0.0",
+        "event": "ft_burn",
+        "data": [{
+            "owner_id": DEPOSITED_RECIPIENT,
+            "amount": balance.to_string(),
+            "memo": "storage_unregister"
+        }]
+    });
+    assert_eq!(event_logs(&res), vec![expected]);
+    let storage_balance = storage_view(
         &master_account,
-        validate_eth_address(CUSTODIAN_ADDRESS),
-        CONTRACT_ACC,
+        "storage_balance_of",
+        json!({ "account_id": DEPOSITED_RECIPIENT }),
     );
-    assert_eq!(balance, 0);
+    assert_eq!(storage_balance, serde_json::Value::Null);
+    assert_eq!(
+        total_eth_supply_on_near(&master_account, CONTRACT_ACC),
+        DEPOSITED_AMOUNT - balance
+    );
+    let audit = get_supply_audit(&master_account);
+    assert_eq!(audit.burned, balance);
+    assert_eq!(audit.bridge_drift, 0);
 
-    let balance = total_supply(&master_account, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_AMOUNT);
+    let res = call_storage_unregister(json!({ "force": true }));
+    res.assert_success();
+    assert_eq!(res.unwrap_json_value(), json!(false));
 
-    let balance = total_eth_supply_on_near(&master_account, CONTRACT_ACC);
-    assert_eq!(balance, DEPOSITED_AMOUNT);
+    // The storage deposit paid by the account is refunded when it unregisters
+    let min: u128 = min.parse().unwrap();
+    user_account
+        .call(
+            CONTRACT_ACC.parse().unwrap(),
+            "storage_deposit",
+            json!({}).to_string().as_bytes(),
+            DEFAULT_GAS,
+            min,
+        )
+        .assert_success();
+    let contract_balance = contract.account().unwrap().amount;
+    let res = call_storage_unregister(json!({}));
+    res.assert_success();
+    assert_eq!(res.unwrap_json_value(), json!(true));
+    assert!(contract.account().unwrap().amount < contract_balance);
+}
 
-    let balance = total_eth_supply_on_aurora(&master_account, CONTRACT_ACC);
-    assert_eq!(balance, 0);
+fn storage_view(
+    master_account: &UserAccount,
+    method: &str,
+    args: serde_json::Value,
+) -> serde_json::Value {
+    let result = master_account
+        .view(
+            CONTRACT_ACC.parse().unwrap(),
+            method,
+            args.to_string().as_bytes(),
+        )
+        .unwrap();
+    serde_json::from_slice(&result).unwrap()
 }
 
 #[test]
-fn test_deposit_with_0x_prefix() {
+fn test_set_ft_metadata() {
     let (master_account, contract) = init(CUSTODIAN_ADDRESS);
-
-    let eth_custodian_address: [u8; 20] = {
-        let mut buf = [0u8; 20];
-        let bytes = hex::decode(CUSTODIAN_ADDRESS).unwrap();
-        buf.copy_from_slice(&bytes);
-        buf
-    };
-    let recipient_address = [10u8; 20];
-    let deposit_amount = U256::from(17);
-    let deposit_event = aurora_engine::deposit_event::DepositedEvent {
-        eth_custodian_address,
-        sender: [0u8; 20],
-        // Note the 0x prefix before the deposit address.
-        recipient: [
-            CONTRACT_ACC,
-            ":",
-            "0x",
-            hex::encode(&recipient_address).as_str(),
-        ]
-        .concat(),
-        amount: deposit_amount,
-        fee: U256::zero(),
-    };
-
-    let event_schema = ethabi::Event {
-        name: aurora_engine::deposit_event::DEPOSITED_EVENT.into(),
-        inputs: aurora_engine::deposit_event::DepositedEvent::event_params(),
-        anonymous: false,
-    };
-    let log_entry = aurora_engine::log_entry::LogEntry {
-        address: eth_custodian_address.into(),
-        topics: vec![
-            event_schema.signature(),
-            // the sender is not important
-            crate::prelude::H256::zero(),
-        ],
-        data: ethabi::encode(&[
-            ethabi::Token::String(deposit_event.recipient),
-            ethabi::Token::Uint(deposit_event.amount),
-            ethabi::Token::Uint(deposit_event.fee),
-        ]),
-    };
-    let proof = Proof {
-        log_index: 1,
-        // Only this field matters for the purpose of this test
-        log_entry_data: rlp::encode(&log_entry).to_vec(),
-        receipt_index: 1,
-        receipt_data: Vec::new(),
-        header_data: Vec::new(),
-        proof: Vec::new(),
+    let metadata = FungibleTokenMetadata {
+        name: "Wrapped Ether".to_string(),
+        symbol: "nETH".to_string(),
+        reference: Some("https://example.com/neth.json".to_string()),
+        reference_hash: Some([1u8; 32]),
+        ..Default::default()
     };
 
     let res = master_account.call(
-        contract.account_id(),
-        "deposit",
-        &proof.try_to_vec().unwrap(),
+        CONTRACT_ACC.parse().unwrap(),
+        "set_ft_metadata",
+        &metadata.try_to_vec().unwrap(),
         DEFAULT_GAS,
         0,
     );
-    res.assert_success();
-
-    let aurora_balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
-    assert_eq!(aurora_balance, deposit_amount.low_u128());
-    let address_balance = get_eth_balance(&master_account, recipient_address, CONTRACT_ACC);
-    assert_eq!(address_balance, deposit_amount.low_u128());
-}
-
-#[test]
-fn test_deposit_with_same_proof() {
-    let (_master_account, contract) = init(CUSTODIAN_ADDRESS);
-
-    assert_proof_was_not_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+    let promises = res.promise_results();
+    assert_execution_status_failure(
+        promises[1].clone().unwrap().outcome().clone().status,
+        "ERR_PRIVATE_CALL",
+        "Expected failure as only the contract can set the metadata",
+    );
 
-    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
-    for p in promises.iter() {
-        assert!(p.is_some());
-        let p = p.as_ref().unwrap();
-        p.assert_success()
+    call_set_ft_metadata(&contract, &metadata).assert_success();
+    let json = master_account
+        .view(CONTRACT_ACC.parse().unwrap(), "ft_metadata", &[])
+        .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(json["name"], "Wrapped Ether");
+    assert_eq!(json["symbol"], "nETH");
+    assert_eq!(json["decimals"], 18);
+    assert_eq!(json["reference"], "https://example.com/neth.json");
+    assert!(json["reference_hash"].is_string());
+
+    let version = master_account
+        .view(
+            CONTRACT_ACC.parse().unwrap(),
+            "get_ft_metadata_version",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(u64::try_from_slice(&version).unwrap(), 1);
+
+    for (invalid_metadata, err) in vec![
+        (
+            FungibleTokenMetadata {
+                decimals: 25,
+                ..metadata.clone()
+            },
+            "ERR_FT_METADATA_INVALID_DECIMALS",
+        ),
+        (
+            FungibleTokenMetadata {
+                icon: Some("https://example.com/neth.svg".to_string()),
+                ..metadata.clone()
+            },
+            "ERR_FT_METADATA_INVALID_ICON",
+        ),
+        (
+            FungibleTokenMetadata {
+                icon: Some(format!("data:image/png;base64,{}", "A".repeat(8 * 1024))),
+                ..metadata.clone()
+            },
+            "ERR_FT_METADATA_ICON_TOO_LARGE",
+        ),
+        (
+            FungibleTokenMetadata {
+                spec: "ft-2.0.0".to_string(),
+                ..metadata.clone()
+            },
+            "ERR_FT_METADATA_INVALID_SPEC",
+        ),
+        (
+            FungibleTokenMetadata {
+                reference: None,
+                ..metadata.clone()
+            },
+            "ERR_FT_METADATA_INVALID_REFERENCE_HASH",
+        ),
+        (
+            FungibleTokenMetadata {
+                reference_hash: Some([0u8; 32]),
+                ..metadata.clone()
+            },
+            "ERR_FT_METADATA_INVALID_REFERENCE_HASH",
+        ),
+    ] {
+        let res = call_set_ft_metadata(&contract, &invalid_metadata);
+        let promises = res.promise_results();
+        assert_execution_status_failure(
+            promises[1].clone().unwrap().outcome().clone().status,
+            err,
+            "Expected failure as the metadata is invalid",
+        );
     }
 
-    assert_proof_was_used(&contract, CONTRACT_ACC, PROOF_DATA_NEAR);
+    let version = master_account
+        .view(
+            CONTRACT_ACC.parse().unwrap(),
+            "get_ft_metadata_version",
+            &[],
+        )
+        .unwrap();
+    assert_eq!(u64::try_from_slice(&version).unwrap(), 1);
 
-    let promises = call_deposit_eth_to_near(&contract, CONTRACT_ACC);
-    let promise = &promises[promises.len() - 2];
+    // The metadata set with the contract data is validated as well
+    let res = contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "set_eth_connector_contract_data",
+        &SetContractDataCallArgs {
+            prover_account: str_to_account_id(PROVER_ACCOUNT),
+            eth_custodian_address: CUSTODIAN_ADDRESS.to_string(),
+            metadata: FungibleTokenMetadata {
+                decimals: 25,
+                ..metadata
+            },
+        }
+        .try_to_vec()
+        .unwrap(),
+        DEFAULT_GAS,
+        0,
+    );
     assert_execution_status_failure(
-        promise.as_ref().unwrap().outcome().clone().status,
-        "ERR_PROOF_EXIST",
-        "Expected failure as the provided proof already exists, but deposit succeeded",
+        res.promise_results()[1]
+            .clone()
+            .unwrap()
+            .outcome()
+            .clone()
+            .status,
+        "ERR_FT_METADATA_INVALID_DECIMALS",
+        "Expected failure as the metadata of the contract data is invalid",
     );
 }
 
+fn call_set_ft_metadata(
+    contract: &UserAccount,
+    metadata: &FungibleTokenMetadata,
+) -> ExecutionResult {
+    contract.call(
+        CONTRACT_ACC.parse().unwrap(),
+        "set_ft_metadata",
+        &metadata.try_to_vec().unwrap(),
+        DEFAULT_GAS,
+        0,
+    )
+}
+
 #[test]
 fn test_deposit_wrong_custodian_address() {
     let wrong_custodian_address = "0000000000000000000000000000000000000001";
@@ -900,6 +2391,8 @@ fn test_admin_controlled_admin_can_peform_actions_when_paused() {
         &WithdrawCallArgs {
             recipient_address: recipient_addr,
             amount: withdraw_amount,
+            fee: None,
+            sender: None,
         }
         .try_to_vec()
         .unwrap(),
@@ -939,6 +2432,8 @@ fn test_admin_controlled_admin_can_peform_actions_when_paused() {
         &WithdrawCallArgs {
             recipient_address: recipient_addr,
             amount: withdraw_amount,
+            fee: None,
+            sender: None,
         }
         .try_to_vec()
         .unwrap(),
@@ -1012,6 +2507,8 @@ fn test_withdraw_from_near_pausability() {
         &WithdrawCallArgs {
             recipient_address: recipient_addr,
             amount: withdraw_amount,
+            fee: None,
+            sender: None,
         }
         .try_to_vec()
         .unwrap(),
@@ -1038,6 +2535,8 @@ fn test_withdraw_from_near_pausability() {
         &WithdrawCallArgs {
             recipient_address: recipient_addr,
             amount: withdraw_amount,
+            fee: None,
+            sender: None,
         }
         .try_to_vec()
         .unwrap(),
@@ -1062,6 +2561,8 @@ fn test_withdraw_from_near_pausability() {
         &WithdrawCallArgs {
             recipient_address: recipient_addr,
             amount: withdraw_amount,
+            fee: None,
+            sender: None,
         }
         .try_to_vec()
         .unwrap(),
@@ -1473,6 +2974,64 @@ fn test_ft_transfer_wrong_u128_json_type() {
     );
 }
 
+#[test]
+fn test_ft_transfer_invalid_json_args() {
+    let (master_account, contract) = init(CUSTODIAN_ADDRESS);
+    call_deposit_eth_to_near(&contract, CONTRACT_ACC);
+
+    let cases = [
+        (
+            json!({
+                "receiver_id": "Invalid Account",
+                "amount": "10",
+            })
+            .to_string(),
+            "ERR_FAILED_PARSE_ACCOUNT_ID at receiver_id",
+        ),
+        (
+            json!({
+                "receiver_id": DEPOSITED_RECIPIENT,
+                "amount": "10",
+                "memo": 10,
+            })
+            .to_string(),
+            "ERR_FAILED_PARSE_STRING at memo",
+        ),
+        (
+            json!({ "receiver_id": DEPOSITED_RECIPIENT }).to_string(),
+            "ERR_JSON_MISSING_VALUE at amount",
+        ),
+        // Content after the arguments object is not valid JSON
+        (
+            format!(
+                r#"{{"receiver_id": "{}", "amount": "10"}} {{}}"#,
+                DEPOSITED_RECIPIENT
+            ),
+            "ERR_FAILED_PARSE",
+        ),
+    ];
+    for (args, err) in cases.iter() {
+        let res = contract.call(
+            CONTRACT_ACC.parse().unwrap(),
+            "ft_transfer",
+            args.as_bytes(),
+            DEFAULT_GAS,
+            1,
+        );
+        let promises = res.promise_results();
+        let promise = &promises[promises.len() - 3];
+        assert_execution_status_failure(
+            promise.as_ref().unwrap().outcome().clone().status,
+            err,
+            "Expected failure as the arguments are invalid",
+        );
+    }
+
+    // Nothing was transferred
+    let balance = get_eth_on_near_balance(&master_account, CONTRACT_ACC, CONTRACT_ACC);
+    assert_eq!(balance, DEPOSITED_FEE);
+}
+
 /// Bytes for a NEAR smart contract implementing `ft_on_transfer`
 fn dummy_ft_receiver_bytes() -> Vec<u8> {
     let base_path = std::path::Path::new("../etc").join("ft-receiver");
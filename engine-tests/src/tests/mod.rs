@@ -1,10 +1,13 @@
 mod access_lists;
 mod contract_call;
+mod cross_contract_call;
 mod erc20;
 mod erc20_connector;
+mod erc721_connector;
 mod eth_connector;
 #[cfg(feature = "meta-call")]
 mod meta_parsing;
+mod native_erc20_connector;
 mod one_inch;
 mod sanity;
 mod self_destruct_state;
@@ -0,0 +1,278 @@
+use crate::prelude::{Address, RawAddress, TryInto, Wei, U256};
+use crate::test_utils::{
+    self,
+    erc20::{ERC20Constructor, ERC20},
+    origin, AuroraRunner, Signer,
+};
+use crate::tests::erc20_connector::{build_input, CallResult};
+use aurora_engine::parameters::{RegisterNativeErc20Args, SetNativeErc20FactoryArgs};
+use aurora_engine::transaction::LegacyEthTransaction;
+use borsh::BorshSerialize;
+use ethabi::Token;
+use secp256k1::SecretKey;
+
+const FACTORY: &str = "factory.near";
+const NEP141: &str = "native.near";
+const DEST_ACCOUNT: &str = "target.aurora";
+const INITIAL_BALANCE: u64 = 1_000_000;
+const LOCK_AMOUNT: u64 = 42;
+
+impl AuroraRunner {
+    pub fn set_native_erc20_factory(&mut self, caller: String, factory: &str) -> CallResult {
+        let args = SetNativeErc20FactoryArgs {
+            factory: test_utils::str_to_account_id(factory),
+        };
+        self.make_call(
+            "set_native_erc20_factory",
+            caller,
+            args.try_to_vec().unwrap(),
+        )
+    }
+
+    pub fn register_native_erc20(
+        &mut self,
+        caller: String,
+        erc20: RawAddress,
+        nep141: Option<&str>,
+    ) -> CallResult {
+        let args = RegisterNativeErc20Args {
+            erc20,
+            nep141: nep141.map(test_utils::str_to_account_id),
+        };
+        self.make_call("register_native_erc20", caller, args.try_to_vec().unwrap())
+    }
+
+    pub fn get_native_erc20_escrow(&mut self) -> RawAddress {
+        let result = self.make_call("get_native_erc20_escrow", origin(), Vec::new());
+        result.check_ok();
+        result.value().as_slice().try_into().unwrap()
+    }
+}
+
+fn initialize_native_erc20() -> (AuroraRunner, Signer, ERC20) {
+    let mut runner = AuroraRunner::new();
+    let source_account = SecretKey::random(&mut rand::thread_rng());
+    runner.create_address(
+        test_utils::address_from_secret_key(&source_account),
+        Wei::new_u64(INITIAL_BALANCE),
+        U256::zero(),
+    );
+
+    let mut signer = Signer::new(source_account);
+    let nonce = signer.use_nonce();
+    let contract = ERC20(runner.deploy_contract(
+        &signer.secret_key,
+        |c| c.deploy("NativeToken", "NATIVE", nonce.into()),
+        ERC20Constructor::load(),
+    ));
+    let address = test_utils::address_from_secret_key(&signer.secret_key);
+    runner
+        .submit_with_signer(&mut signer, |nonce| {
+            contract.mint(address, LOCK_AMOUNT.into(), nonce)
+        })
+        .unwrap();
+
+    (runner, signer, contract)
+}
+
+/// Locks `LOCK_AMOUNT` of the native ERC-20 token in the escrow, for `DEST_ACCOUNT`.
+fn lock_to_near(runner: &mut AuroraRunner, signer: &mut Signer, contract: &ERC20) {
+    let escrow = Address(runner.get_native_erc20_escrow());
+    runner
+        .submit_with_signer(signer, |nonce| {
+            contract.approve(escrow, LOCK_AMOUNT.into(), nonce)
+        })
+        .unwrap();
+
+    let data = build_input(
+        "lockToNear(address,uint256,bytes)",
+        &[
+            Token::Address(contract.0.address),
+            Token::Uint(LOCK_AMOUNT.into()),
+            Token::Bytes(DEST_ACCOUNT.as_bytes().to_vec()),
+        ],
+    );
+    let result = runner
+        .submit_with_signer(signer, |nonce| LegacyEthTransaction {
+            nonce,
+            gas_price: Default::default(),
+            gas: u64::MAX.into(),
+            to: Some(escrow),
+            value: Default::default(),
+            data,
+        })
+        .unwrap();
+    test_utils::unwrap_success(result);
+}
+
+#[test]
+fn test_register_native_erc20_deploys_nep141() {
+    let (mut runner, _signer, contract) = initialize_native_erc20();
+    let owner = runner.aurora_account_id.clone();
+    let erc20 = contract.0.address.0;
+
+    // The factory must be set first
+    let result = runner.register_native_erc20(owner.clone(), erc20, None);
+    assert!(result.error.is_some());
+
+    runner
+        .set_native_erc20_factory(owner.clone(), FACTORY)
+        .check_ok();
+    let result = runner.register_native_erc20(owner, erc20, None);
+    result.check_ok();
+
+    let nep141 = format!("{}.{}", hex::encode(erc20), FACTORY);
+    assert_eq!(String::from_utf8(result.value()).unwrap(), nep141);
+    // The tokens are mapped once the factory deployed the NEP-141 token
+    let expected = format!("Call contract: {}.deploy_bridge_token", FACTORY);
+    assert!(runner.previous_logs.contains(&expected));
+    assert!(runner
+        .previous_logs
+        .contains(&"Callback contract: aurora.native_erc20_deployed_callback".to_string()));
+}
+
+#[test]
+fn test_register_native_erc20_is_owner_only() {
+    let (mut runner, _signer, contract) = initialize_native_erc20();
+
+    let result = runner.set_native_erc20_factory(origin(), FACTORY);
+    assert!(result.error.is_some());
+    let result = runner.register_native_erc20(origin(), contract.0.address.0, Some(NEP141));
+    assert!(result.error.is_some());
+}
+
+#[test]
+fn test_register_native_erc20_rejects_bridged_tokens() {
+    let mut runner = AuroraRunner::new();
+    let owner = runner.aurora_account_id.clone();
+    let bridged = runner.deploy_erc20_token(&"tt.testnet".to_string());
+
+    // A bridged ERC-20 token cannot be bridged back
+    let result = runner.register_native_erc20(owner.clone(), bridged, Some(NEP141));
+    assert!(result.error.is_some());
+
+    // A bridged NEP-141 token cannot be reused
+    let (mut runner, _signer, contract) = initialize_native_erc20();
+    runner.deploy_erc20_token(&"tt.testnet".to_string());
+    let result = runner.register_native_erc20(owner, contract.0.address.0, Some("tt.testnet"));
+    assert!(result.error.is_some());
+}
+
+#[test]
+fn test_lock_native_erc20_to_near() {
+    let (mut runner, mut signer, contract) = initialize_native_erc20();
+    let owner = runner.aurora_account_id.clone();
+    let erc20 = contract.0.address.0;
+    let sender = test_utils::address_from_secret_key(&signer.secret_key);
+    runner
+        .register_native_erc20(owner, erc20, Some(NEP141))
+        .check_ok();
+
+    lock_to_near(&mut runner, &mut signer, &contract);
+
+    // The tokens are minted on NEAR, and unlocked if the mint fails
+    let expected = format!("Call contract: {}.mint", NEP141);
+    assert!(runner.previous_logs.contains(&expected));
+    assert!(runner
+        .previous_logs
+        .contains(&"Callback contract: aurora.refund_on_error".to_string()));
+
+    let escrow = runner.get_native_erc20_escrow();
+    assert_eq!(
+        runner.balance_of(erc20, escrow, origin()),
+        LOCK_AMOUNT.into()
+    );
+    assert_eq!(runner.balance_of(erc20, sender.0, origin()), U256::zero());
+}
+
+#[test]
+fn test_ft_on_transfer_unlocks_native_erc20() {
+    let (mut runner, mut signer, contract) = initialize_native_erc20();
+    let owner = runner.aurora_account_id.clone();
+    let erc20 = contract.0.address.0;
+    runner
+        .register_native_erc20(owner, erc20, Some(NEP141))
+        .check_ok();
+    lock_to_near(&mut runner, &mut signer, &contract);
+
+    let recipient = runner.create_account().address;
+    let output = runner.ft_on_transfer(
+        NEP141.to_string(),
+        DEST_ACCOUNT.to_string(),
+        DEST_ACCOUNT.to_string(),
+        LOCK_AMOUNT.into(),
+        hex::encode(recipient),
+    );
+    assert_eq!(output, "\"0\"");
+
+    // The tokens are unlocked here and burned on NEAR
+    let expected = format!("Call contract: {}.burn", NEP141);
+    assert!(runner.previous_logs.contains(&expected));
+    assert_eq!(
+        runner.balance_of(erc20, recipient, origin()),
+        LOCK_AMOUNT.into()
+    );
+    let escrow = runner.get_native_erc20_escrow();
+    assert_eq!(runner.balance_of(erc20, escrow, origin()), U256::zero());
+
+    // More tokens than locked cannot be unlocked, so they are returned
+    let output = runner.ft_on_transfer(
+        NEP141.to_string(),
+        DEST_ACCOUNT.to_string(),
+        DEST_ACCOUNT.to_string(),
+        LOCK_AMOUNT.into(),
+        hex::encode(recipient),
+    );
+    assert_eq!(output, format!("\"{}\"", LOCK_AMOUNT));
+}
+
+#[test]
+fn test_ft_on_transfer_native_erc20_deposit_message() {
+    let (mut runner, mut signer, contract) = initialize_native_erc20();
+    let owner = runner.aurora_account_id.clone();
+    let erc20 = contract.0.address.0;
+    runner
+        .register_native_erc20(owner, erc20, Some(NEP141))
+        .check_ok();
+    lock_to_near(&mut runner, &mut signer, &contract);
+    let recipient = runner.create_account().address;
+
+    // Neither a relayer fee nor a call is supported, so the tokens are returned
+    let unsupported = [
+        format!(
+            r#"{{"version": 1, "recipient": "{}", "fee": "1"}}"#,
+            hex::encode(recipient)
+        ),
+        format!(
+            r#"{{"version": 1, "recipient": "{}", "call": {{"contract": "{}", "input": ""}}}}"#,
+            hex::encode(recipient),
+            hex::encode(erc20)
+        ),
+    ];
+    for msg in unsupported.iter() {
+        let output = runner.ft_on_transfer(
+            NEP141.to_string(),
+            DEST_ACCOUNT.to_string(),
+            DEST_ACCOUNT.to_string(),
+            LOCK_AMOUNT.into(),
+            msg.clone(),
+        );
+        assert_eq!(output, format!("\"{}\"", LOCK_AMOUNT));
+    }
+
+    let output = runner.ft_on_transfer(
+        NEP141.to_string(),
+        DEST_ACCOUNT.to_string(),
+        DEST_ACCOUNT.to_string(),
+        LOCK_AMOUNT.into(),
+        format!(
+            r#"{{"version": 1, "recipient": "{}"}}"#,
+            hex::encode(recipient)
+        ),
+    );
+    assert_eq!(output, "\"0\"");
+    assert_eq!(
+        runner.balance_of(erc20, recipient, origin()),
+        LOCK_AMOUNT.into()
+    );
+}
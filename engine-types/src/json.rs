@@ -0,0 +1,1146 @@
+//! JSON codec of the arguments and results exchanged with other NEAR contracts.
+//!
+//! `parse_json` accepts the RFC 8259 grammar only: the input must be UTF-8,
+//! strings are unescaped (including UTF-16 surrogate pairs) and anything after
+//! the top-level value is an error. The `Display` impl of `JsonValue` is the
+//! serializer, escaping the strings it writes.
+//!
+//! Arguments are deserialized with `TryFrom<JsonValue>` impls built on
+//! `JsonValue::field`, whose errors carry the path of the invalid field,
+//! e.g. `ERR_FAILED_PARSE_U128 at amount`.
+use crate::account_id::AccountId;
+use crate::types::Balance;
+use crate::{fmt, format, str, BTreeMap, String, ToString, TryFrom, TryInto, Vec};
+
+/// Maximum nesting of arrays and objects, so that the parser can't overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+#[derive(PartialEq, Clone)]
+pub enum JsonValue {
+    Null,
+    F64(f64),
+    I64(i64),
+    U64(u64),
+    Bool(bool),
+    String(String),
+    Array(Vec<JsonValue>),
+    Object(BTreeMap<String, JsonValue>),
+}
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub enum JsonError {
+    NotJsonType,
+    MissingValue,
+    InvalidU8,
+    InvalidU64,
+    InvalidU128,
+    InvalidBool,
+    InvalidString,
+    InvalidArray,
+    InvalidAccountId,
+    ExpectedStringGotNumber,
+    OutOfRange(JsonOutOfRangeError),
+}
+
+#[derive(Debug, Ord, PartialOrd, Eq, PartialEq, Clone)]
+pub enum JsonOutOfRangeError {
+    OutOfRangeU8,
+    OutOfRangeU128,
+}
+
+/// Error of the deserialization of a field, with the path to the field,
+/// like `data[0].amount`, in its message.
+#[derive(Eq, PartialEq, Clone)]
+pub struct JsonFieldError {
+    error: JsonError,
+    path: String,
+    message: String,
+}
+
+impl JsonValue {
+    pub fn string(&self, key: &str) -> Result<String, JsonError> {
+        match self {
+            JsonValue::Object(o) => match o.get(key).ok_or(JsonError::MissingValue)? {
+                JsonValue::String(s) => Ok(s.into()),
+                _ => Err(JsonError::InvalidString),
+            },
+            _ => Err(JsonError::NotJsonType),
+        }
+    }
+
+    pub fn u64(&self, key: &str) -> Result<u64, JsonError> {
+        match self {
+            JsonValue::Object(o) => match o.get(key).ok_or(JsonError::MissingValue)? {
+                JsonValue::U64(n) => Ok(*n),
+                _ => Err(JsonError::InvalidU64),
+            },
+            _ => Err(JsonError::NotJsonType),
+        }
+    }
+
+    pub fn u128(&self, key: &str) -> Result<u128, JsonError> {
+        match self {
+            JsonValue::Object(o) => o.get(key).ok_or(JsonError::MissingValue)?.try_into(),
+            _ => Err(JsonError::NotJsonType),
+        }
+    }
+
+    pub fn bool(&self, key: &str) -> Result<bool, JsonError> {
+        match self {
+            JsonValue::Object(o) => match o.get(key).ok_or(JsonError::MissingValue)? {
+                JsonValue::Bool(n) => Ok(*n),
+                _ => Err(JsonError::InvalidBool),
+            },
+            _ => Err(JsonError::NotJsonType),
+        }
+    }
+
+    pub fn parse_u8(v: &JsonValue) -> Result<u8, JsonError> {
+        match v {
+            JsonValue::U64(n) => {
+                if *n > u8::MAX as u64 {
+                    Err(JsonError::OutOfRange(JsonOutOfRangeError::OutOfRangeU8))
+                } else {
+                    Ok(*n as u8)
+                }
+            }
+            _ => Err(JsonError::InvalidU8),
+        }
+    }
+
+    /// Deserializes the field `key` of an object. A missing field is an error,
+    /// unless `T` is an `Option`.
+    pub fn field<T: FromJson>(&self, key: &str) -> Result<T, JsonFieldError> {
+        let fields = match self {
+            JsonValue::Object(o) => o,
+            _ => return Err(JsonError::NotJsonType.into()),
+        };
+        match fields.get(key) {
+            Some(value) => T::from_json(value).map_err(|e| e.at_field(key)),
+            None => T::from_missing()
+                .ok_or_else(|| JsonFieldError::from(JsonError::MissingValue).at_field(key)),
+        }
+    }
+
+    /// Builds an object from its fields.
+    pub fn object<'a, I: IntoIterator<Item = (&'a str, JsonValue)>>(fields: I) -> Self {
+        JsonValue::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// Serializes the value, as returned to or sent to other contracts.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.to_string().into_bytes()
+    }
+}
+
+impl AsRef<[u8]> for JsonError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::NotJsonType => b"ERR_NOT_A_JSON_TYPE",
+            Self::MissingValue => b"ERR_JSON_MISSING_VALUE",
+            Self::InvalidU8 => b"ERR_FAILED_PARSE_U8",
+            Self::InvalidU64 => b"ERR_FAILED_PARSE_U64",
+            Self::InvalidU128 => b"ERR_FAILED_PARSE_U128",
+            Self::InvalidBool => b"ERR_FAILED_PARSE_BOOL",
+            Self::InvalidString => b"ERR_FAILED_PARSE_STRING",
+            Self::InvalidArray => b"ERR_FAILED_PARSE_ARRAY",
+            Self::InvalidAccountId => b"ERR_FAILED_PARSE_ACCOUNT_ID",
+            Self::ExpectedStringGotNumber => b"ERR_EXPECTED_STRING_GOT_NUMBER",
+            Self::OutOfRange(err) => err.as_ref(),
+        }
+    }
+}
+
+impl AsRef<[u8]> for JsonOutOfRangeError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::OutOfRangeU8 => b"ERR_OUT_OF_RANGE_U8",
+            Self::OutOfRangeU128 => b"ERR_OUT_OF_RANGE_U128",
+        }
+    }
+}
+
+impl fmt::Debug for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(str::from_utf8(self.as_ref()).unwrap())
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_fmt(format_args!("{:?}", *self))
+    }
+}
+
+impl JsonFieldError {
+    pub fn error(&self) -> &JsonError {
+        &self.error
+    }
+
+    /// Path to the invalid field, empty if it's the value itself.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Locates the error in the field `key` of an object.
+    pub fn at_field(self, key: &str) -> Self {
+        let path = if self.path.is_empty() || self.path.starts_with('[') {
+            format!("{}{}", key, self.path)
+        } else {
+            format!("{}.{}", key, self.path)
+        };
+        Self::new(self.error, path)
+    }
+
+    /// Locates the error in the item `index` of an array.
+    pub fn at_index(self, index: usize) -> Self {
+        let path = if self.path.is_empty() || self.path.starts_with('[') {
+            format!("[{}]{}", index, self.path)
+        } else {
+            format!("[{}].{}", index, self.path)
+        };
+        Self::new(self.error, path)
+    }
+
+    fn new(error: JsonError, path: String) -> Self {
+        let message = if path.is_empty() {
+            error.to_string()
+        } else {
+            format!("{} at {}", error, path)
+        };
+        Self {
+            error,
+            path,
+            message,
+        }
+    }
+}
+
+impl From<JsonError> for JsonFieldError {
+    fn from(error: JsonError) -> Self {
+        Self::new(error, String::new())
+    }
+}
+
+impl AsRef<[u8]> for JsonFieldError {
+    fn as_ref(&self) -> &[u8] {
+        self.message.as_bytes()
+    }
+}
+
+impl fmt::Debug for JsonFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl fmt::Display for JsonFieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Types deserialized from a JSON value, the fields of the arguments.
+pub trait FromJson: Sized {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError>;
+
+    /// Value of a missing field, `None` if the field is required.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+impl FromJson for JsonValue {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        match value {
+            JsonValue::String(s) => Ok(s.clone()),
+            _ => Err(JsonError::InvalidString.into()),
+        }
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        match value {
+            JsonValue::Bool(b) => Ok(*b),
+            _ => Err(JsonError::InvalidBool.into()),
+        }
+    }
+}
+
+impl FromJson for u8 {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        Ok(JsonValue::parse_u8(value)?)
+    }
+}
+
+impl FromJson for u64 {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        match value {
+            JsonValue::U64(n) => Ok(*n),
+            _ => Err(JsonError::InvalidU64.into()),
+        }
+    }
+}
+
+/// Balances are decimal strings, since they don't fit in JSON numbers.
+impl FromJson for u128 {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        Ok(u128::try_from(value)?)
+    }
+}
+
+impl FromJson for AccountId {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        AccountId::try_from(String::from_json(value)?)
+            .map_err(|_| JsonError::InvalidAccountId.into())
+    }
+}
+
+/// Optional fields may be missing or `null`.
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        match value {
+            JsonValue::Null => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+        match value {
+            JsonValue::Array(items) => items
+                .iter()
+                .enumerate()
+                .map(|(index, item)| T::from_json(item).map_err(|e| e.at_index(index)))
+                .collect(),
+            _ => Err(JsonError::InvalidArray.into()),
+        }
+    }
+}
+
+impl From<f64> for JsonValue {
+    fn from(v: f64) -> Self {
+        JsonValue::F64(v)
+    }
+}
+
+impl From<i64> for JsonValue {
+    fn from(v: i64) -> Self {
+        JsonValue::I64(v)
+    }
+}
+
+impl From<u64> for JsonValue {
+    fn from(v: u64) -> Self {
+        JsonValue::U64(v)
+    }
+}
+
+/// Balances are decimal strings, since they don't fit in JSON numbers.
+impl From<Balance> for JsonValue {
+    fn from(v: Balance) -> Self {
+        JsonValue::String(v.to_string())
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(v: bool) -> Self {
+        JsonValue::Bool(v)
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(v: String) -> Self {
+        JsonValue::String(v)
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(v: &str) -> Self {
+        JsonValue::String(v.to_string())
+    }
+}
+
+impl From<&AccountId> for JsonValue {
+    fn from(v: &AccountId) -> Self {
+        JsonValue::String(v.to_string())
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(v: Option<T>) -> Self {
+        v.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+impl From<Vec<JsonValue>> for JsonValue {
+    fn from(v: Vec<JsonValue>) -> Self {
+        JsonValue::Array(v)
+    }
+}
+
+impl TryFrom<&JsonValue> for u128 {
+    type Error = JsonError;
+
+    fn try_from(value: &JsonValue) -> Result<Self, Self::Error> {
+        match value {
+            // `parse` accepts a leading `+`, which isn't a valid balance
+            JsonValue::String(n) if n.starts_with('+') => Err(JsonError::InvalidU128),
+            JsonValue::String(n) => {
+                if let Ok(x) = n.parse::<u128>() {
+                    Ok(x)
+                } else if n.parse::<i128>().is_ok() {
+                    Err(JsonError::OutOfRange(JsonOutOfRangeError::OutOfRangeU128))
+                } else {
+                    Err(JsonError::InvalidU128)
+                }
+            }
+            JsonValue::F64(_) => Err(JsonError::ExpectedStringGotNumber),
+            JsonValue::I64(_) => Err(JsonError::ExpectedStringGotNumber),
+            JsonValue::U64(_) => Err(JsonError::ExpectedStringGotNumber),
+            _ => Err(JsonError::InvalidU128),
+        }
+    }
+}
+
+impl fmt::Debug for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JsonValue::Null => f.write_str("null"),
+            JsonValue::String(v) => write_escaped_string(f, v),
+            // JSON has no representation of NaN and infinities
+            JsonValue::F64(v) if !v.is_finite() => f.write_str("null"),
+            JsonValue::F64(v) => f.write_fmt(format_args!("{}", v)),
+            JsonValue::I64(v) => f.write_fmt(format_args!("{}", v)),
+            JsonValue::U64(v) => f.write_fmt(format_args!("{}", v)),
+            JsonValue::Bool(v) => f.write_fmt(format_args!("{}", v)),
+            JsonValue::Array(arr) => {
+                f.write_str("[")?;
+                let mut items = arr.iter();
+                if let Some(item) = items.next() {
+                    f.write_fmt(format_args!("{:?}", item))?;
+                }
+                for item in items {
+                    f.write_fmt(format_args!(", {:?}", item))?;
+                }
+                f.write_str("]")
+            }
+            JsonValue::Object(kvs) => {
+                f.write_str("{")?;
+                let mut pairs = kvs.iter();
+                if let Some((key, value)) = pairs.next() {
+                    write_escaped_string(f, key)?;
+                    f.write_fmt(format_args!(": {:?}", value))?;
+                }
+                for (key, value) in pairs {
+                    f.write_str(", ")?;
+                    write_escaped_string(f, key)?;
+                    f.write_fmt(format_args!(": {:?}", value))?;
+                }
+                f.write_str("}")
+            }
+        }
+    }
+}
+
+/// Writes the string as a JSON string, escaping quotes, backslashes and control characters
+fn write_escaped_string(f: &mut fmt::Formatter, value: &str) -> fmt::Result {
+    f.write_str("\"")?;
+    for c in value.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if (c as u32) < 0x20 => f.write_fmt(format_args!("\\u{:04x}", c as u32))?,
+            c => f.write_fmt(format_args!("{}", c))?,
+        }
+    }
+    f.write_str("\"")
+}
+
+impl fmt::Display for JsonValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_fmt(format_args!("{:?}", *self))
+    }
+}
+
+/// Parses a JSON document, `None` if it isn't valid JSON.
+pub fn parse_json(data: &[u8]) -> Option<JsonValue> {
+    let text = str::from_utf8(data).ok()?;
+    let mut parser = Parser { text, index: 0 };
+    let value = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.index == text.len() {
+        Some(value)
+    } else {
+        None
+    }
+}
+
+struct Parser<'a> {
+    text: &'a str,
+    index: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.index).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.index += 1;
+        Some(byte)
+    }
+
+    fn expect(&mut self, byte: u8) -> Option<()> {
+        if self.next()? == byte {
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ') | Some(b'\t') | Some(b'\n') | Some(b'\r') = self.peek() {
+            self.index += 1;
+        }
+    }
+
+    fn literal(&mut self, literal: &str, value: JsonValue) -> Option<JsonValue> {
+        if self.text[self.index..].starts_with(literal) {
+            self.index += literal.len();
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    fn value(&mut self, depth: usize) -> Option<JsonValue> {
+        self.skip_whitespace();
+        match self.peek()? {
+            b'n' => self.literal("null", JsonValue::Null),
+            b't' => self.literal("true", JsonValue::Bool(true)),
+            b'f' => self.literal("false", JsonValue::Bool(false)),
+            b'"' => self.string().map(JsonValue::String),
+            b'[' => self.array(depth + 1),
+            b'{' => self.object(depth + 1),
+            b'-' | b'0'..=b'9' => self.number(),
+            _ => None,
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Option<JsonValue> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.expect(b'[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.peek()? == b']' {
+            self.index += 1;
+            return Some(JsonValue::Array(items));
+        }
+        loop {
+            items.push(self.value(depth)?);
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => continue,
+                b']' => return Some(JsonValue::Array(items)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Option<JsonValue> {
+        if depth > MAX_DEPTH {
+            return None;
+        }
+        self.expect(b'{')?;
+        let mut fields = BTreeMap::new();
+        self.skip_whitespace();
+        if self.peek()? == b'}' {
+            self.index += 1;
+            return Some(JsonValue::Object(fields));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(b':')?;
+            let value = self.value(depth)?;
+            // Duplicate keys are ambiguous, so they are rejected
+            if fields.insert(key, value).is_some() {
+                return None;
+            }
+            self.skip_whitespace();
+            match self.next()? {
+                b',' => continue,
+                b'}' => return Some(JsonValue::Object(fields)),
+                _ => return None,
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+        let mut value = String::new();
+        let mut start = self.index;
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    value.push_str(&self.text[start..self.index]);
+                    self.index += 1;
+                    return Some(value);
+                }
+                b'\\' => {
+                    value.push_str(&self.text[start..self.index]);
+                    self.index += 1;
+                    value.push(self.escape()?);
+                    start = self.index;
+                }
+                // Control characters must be escaped
+                0x00..=0x1f => return None,
+                _ => self.index += 1,
+            }
+        }
+    }
+
+    /// Unescapes the character following a backslash.
+    fn escape(&mut self) -> Option<char> {
+        let c = match self.next()? {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\u{8}',
+            b'f' => '\u{c}',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let code = self.hex_code_unit()?;
+                match code {
+                    // A high surrogate must be followed by an escaped low surrogate
+                    0xd800..=0xdbff => {
+                        self.expect(b'\\')?;
+                        self.expect(b'u')?;
+                        let low = self.hex_code_unit()?;
+                        if !(0xdc00..=0xdfff).contains(&low) {
+                            return None;
+                        }
+                        let code = 0x10000 + ((code - 0xd800) << 10) + (low - 0xdc00);
+                        core::char::from_u32(code)?
+                    }
+                    0xdc00..=0xdfff => return None,
+                    code => core::char::from_u32(code)?,
+                }
+            }
+            _ => return None,
+        };
+        Some(c)
+    }
+
+    fn hex_code_unit(&mut self) -> Option<u32> {
+        let digits = self.text.get(self.index..self.index + 4)?;
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.index += 4;
+        u32::from_str_radix(digits, 16).ok()
+    }
+
+    /// Integers are `U64` or `I64` if negative, or `F64` if they don't fit.
+    fn number(&mut self) -> Option<JsonValue> {
+        let start = self.index;
+        let negative = self.peek()? == b'-';
+        if negative {
+            self.index += 1;
+        }
+        match self.next()? {
+            b'0' => {}
+            b'1'..=b'9' => self.digits(),
+            _ => return None,
+        }
+        let mut integer = true;
+        if self.peek() == Some(b'.') {
+            integer = false;
+            self.index += 1;
+            self.first_digit()?;
+        }
+        if let Some(b'e') | Some(b'E') = self.peek() {
+            integer = false;
+            self.index += 1;
+            if let Some(b'+') | Some(b'-') = self.peek() {
+                self.index += 1;
+            }
+            self.first_digit()?;
+        }
+        let number = &self.text[start..self.index];
+        if integer {
+            if negative {
+                if let Ok(n) = number.parse::<i64>() {
+                    return Some(JsonValue::I64(n));
+                }
+            } else if let Ok(n) = number.parse::<u64>() {
+                return Some(JsonValue::U64(n));
+            }
+        }
+        number.parse::<f64>().ok().map(JsonValue::F64)
+    }
+
+    fn first_digit(&mut self) -> Option<()> {
+        if !self.peek()?.is_ascii_digit() {
+            return None;
+        }
+        self.digits();
+        Some(())
+    }
+
+    fn digits(&mut self) {
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.index += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vec;
+
+    #[test]
+    fn test_json_type_string() {
+        let json = parse_json(r#"{"foo": "abcd"}"#.as_bytes()).unwrap();
+        let string_data = json.string("foo").ok().unwrap();
+        assert_eq!(string_data, "abcd");
+
+        let json = parse_json(r#"{"foo": 123}"#.as_bytes()).unwrap();
+        let err = json.string("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidString);
+
+        let json = parse_json(r#"{"foo": true}"#.as_bytes()).unwrap();
+        let err = json.string("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidString);
+
+        let json = parse_json(r#"{"foo": ["abcd"]}"#.as_bytes()).unwrap();
+        let err = json.string("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidString);
+
+        let json = parse_json(r#"{"foo": {}}"#.as_bytes()).unwrap();
+        let err = json.string("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidString);
+
+        let json = parse_json(r#"{"foo": null}"#.as_bytes()).unwrap();
+        let err = json.string("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidString);
+
+        let json = JsonValue::Null;
+        let err = json.string("foo").unwrap_err();
+        assert_eq!(err, JsonError::NotJsonType);
+    }
+
+    #[test]
+    fn test_json_type_u64_with_u128_value() {
+        // Integers out of the `u64` range are parsed as floats
+        let json = parse_json(format!(r#"{{"foo": {} }}"#, u128::MAX).as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+    }
+
+    #[test]
+    fn test_json_type_u64() {
+        let json = parse_json(r#"{"foo": 123}"#.as_bytes()).unwrap();
+        let val = json.u64("foo").ok().unwrap();
+        assert_eq!(val, 123);
+
+        let json = parse_json(format!(r#"{{"foo": {} }}"#, u64::MAX).as_bytes()).unwrap();
+        let val = json.u64("foo").ok().unwrap();
+        assert_eq!(val, u64::MAX);
+
+        let json = parse_json(r#"{"foo": 12.99}"#.as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+
+        let json = parse_json(r#"{"foo": -123}"#.as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+
+        let json = parse_json(r#"{"foo": "abcd"}"#.as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+
+        let json = parse_json(r#"{"foo": "123"}"#.as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+
+        let json = parse_json(r#"{"foo": true}"#.as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+
+        let json = parse_json(r#"{"foo": [123]}"#.as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+
+        let json = parse_json(r#"{"foo": {}}"#.as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+
+        let json = parse_json(r#"{"foo": null}"#.as_bytes()).unwrap();
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU64);
+
+        let json = JsonValue::Null;
+        let err = json.u64("foo").unwrap_err();
+        assert_eq!(err, JsonError::NotJsonType);
+    }
+
+    #[test]
+    fn test_json_type_u128() {
+        let json = parse_json(r#"{"foo": "123"}"#.as_bytes()).unwrap();
+        let val = json.u128("foo").ok().unwrap();
+        assert_eq!(val, 123);
+
+        let json = parse_json(r#"{"foo": "-123"}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(
+            err,
+            JsonError::OutOfRange(JsonOutOfRangeError::OutOfRangeU128)
+        );
+
+        let json = parse_json(r#"{"foo": 123}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::ExpectedStringGotNumber);
+
+        let json = parse_json(r#"{"foo": 12.3}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::ExpectedStringGotNumber);
+
+        let json = parse_json(r#"{"foo": "12.3"}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU128);
+
+        let json = parse_json(r#"{"foo": "+123"}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU128);
+
+        let json = parse_json(r#"{"foo": "abcd"}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU128);
+
+        let json = parse_json(r#"{"foo": true}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU128);
+
+        let json = parse_json(r#"{"foo": ["123"]}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU128);
+
+        let json = parse_json(r#"{"foo": {}}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU128);
+
+        let json = parse_json(r#"{"foo": null}"#.as_bytes()).unwrap();
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidU128);
+
+        let json = JsonValue::Null;
+        let err = json.u128("foo").unwrap_err();
+        assert_eq!(err, JsonError::NotJsonType);
+    }
+
+    #[test]
+    fn test_json_type_bool() {
+        let json = parse_json(r#"{"foo": true}"#.as_bytes()).unwrap();
+        let val = json.bool("foo").ok().unwrap();
+        assert_eq!(val, true);
+
+        let json = parse_json(r#"{"foo": false}"#.as_bytes()).unwrap();
+        let val = json.bool("foo").ok().unwrap();
+        assert_eq!(val, false);
+
+        let json = parse_json(r#"{"foo": "true"}"#.as_bytes()).unwrap();
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidBool);
+
+        let json = parse_json(r#"{"foo": "false"}"#.as_bytes()).unwrap();
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidBool);
+
+        let json = parse_json(r#"{"foo": [true]}"#.as_bytes()).unwrap();
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidBool);
+
+        let json = parse_json(r#"{"foo": 123}"#.as_bytes()).unwrap();
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidBool);
+
+        let json = parse_json(r#"{"foo": 12.3}"#.as_bytes()).unwrap();
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidBool);
+
+        let json = parse_json(r#"{"foo": "abcd"}"#.as_bytes()).unwrap();
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidBool);
+
+        let json = parse_json(r#"{"foo": {}}"#.as_bytes()).unwrap();
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidBool);
+
+        let json = parse_json(r#"{"foo": null}"#.as_bytes()).unwrap();
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::InvalidBool);
+
+        let json = JsonValue::Null;
+        let err = json.bool("foo").unwrap_err();
+        assert_eq!(err, JsonError::NotJsonType);
+    }
+
+    #[test]
+    fn test_json_type_u8() {
+        let json = JsonValue::from(123_u64);
+        let val = JsonValue::parse_u8(&json).ok().unwrap();
+        assert_eq!(val, 123);
+
+        let json = JsonValue::from(-1_i64);
+        let err = JsonValue::parse_u8(&json).unwrap_err();
+        assert_eq!(err, JsonError::InvalidU8);
+
+        let json = JsonValue::from(256_u64);
+        let err = JsonValue::parse_u8(&json).unwrap_err();
+        assert_eq!(
+            err,
+            JsonError::OutOfRange(JsonOutOfRangeError::OutOfRangeU8)
+        );
+
+        let json = JsonValue::from("abcd".to_string());
+        let err = JsonValue::parse_u8(&json).unwrap_err();
+        assert_eq!(err, JsonError::InvalidU8);
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let json = parse_json(
+            " {\"a\" : [1, -2, 3.5, 1e3, true, false, null, {}, []], \"b\": \"\\u00e9\\ud83d\\ude00\\n\\\"\\/\"}\n"
+                .as_bytes(),
+        )
+        .unwrap();
+        assert_eq!(
+            json,
+            JsonValue::object(vec![
+                (
+                    "a",
+                    JsonValue::Array(vec![
+                        JsonValue::U64(1),
+                        JsonValue::I64(-2),
+                        JsonValue::F64(3.5),
+                        JsonValue::F64(1000.0),
+                        JsonValue::Bool(true),
+                        JsonValue::Bool(false),
+                        JsonValue::Null,
+                        JsonValue::Object(BTreeMap::new()),
+                        JsonValue::Array(vec![]),
+                    ])
+                ),
+                ("b", JsonValue::from("é😀\n\"/")),
+            ])
+        );
+
+        // Any value may be at the top level
+        assert_eq!(parse_json(b"\"123\"").unwrap(), JsonValue::from("123"));
+        // UTF-8 is decoded
+        assert_eq!(
+            parse_json("\"né\"".as_bytes()).unwrap(),
+            JsonValue::from("né")
+        );
+
+        let invalid = [
+            "",
+            "{",
+            "{\"a\": 1,}",
+            "[1,]",
+            "{\"a\" 1}",
+            "{a: 1}",
+            "{\"a\": 1} {}",
+            "01",
+            "1.",
+            "-",
+            ".5",
+            "1e",
+            "nul",
+            "truex",
+            "\"abc",
+            "\"a\nb\"",
+            "\"\\x\"",
+            "\"\\u12\"",
+            "\"\\ud83d\"",
+            "\"\\ude00\"",
+            "'a'",
+            "{\"a\": 1, \"a\": 2}",
+        ];
+        for json in invalid.iter() {
+            assert!(parse_json(json.as_bytes()).is_none(), "{}", json);
+        }
+        // Invalid UTF-8
+        assert!(parse_json(b"\"\xff\"").is_none());
+        // Nesting is limited
+        assert!(parse_json("[".repeat(MAX_DEPTH).as_bytes()).is_none());
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH), "]".repeat(MAX_DEPTH));
+        assert!(parse_json(nested.as_bytes()).is_some());
+        let nested = format!("{}{}", "[".repeat(MAX_DEPTH + 1), "]".repeat(MAX_DEPTH + 1));
+        assert!(parse_json(nested.as_bytes()).is_none());
+    }
+
+    #[test]
+    fn test_json_roundtrip() {
+        let value = JsonValue::object(vec![
+            ("memo", JsonValue::from("\"}, \"amount\": \"1\\\u{1}")),
+            ("amount", JsonValue::from(u128::MAX)),
+            ("msg", JsonValue::from(None::<String>)),
+        ]);
+        assert_eq!(parse_json(&value.to_bytes()).unwrap(), value);
+        assert_eq!(
+            value.to_string(),
+            format!(
+                r#"{{"amount": "{}", "memo": "\"}}, \"amount\": \"1\\\u0001", "msg": null}}"#,
+                u128::MAX
+            )
+        );
+    }
+
+    #[test]
+    fn test_json_field() {
+        let json = parse_json(
+            br#"{"id": "alice.near", "amount": "10", "memo": null, "data": [{"amount": "1"}, {"amount": 2}]}"#,
+        )
+        .unwrap();
+        assert_eq!(json.field::<String>("id").unwrap(), "alice.near");
+        assert_eq!(
+            json.field::<AccountId>("id").unwrap(),
+            AccountId::try_from("alice.near".to_string()).unwrap()
+        );
+        assert_eq!(json.field::<u128>("amount").unwrap(), 10);
+        assert_eq!(json.field::<Option<String>>("memo").unwrap(), None);
+        assert_eq!(json.field::<Option<bool>>("missing").unwrap(), None);
+
+        let err = json.field::<bool>("missing").unwrap_err();
+        assert_eq!(err.error(), &JsonError::MissingValue);
+        assert_eq!(err.as_ref(), b"ERR_JSON_MISSING_VALUE at missing");
+
+        let err = json.field::<Option<u64>>("amount").unwrap_err();
+        assert_eq!(err.as_ref(), b"ERR_FAILED_PARSE_U64 at amount");
+
+        let err = json.field::<AccountId>("amount").unwrap_err();
+        assert_eq!(err.as_ref(), b"ERR_FAILED_PARSE_ACCOUNT_ID at amount");
+
+        #[derive(Debug)]
+        struct Item {
+            amount: u128,
+        }
+        impl FromJson for Item {
+            fn from_json(value: &JsonValue) -> Result<Self, JsonFieldError> {
+                Ok(Self {
+                    amount: value.field("amount")?,
+                })
+            }
+        }
+        let err = json.field::<Vec<Item>>("data").unwrap_err();
+        assert_eq!(err.path(), "data[1].amount");
+        assert_eq!(
+            err.as_ref(),
+            b"ERR_EXPECTED_STRING_GOT_NUMBER at data[1].amount"
+        );
+        let bytes = JsonValue::Array(vec![json.clone()]).to_bytes();
+        let items = Vec::<Item>::from_json(&parse_json(&bytes).unwrap()).unwrap();
+        assert_eq!(items[0].amount, 10);
+
+        let err = JsonValue::Null.field::<u128>("amount").unwrap_err();
+        assert_eq!(err.as_ref(), b"ERR_NOT_A_JSON_TYPE");
+    }
+
+    #[test]
+    fn test_json_serialization() {
+        // Test showing valid json (without trailing commas) is produced from the
+        // `Display` impl on `JsonValue`.
+
+        // empty object
+        let object = JsonValue::Object(BTreeMap::new());
+        assert_eq!(&format!("{}", object), "{}");
+
+        // object with 1 field
+        let object = JsonValue::Object(
+            vec![("pi".to_string(), JsonValue::F64(core::f64::consts::PI))]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(&format!("{}", object), "{\"pi\": 3.141592653589793}");
+
+        // object with 2 fields
+        let object = JsonValue::Object(
+            vec![
+                ("pi".to_string(), JsonValue::F64(core::f64::consts::PI)),
+                ("Pie".to_string(), JsonValue::String("Apple".to_string())),
+            ]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(
+            &format!("{}", object),
+            "{\"Pie\": \"Apple\", \"pi\": 3.141592653589793}"
+        );
+
+        // object with empty array
+        let object = JsonValue::Object(
+            vec![("empty".to_string(), JsonValue::Array(vec![]))]
+                .into_iter()
+                .collect(),
+        );
+        assert_eq!(&format!("{}", object), "{\"empty\": []}");
+
+        // object with single element array
+        let object = JsonValue::Object(
+            vec![(
+                "numbers".to_string(),
+                JsonValue::Array(vec![JsonValue::U64(42)]),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(&format!("{}", object), "{\"numbers\": [42]}");
+
+        // object with two-element array
+        let object = JsonValue::Object(
+            vec![(
+                "words".to_string(),
+                JsonValue::Array(vec![
+                    JsonValue::String("Hello".to_string()),
+                    JsonValue::String("World".to_string()),
+                ]),
+            )]
+            .into_iter()
+            .collect(),
+        );
+        assert_eq!(
+            &format!("{}", object),
+            "{\"words\": [\"Hello\", \"World\"]}"
+        );
+    }
+}
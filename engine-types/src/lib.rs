@@ -4,6 +4,7 @@
 #![cfg_attr(feature = "log", feature(panic_info_message))]
 
 pub mod account_id;
+pub mod json;
 pub mod parameters;
 pub mod storage;
 pub mod types;
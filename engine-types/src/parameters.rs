@@ -12,9 +12,128 @@ pub struct PromiseCreateArgs {
     pub attached_gas: u64,
 }
 
+/// A promise scheduled by a precompile, optionally followed by a callback
+/// which receives the result of the first call.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub enum PromiseArgs {
+    Create(PromiseCreateArgs),
+    Callback(PromiseWithCallbackArgs),
+    Batch(PromiseBatchArgs),
+    BatchCallback(PromiseBatchWithCallbackArgs),
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct PromiseWithCallbackArgs {
+    pub base: PromiseCreateArgs,
+    pub callback: PromiseCreateArgs,
+}
+
+/// Function calls on a single account, executed in order as one receipt.
+/// If any of the calls fails, the effects of all of them are reverted.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct PromiseBatchArgs {
+    pub target_account_id: AccountId,
+    pub actions: Vec<PromiseFunctionCallAction>,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct PromiseFunctionCallAction {
+    pub method: String,
+    pub args: Vec<u8>,
+    pub attached_balance: u128,
+    pub attached_gas: u64,
+}
+
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct PromiseBatchWithCallbackArgs {
+    pub base: PromiseBatchArgs,
+    pub callback: PromiseCreateArgs,
+}
+
+/// Arguments of the engine's private `evm_callback` method, which forwards
+/// the result of a NEAR promise to an EVM contract.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct EvmCallbackArgs {
+    /// EVM contract which scheduled the promise; used as the caller of the callback.
+    pub sender: EthAddress,
+    /// EVM contract receiving the callback.
+    pub callback_address: EthAddress,
+    /// Selector of the callback function, which takes `(uint8 status, bytes result)`.
+    pub selector: [u8; 4],
+    /// EVM gas limit of the callback, paid for when the promise was scheduled.
+    pub gas_limit: u64,
+}
+
+/// Arguments of the engine's private `refund_on_error` method, which reverts
+/// an exit whose NEAR promise failed.
+#[derive(Debug, BorshSerialize, BorshDeserialize)]
+pub struct RefundCallArgs {
+    /// EVM address receiving the refund.
+    pub recipient_address: EthAddress,
+    /// Bridged token to mint back; `None` when ETH was exited. For an ERC-721
+    /// token, `amount` is the id of the token.
+    pub erc20_address: Option<EthAddress>,
+    /// Exit precompile which received the exited ETH.
+    pub exit_address: EthAddress,
+    pub amount: RawU256,
+    /// Set when the exit was done with `ft_transfer_call`, whose result is
+    /// the amount actually used by the receiver; only the rest is refunded.
+    pub transfer_call: bool,
+    /// Set when `erc20_address` is an EVM-native ERC-20 token, which is
+    /// unlocked from its escrow instead of minted.
+    pub native_erc20: bool,
+}
+
 /// withdraw NEAR eth-connector call args
-#[derive(BorshSerialize, BorshDeserialize)]
+///
+/// The fee and the sender are only serialized when there is one, after the other
+/// fields, so that the args without them keep the layout of the callers which
+/// predate them. A zero fee is serialized before a sender without a fee.
 pub struct WithdrawCallArgs {
     pub recipient_address: EthAddress,
     pub amount: Balance,
+    /// Part of `amount` paid to the relayer which finalizes the withdrawal on Ethereum
+    pub fee: Option<Balance>,
+    /// EVM address withdrawing from Aurora, set by the exit precompile only
+    pub sender: Option<EthAddress>,
+}
+
+impl BorshSerialize for WithdrawCallArgs {
+    fn serialize<W: borsh::maybestd::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> borsh::maybestd::io::Result<()> {
+        self.recipient_address.serialize(writer)?;
+        self.amount.serialize(writer)?;
+        if self.fee.is_some() || self.sender.is_some() {
+            self.fee.unwrap_or(0).serialize(writer)?;
+        }
+        match self.sender {
+            Some(sender) => sender.serialize(writer),
+            None => Ok(()),
+        }
+    }
+}
+
+impl BorshDeserialize for WithdrawCallArgs {
+    fn deserialize(buf: &mut &[u8]) -> borsh::maybestd::io::Result<Self> {
+        let recipient_address = EthAddress::deserialize(buf)?;
+        let amount = Balance::deserialize(buf)?;
+        let fee = if buf.is_empty() {
+            None
+        } else {
+            Some(Balance::deserialize(buf)?)
+        };
+        let sender = if buf.is_empty() {
+            None
+        } else {
+            Some(EthAddress::deserialize(buf)?)
+        };
+        Ok(Self {
+            recipient_address,
+            amount,
+            fee,
+            sender,
+        })
+    }
 }
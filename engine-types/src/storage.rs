@@ -20,6 +20,15 @@ pub enum KeyPrefix {
     Generation = 0x7,
     Nep141Erc20Map = 0x8,
     Erc20Nep141Map = 0x9,
+    CrossContractCallPermission = 0xa,
+    Nep171Erc721Map = 0xb,
+    Erc721Nep171Map = 0xc,
+    Erc721TokenIdMap = 0xd,
+    Erc20AutoDeployDenyList = 0xe,
+    Erc20TokenIndex = 0xf,
+    NativeErc20Nep141Map = 0x10,
+    Nep141NativeErc20Map = 0x11,
+    Erc20AutoDeployFunding = 0x12,
 }
 
 /// Enum used to differentiate different storage keys used by eth-connector
@@ -31,8 +40,29 @@ pub enum EthConnectorStorageId {
     PausedMask = 0x3,
     StatisticsAuroraAccountsCounter = 0x4,
     FungibleTokenMetadata = 0x5,
+    EthCustodians = 0x6,
+    BridgeLimits = 0x7,
+    WithdrawUsage = 0x8,
+    PendingWithdrawal = 0x9,
+    PendingWithdrawalNonce = 0xa,
+    FungibleTokenMetadataVersion = 0xb,
+    EvmBalances = 0xc,
+    BridgedAmounts = 0xd,
+    DepositEventSchemas = 0xe,
+    UsedProofs = 0xf,
+    FrozenAccounts = 0x10,
+    FrozenEvmAddresses = 0x11,
+    StorageDeposits = 0x12,
+    PendingDeposit = 0x13,
+    PendingDepositNonce = 0x14,
+    LastDepositBlock = 0x15,
+    UsedProofsMigrated = 0x16,
 }
 
+/// Key in `KeyPrefix::Config` of the address of the escrow contract holding
+/// the EVM-native ERC-20 tokens bridged to NEAR.
+pub const NATIVE_ERC20_ESCROW_KEY: &[u8; 19] = b"NATIVE_ERC20_ESCROW";
+
 /// We can't use const generic over Enum, but we can do it over integral type
 pub type KeyPrefixU8 = u8;
 
@@ -50,6 +80,15 @@ impl From<KeyPrefixU8> for KeyPrefix {
             0x7 => Self::Generation,
             0x8 => Self::Nep141Erc20Map,
             0x9 => Self::Erc20Nep141Map,
+            0xa => Self::CrossContractCallPermission,
+            0xb => Self::Nep171Erc721Map,
+            0xc => Self::Erc721Nep171Map,
+            0xd => Self::Erc721TokenIdMap,
+            0xe => Self::Erc20AutoDeployDenyList,
+            0xf => Self::Erc20TokenIndex,
+            0x10 => Self::NativeErc20Nep141Map,
+            0x11 => Self::Nep141NativeErc20Map,
+            0x12 => Self::Erc20AutoDeployFunding,
             _ => unreachable!(),
         }
     }
@@ -60,6 +99,11 @@ pub fn bytes_to_key(prefix: KeyPrefix, bytes: &[u8]) -> Vec<u8> {
     [&[VersionPrefix::V1 as u8], &[prefix as u8], bytes].concat()
 }
 
+/// Key of the entry `id` of the eth-connector storage `storage_id`
+pub fn eth_connector_key(storage_id: EthConnectorStorageId, id: &[u8]) -> Vec<u8> {
+    bytes_to_key(KeyPrefix::EthConnector, &[&[storage_id as u8], id].concat())
+}
+
 #[allow(dead_code)]
 pub fn address_to_key(prefix: KeyPrefix, address: &Address) -> [u8; 22] {
     let mut result = [0u8; 22];
@@ -1,4 +1,5 @@
 use super::{str, vec, Add, Address, String, Sub, Vec, U256};
+use crate::json::JsonValue;
 use borsh::{BorshDeserialize, BorshSerialize};
 
 pub type Balance = u128;
@@ -128,6 +129,12 @@ pub struct StorageBalanceBounds {
     pub max: Option<Balance>,
 }
 
+impl StorageBalanceBounds {
+    pub fn to_json_bytes(&self) -> Vec<u8> {
+        JsonValue::object(vec![("min", self.min.into()), ("max", self.max.into())]).to_bytes()
+    }
+}
+
 /// promise results structure
 pub enum PromiseResult {
     NotReady,
@@ -1,28 +1,46 @@
 use crate::admin_controlled::{AdminControlled, PausedMask};
-use crate::deposit_event::DepositedEvent;
+use crate::deposit_event::{DepositedEvent, DepositedEventSchema};
 use crate::engine::Engine;
+use crate::events::{ConnectorEvent, FtEvent};
 use crate::fungible_token::{FungibleToken, FungibleTokenMetadata};
-use crate::json::parse_json;
+use crate::json::{parse_json, JsonValue};
 use crate::parameters::{
-    BalanceOfCallArgs, BalanceOfEthCallArgs, FinishDepositCallArgs, InitCallArgs,
-    NEP141FtOnTransferArgs, PauseEthConnectorCallArgs, ResolveTransferCallArgs,
-    SetContractDataCallArgs, StorageBalanceOfCallArgs, StorageDepositCallArgs,
-    StorageWithdrawCallArgs, TransferCallArgs, TransferCallCallArgs, WithdrawResult,
+    BalanceOfCallArgs, BalanceOfEthCallArgs, BridgeLimits, CancelWithdrawalArgs, DepositAndCall,
+    DepositCall, DepositMessage, DepositMessageError, FinishDepositCallArgs, Freeze, FreezeArgs,
+    FreezeTarget, InitCallArgs, LegacyFinishDepositCallArgs, MigrateUsedProofArgs,
+    NEP141FtOnTransferArgs, PauseEthConnectorCallArgs, ReconcileSupplyArgs, ReleaseDepositArgs,
+    ReleaseWithdrawalArgs, ResolveTransferCallArgs, RotateEthCustodianArgs,
+    SetContractDataCallArgs, SetDepositEventSchemaArgs, StorageBalanceOfCallArgs,
+    StorageDepositCallArgs, StorageUnregisterCallArgs, StorageWithdrawCallArgs, SupplyAudit,
+    TransferCallArgs, TransferCallCallArgs, UsedProof, WithdrawResult, WithdrawWithFeeResult,
 };
-use crate::prelude::sdk::types::{ExpectUtf8, SdkUnwrap};
+use crate::prelude::sdk::types::{near_account_to_evm_address, ExpectUtf8, SdkUnwrap};
 use crate::prelude::{
-    format, sdk, str, validate_eth_address, AccountId, Address, Balance, BorshDeserialize,
-    BorshSerialize, EthAddress, EthConnectorStorageId, Gas, KeyPrefix, PromiseResult, String,
-    ToString, TryFrom, Vec, WithdrawCallArgs, ERR_FAILED_PARSE, ERR_INVALID_ETH_ADDRESS, H160,
-    U256,
+    eth_connector_key, format, sdk, str, validate_eth_address, AccountId, Address, Balance,
+    BorshDeserialize, BorshSerialize, EthAddress, EthConnectorStorageId, Gas, KeyPrefix,
+    PromiseResult, RawH256, String, ToString, TryFrom, Vec, Wei, WithdrawCallArgs,
+    ERR_FAILED_PARSE, ERR_INVALID_ETH_ADDRESS, H160, U256,
 };
-use crate::proof::Proof;
+use crate::proof::{legacy_proof_key, Proof};
 
 pub const ERR_NOT_ENOUGH_BALANCE_FOR_FEE: &str = "ERR_NOT_ENOUGH_BALANCE_FOR_FEE";
+pub const ERR_WITHDRAW_CAP_EXCEEDED: &str = "ERR_WITHDRAW_CAP_EXCEEDED";
+pub const ERR_DEPOSIT_ABOVE_MAX: &str = "ERR_DEPOSIT_ABOVE_MAX";
+pub const ERR_SUPPLY_DRIFT_ABOVE_TOLERANCE: &str = "ERR_SUPPLY_DRIFT_ABOVE_TOLERANCE";
+pub const ERR_DEPOSIT_CALL_NOT_BY_RECIPIENT: &str = "ERR_DEPOSIT_CALL_NOT_BY_RECIPIENT";
+pub const ERR_ACCOUNT_FROZEN: &str = "ERR_ACCOUNT_FROZEN";
+pub const ERR_DEPRECATION_BELOW_ACTIVATION: &str = "ERR_DEPRECATION_BELOW_ACTIVATION";
+pub const ERR_DEPRECATION_BELOW_LAST_DEPOSIT: &str = "ERR_DEPRECATION_BELOW_LAST_DEPOSIT";
+pub const ERR_USED_PROOFS_MIGRATED: &str = "ERR_USED_PROOFS_MIGRATED";
 pub const NO_DEPOSIT: Balance = 0;
 const GAS_FOR_FINISH_DEPOSIT: Gas = 50_000_000_000_000;
-// Note: Is 40Tgas always enough?
-const GAS_FOR_VERIFY_LOG_ENTRY: Gas = 40_000_000_000_000;
+/// NEAR gas of the `deposit_call` following a deposit into an EVM call, which
+/// covers the `MAX_DEPOSIT_CALL_GAS_LIMIT` of the call
+const GAS_FOR_DEPOSIT_CALL: Gas = 150_000_000_000_000;
+const GAS_FOR_ASSERT_ETHCLIENT_HASH: Gas = 10_000_000_000_000;
+/// Number of buckets the withdrawals of a rolling window are recorded in, which
+/// bounds the storage of the usage of the caps
+const WITHDRAW_USAGE_BUCKETS: u64 = 16;
 
 pub const UNPAUSE_ALL: PausedMask = 0;
 pub const PAUSE_DEPOSIT: PausedMask = 1 << 0;
@@ -39,9 +57,70 @@ pub struct EthConnectorContract {
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct EthConnector {
     pub prover_account: AccountId,
+    /// Active custodian, receiving the new deposits and the withdrawals.
     pub eth_custodian_address: EthAddress,
 }
 
+/// Ethereum custodian, whose deposits are accepted for the Ethereum blocks from
+/// `activation_height` and before `deprecation_height`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EthCustodian {
+    pub address: EthAddress,
+    pub activation_height: u64,
+    pub deprecation_height: Option<u64>,
+}
+
+impl EthCustodian {
+    fn accepts(&self, address: EthAddress, block_number: u64) -> bool {
+        self.address == address
+            && self.activation_height <= block_number
+            && self
+                .deprecation_height
+                .map_or(true, |height| block_number < height)
+    }
+}
+
+/// Withdrawal over the caps, released after a delay
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct PendingWithdrawal {
+    pub result: WithdrawResult,
+    pub release_height: u64,
+    pub fee: Balance,
+    /// Refunded if the withdrawal is cancelled
+    pub refund: WithdrawRefund,
+}
+
+/// Account which made a withdrawal, refunded if it is cancelled
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum WithdrawRefund {
+    Near(AccountId),
+    /// EVM address which withdrew from Aurora
+    Aurora(EthAddress),
+}
+
+/// Deposit above the maximum, released after a delay
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct PendingDeposit {
+    pub args: FinishDepositCallArgs,
+    pub release_height: u64,
+}
+
+/// Amounts bridged from and to Ethereum, which the supply on NEAR is audited against
+#[derive(Default, BorshSerialize, BorshDeserialize)]
+struct BridgedAmounts {
+    deposited: Balance,
+    withdrawn: Balance,
+    /// Burned by forced `storage_unregister` calls, while still locked on Ethereum
+    burned: Balance,
+}
+
+/// Source of a withdrawal, whose caps apply
+#[derive(Clone, Copy)]
+enum WithdrawSource {
+    Near = 0x0,
+    Aurora = 0x1,
+}
+
 /// Token message data
 #[derive(BorshSerialize, BorshDeserialize)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
@@ -51,6 +130,12 @@ pub enum TokenMessageData {
         receiver_id: AccountId,
         message: String,
     },
+    /// Deposit into an EVM call, made by the recipient
+    EthCall {
+        receiver_id: AccountId,
+        recipient: EthAddress,
+        call: DepositCall,
+    },
 }
 
 /// On-transfer message
@@ -58,6 +143,8 @@ pub struct OnTransferMessageData {
     pub relayer: AccountId,
     pub recipient: EthAddress,
     pub fee: U256,
+    /// EVM call made once the tokens are minted
+    pub call: Option<DepositCall>,
 }
 
 impl EthConnectorContract {
@@ -113,8 +200,10 @@ impl EthConnectorContract {
         .save_ft_contract();
     }
 
-    /// Sets the contract data and returns it back
+    /// Sets the contract data and returns it back. The custodians are reset to the
+    /// given one.
     pub fn set_contract_data(args: SetContractDataCallArgs) -> EthConnector {
+        args.metadata.validate().sdk_unwrap();
         // Get initial contract arguments
         let contract_data = EthConnector {
             prover_account: args.prover_account,
@@ -125,6 +214,9 @@ impl EthConnectorContract {
             &Self::get_contract_key(&EthConnectorStorageId::Contract),
             &contract_data,
         );
+        sdk::remove_storage(&Self::get_contract_key(
+            &EthConnectorStorageId::EthCustodians,
+        ));
 
         sdk::save_contract(
             &Self::get_contract_key(&EthConnectorStorageId::FungibleTokenMetadata),
@@ -134,23 +226,51 @@ impl EthConnectorContract {
         contract_data
     }
 
-    /// Parse event message data for tokens
+    /// Parse event message data for tokens. The message of deposits to Aurora
+    /// is either the recipient address, or a versioned `DepositMessage`.
     fn parse_event_message(&self, message: &str) -> TokenMessageData {
-        let data: Vec<_> = message.split(':').collect();
-        assert!(data.len() < 3);
+        let data: Vec<_> = message.splitn(2, ':').collect();
         let account_id = AccountId::try_from(data[0].as_bytes()).sdk_unwrap();
         if data.len() == 1 {
-            TokenMessageData::Near(account_id)
-        } else {
-            TokenMessageData::Eth {
+            return TokenMessageData::Near(account_id);
+        }
+        if !DepositMessage::is_versioned(data[1]) {
+            return TokenMessageData::Eth {
                 receiver_id: account_id,
                 message: data[1].into(),
-            }
+            };
+        }
+        let message = DepositMessage::parse(data[1]).sdk_unwrap();
+        match message.call {
+            Some(call) => TokenMessageData::EthCall {
+                receiver_id: account_id,
+                recipient: message.recipient,
+                call,
+            },
+            None => TokenMessageData::Eth {
+                receiver_id: account_id,
+                message: hex::encode(message.recipient),
+            },
         }
     }
 
-    /// Get on-transfer data from message
-    fn parse_on_transfer_message(&self, message: &str) -> OnTransferMessageData {
+    /// Get on-transfer data from message, either a versioned `DepositMessage`,
+    /// whose relayer is the signer, or the legacy `relayer:hexdata`
+    fn parse_on_transfer_message(
+        &self,
+        message: &str,
+    ) -> Result<OnTransferMessageData, DepositMessageError> {
+        if DepositMessage::is_versioned(message) {
+            let message = DepositMessage::parse(message)?;
+            // The fee of nETH deposits is always taken from the deposit
+            return Ok(OnTransferMessageData {
+                relayer: AccountId::try_from(sdk::signer_account_id()).sdk_unwrap(),
+                recipient: message.recipient,
+                fee: message.fee,
+                call: message.call,
+            });
+        }
+
         let data: Vec<_> = message.split(':').collect();
         assert_eq!(data.len(), 2);
 
@@ -162,11 +282,12 @@ impl EthConnectorContract {
         recipient.copy_from_slice(&msg[32..52]);
         // Check account
         let account_id = AccountId::try_from(data[0].as_bytes()).sdk_unwrap();
-        OnTransferMessageData {
+        Ok(OnTransferMessageData {
             relayer: account_id,
             recipient,
             fee: U256::from_little_endian(&fee[..]),
-        }
+            call: None,
+        })
     }
 
     /// Prepare message for `ft_transfer_call` -> `ft_on_transfer`
@@ -199,7 +320,11 @@ impl EthConnectorContract {
         let raw_proof = sdk::read_input();
         let proof: Proof = Proof::try_from_slice(&raw_proof).expect(ERR_FAILED_PARSE);
         // Fetch event data from Proof
-        let event = DepositedEvent::from_log_entry_data(&proof.log_entry_data);
+        let event = DepositedEvent::from_log_entry_data(
+            &proof.log_entry_data,
+            Self::get_deposit_event_schema,
+        )
+        .sdk_unwrap();
 
         sdk::log!(&format!(
             "Deposit started: from {} to recipient {:?} with amount: {:?} and fee {:?}",
@@ -209,52 +334,66 @@ impl EthConnectorContract {
             event.fee.as_u128()
         ));
 
+        // Verify the receipt proof against the block header, so that the prover
+        // only has to verify the block hash with a cross-contract call
+        let block = proof.verify().sdk_unwrap();
+
         sdk::log!(&format!(
             "Event's address {}, custodian address {}",
             hex::encode(&event.eth_custodian_address),
             hex::encode(&self.contract.eth_custodian_address),
         ));
 
-        assert_eq!(
-            event.eth_custodian_address, self.contract.eth_custodian_address,
-            "ERR_WRONG_EVENT_ADDRESS",
-        );
+        // Deposits to the previous custodians are accepted until their deprecation
+        let is_accepted_custodian = self
+            .get_eth_custodians()
+            .iter()
+            .any(|custodian| custodian.accepts(event.eth_custodian_address, block.block_number));
+        assert!(is_accepted_custodian, "ERR_WRONG_EVENT_ADDRESS");
 
         assert!(
             event.amount > event.fee,
             "{}",
             ERR_NOT_ENOUGH_BALANCE_FOR_FEE,
         );
+        // Deposits above the maximum are queued in `finish_deposit` if they can be
+        let limits = Self::get_bridge_limits();
+        if let (Some(max_deposit), None) = (limits.max_deposit, limits.queue_delay_blocks) {
+            assert!(
+                event.amount <= U256::from(max_deposit),
+                "{}",
+                ERR_DEPOSIT_ABOVE_MAX,
+            );
+        }
 
-        // Verify proof data with cross-contract call to prover account
         sdk::log!(&format!(
-            "Deposit verify_log_entry for prover: {}",
+            "Deposit assert_ethclient_hash for prover: {}",
             self.contract.prover_account,
         ));
-
-        // Do not skip bridge call. This is only used for development and diagnostics.
-        let skip_bridge_call = false.try_to_vec().unwrap();
-        let mut proof_to_verify = raw_proof;
-        proof_to_verify.extend(skip_bridge_call);
         let promise0 = sdk::promise_create(
             self.contract.prover_account.as_bytes(),
-            b"verify_log_entry",
-            &proof_to_verify,
+            b"assert_ethclient_hash",
+            &block.try_to_vec().unwrap(),
             NO_DEPOSIT,
-            GAS_FOR_VERIFY_LOG_ENTRY,
+            GAS_FOR_ASSERT_ETHCLIENT_HASH,
         );
         let predecessor_account_id = AccountId::try_from(sdk::predecessor_account_id()).unwrap();
+        let proof_key = proof.get_key();
 
         // Finalize deposit
+        let mut finish_deposit_gas = GAS_FOR_FINISH_DEPOSIT;
         let data = match self.parse_event_message(&event.recipient) {
             // Deposit to NEAR accounts
             TokenMessageData::Near(account_id) => FinishDepositCallArgs {
                 new_owner_id: account_id,
                 amount: event.amount.as_u128(),
-                proof_key: proof.get_key(),
+                proof_key,
                 relayer_id: predecessor_account_id,
                 fee: event.fee.as_u128(),
                 msg: None,
+                block_number: block.block_number,
+                recipient: event.recipient.clone(),
+                deposit_call: None,
             }
             .try_to_vec()
             .unwrap(),
@@ -280,10 +419,44 @@ impl EthConnectorContract {
                 FinishDepositCallArgs {
                     new_owner_id: current_account_id,
                     amount: event.amount.as_u128(),
-                    proof_key: proof.get_key(),
+                    proof_key,
                     relayer_id: predecessor_account_id,
                     fee: event.fee.as_u128(),
                     msg: Some(transfer_data),
+                    block_number: block.block_number,
+                    recipient: event.recipient.clone(),
+                    deposit_call: None,
+                }
+                .try_to_vec()
+                .unwrap()
+            }
+            // Deposit into an EVM call, where the tokens are minted on Aurora in
+            // `finish_deposit` directly, for the call to follow them
+            TokenMessageData::EthCall {
+                receiver_id,
+                recipient,
+                call,
+            } => {
+                let current_account_id = AccountId::try_from(sdk::current_account_id()).unwrap();
+                assert!(receiver_id == current_account_id, "ERR_WRONG_RECEIVER");
+                // The call is made by the recipient, so only the recipient may deposit into it
+                assert!(
+                    recipient == event.sender,
+                    "{}",
+                    ERR_DEPOSIT_CALL_NOT_BY_RECIPIENT,
+                );
+                // `finish_deposit` forwards the gas of the call
+                finish_deposit_gas += GAS_FOR_DEPOSIT_CALL;
+                FinishDepositCallArgs {
+                    new_owner_id: current_account_id,
+                    amount: event.amount.as_u128(),
+                    proof_key,
+                    relayer_id: predecessor_account_id,
+                    fee: event.fee.as_u128(),
+                    msg: None,
+                    block_number: block.block_number,
+                    recipient: event.recipient.clone(),
+                    deposit_call: Some(DepositAndCall { recipient, call }),
                 }
                 .try_to_vec()
                 .unwrap()
@@ -296,7 +469,7 @@ impl EthConnectorContract {
             b"finish_deposit",
             &data[..],
             NO_DEPOSIT,
-            GAS_FOR_FINISH_DEPOSIT,
+            finish_deposit_gas,
         );
         sdk::promise_return(promise1);
     }
@@ -306,11 +479,106 @@ impl EthConnectorContract {
     /// is that in this case we only calculate the amount to be credited but
     /// do not save it, however, if an error occurs during the calculation,
     /// this will happen before `record_proof`. After that contract will save.
-    pub fn finish_deposit(&mut self) {
+    pub fn finish_deposit(&mut self, engine: &Engine) {
         sdk::assert_private_call();
-        let data: FinishDepositCallArgs =
-            FinishDepositCallArgs::try_from_slice(&sdk::read_input()).unwrap();
+        let input = sdk::read_input();
+        let data: FinishDepositCallArgs = match FinishDepositCallArgs::try_from_slice(&input) {
+            Ok(data) => data,
+            // Callbacks scheduled before the proofs were keyed by their hash
+            Err(_) => {
+                let data = LegacyFinishDepositCallArgs::try_from_slice(&input).unwrap();
+                self.finish_legacy_deposit(data);
+                return;
+            }
+        };
         sdk::log!(&format!("Finish deposit with the amount: {}", data.amount));
+        Self::assert_proof_verified();
+
+        let used_proof = UsedProof {
+            block_number: data.block_number,
+            recipient: data.recipient.clone(),
+            amount: data.amount,
+            finalized_at: sdk::block_index(),
+        };
+
+        let limits = Self::get_bridge_limits();
+        if limits.max_deposit.map_or(false, |max| data.amount > max) {
+            // The proof is only used once the deposit is queued, so that the
+            // deposits rejected while they can't be queued can be retried
+            let delay = limits.queue_delay_blocks.expect(ERR_DEPOSIT_ABOVE_MAX);
+            self.record_proof(&data.proof_key, &used_proof);
+            Self::queue_deposit(data, delay);
+            return;
+        }
+        self.mint_deposit(engine, data, Some(&used_proof));
+    }
+
+    /// Mints the deposit, recording its proof unless it was already recorded
+    /// when the deposit was queued
+    fn mint_deposit(
+        &mut self,
+        engine: &Engine,
+        data: FinishDepositCallArgs,
+        used_proof: Option<&UsedProof>,
+    ) {
+        // Mint tokens to recipient minus fee
+        if let Some(deposit_call) = &data.deposit_call {
+            // Mint - the tokens on Aurora are backed by the ones of the current account
+            self.mint_eth_on_near(data.new_owner_id.clone(), data.amount);
+            self.mint_deposit_on_aurora(
+                engine,
+                deposit_call.recipient,
+                data.amount,
+                data.fee,
+                &data.relayer_id,
+            );
+            // Store proof only after `mint` calculations
+            self.record_used_proof(&data.proof_key, used_proof);
+            // Save new contract data
+            self.save_ft_contract();
+            // The call is made in its own receipt, so that the minted tokens are
+            // kept if it fails or runs out of gas
+            let promise = sdk::promise_create(
+                &sdk::current_account_id(),
+                b"deposit_call",
+                &deposit_call.try_to_vec().unwrap(),
+                NO_DEPOSIT,
+                GAS_FOR_DEPOSIT_CALL,
+            );
+            sdk::promise_return(promise);
+        } else if let Some(msg) = &data.msg {
+            // Mint - calculate new balances
+            self.mint_eth_on_near(data.new_owner_id.clone(), data.amount);
+            // Store proof only after `mint` calculations
+            self.record_used_proof(&data.proof_key, used_proof);
+            // Save new contract data
+            self.save_ft_contract();
+            let transfer_call_args = TransferCallCallArgs::try_from_slice(msg).unwrap();
+            self.ft_transfer_call(transfer_call_args);
+        } else {
+            // Mint - calculate new balances
+            self.mint_eth_on_near(data.new_owner_id.clone(), data.amount - data.fee);
+            self.mint_eth_on_near(data.relayer_id.clone(), data.fee);
+            // Store proof only after `mint` calculations
+            self.record_used_proof(&data.proof_key, used_proof);
+            // Save new contract data
+            self.save_ft_contract();
+        }
+        let mut bridged = Self::get_bridged_amounts();
+        bridged.deposited = bridged.deposited.saturating_add(data.amount);
+        Self::save_bridged_amounts(&bridged);
+        ConnectorEvent::DepositFinalized {
+            new_owner_id: data.new_owner_id.as_ref(),
+            amount: data.amount,
+            fee: data.fee,
+            relayer_id: data.relayer_id.as_ref(),
+            proof_key: &hex::encode(data.proof_key),
+        }
+        .emit();
+    }
+
+    /// Checks the result of the proof verification by the prover
+    fn assert_proof_verified() {
         assert_eq!(sdk::promise_results_count(), 1);
 
         // Check promise results
@@ -323,26 +591,122 @@ impl EthConnectorContract {
         sdk::log!("Check verification_success");
         let verification_success = bool::try_from_slice(&data0).unwrap();
         assert!(verification_success, "ERR_VERIFY_PROOF");
+    }
+
+    /// Finishes a deposit scheduled with the legacy args, whose proof is
+    /// recorded with its legacy key, as it was when the deposit was made
+    fn finish_legacy_deposit(&mut self, data: LegacyFinishDepositCallArgs) {
+        sdk::log!(&format!("Finish deposit with the amount: {}", data.amount));
+        Self::assert_proof_verified();
+        // The legacy keys are no longer looked up
+        assert!(!Self::are_used_proofs_migrated(), ERR_USED_PROOFS_MIGRATED);
+
+        let proof_key = Self::used_event_key(&data.proof_key);
+        sdk::log!(&format!("Record proof: {}", data.proof_key));
+        assert!(!sdk::storage_has_key(&proof_key), "ERR_PROOF_EXIST");
 
         // Mint tokens to recipient minus fee
-        if let Some(msg) = data.msg {
+        if let Some(msg) = &data.msg {
             // Mint - calculate new balances
-            self.mint_eth_on_near(data.new_owner_id, data.amount);
+            self.mint_eth_on_near(data.new_owner_id.clone(), data.amount);
             // Store proof only after `mint` calculations
-            self.record_proof(&data.proof_key);
+            sdk::save_contract(&proof_key, &0u8);
             // Save new contract data
             self.save_ft_contract();
-            let transfer_call_args = TransferCallCallArgs::try_from_slice(&msg).unwrap();
+            let transfer_call_args = TransferCallCallArgs::try_from_slice(msg).unwrap();
             self.ft_transfer_call(transfer_call_args);
         } else {
             // Mint - calculate new balances
             self.mint_eth_on_near(data.new_owner_id.clone(), data.amount - data.fee);
-            self.mint_eth_on_near(data.relayer_id, data.fee);
+            self.mint_eth_on_near(data.relayer_id.clone(), data.fee);
             // Store proof only after `mint` calculations
-            self.record_proof(&data.proof_key);
+            sdk::save_contract(&proof_key, &0u8);
             // Save new contract data
             self.save_ft_contract();
         }
+        let mut bridged = Self::get_bridged_amounts();
+        bridged.deposited = bridged.deposited.saturating_add(data.amount);
+        Self::save_bridged_amounts(&bridged);
+        ConnectorEvent::DepositFinalized {
+            new_owner_id: data.new_owner_id.as_ref(),
+            amount: data.amount,
+            fee: data.fee,
+            relayer_id: data.relayer_id.as_ref(),
+            proof_key: &data.proof_key,
+        }
+        .emit();
+    }
+
+    fn record_used_proof(&mut self, key: &RawH256, used_proof: Option<&UsedProof>) {
+        if let Some(used_proof) = used_proof {
+            self.record_proof(key, used_proof);
+        }
+    }
+
+    fn pending_deposit_key(id: u64) -> Vec<u8> {
+        let mut key = Self::get_contract_key(&EthConnectorStorageId::PendingDeposit);
+        key.extend_from_slice(&id.to_le_bytes());
+        key
+    }
+
+    /// Queues the deposit, and returns its id
+    fn queue_deposit(args: FinishDepositCallArgs, delay: u64) {
+        let nonce_key = Self::get_contract_key(&EthConnectorStorageId::PendingDepositNonce);
+        let id = sdk::read_u64(&nonce_key).unwrap_or(0);
+        sdk::write_storage(&nonce_key, &(id + 1).to_le_bytes());
+
+        let release_height = sdk::block_index().saturating_add(delay);
+        sdk::log!(&format!(
+            "Deposit {} is queued until block {}",
+            id, release_height
+        ));
+        sdk::save_contract(
+            &Self::pending_deposit_key(id),
+            &PendingDeposit {
+                args,
+                release_height,
+            },
+        );
+        sdk::return_output(&id.try_to_vec().unwrap());
+    }
+
+    /// Releases a queued deposit once its delay is over. It is minted by
+    /// `finish_released_deposit`, for the transfers to be made by the current account.
+    pub fn release_deposit(&self, args: ReleaseDepositArgs) {
+        self.assert_not_paused(PAUSE_DEPOSIT);
+
+        let key = Self::pending_deposit_key(args.id);
+        let pending = sdk::read_storage(&key)
+            .and_then(|data| PendingDeposit::try_from_slice(&data).ok())
+            .expect("ERR_PENDING_DEPOSIT_NOT_FOUND");
+        assert!(
+            sdk::block_index() >= pending.release_height,
+            "ERR_DEPOSIT_NOT_RELEASED"
+        );
+        sdk::remove_storage(&key);
+
+        let gas = if pending.args.deposit_call.is_some() {
+            GAS_FOR_FINISH_DEPOSIT + GAS_FOR_DEPOSIT_CALL
+        } else {
+            GAS_FOR_FINISH_DEPOSIT
+        };
+        let promise = sdk::promise_create(
+            &sdk::current_account_id(),
+            b"finish_released_deposit",
+            &pending.args.try_to_vec().unwrap(),
+            NO_DEPOSIT,
+            gas,
+        );
+        sdk::promise_return(promise);
+    }
+
+    /// Mints a released deposit, whose proof was recorded when it was queued
+    /// (private method)
+    pub fn finish_released_deposit(&mut self, engine: &Engine) {
+        sdk::assert_private_call();
+        let data: FinishDepositCallArgs =
+            FinishDepositCallArgs::try_from_slice(&sdk::read_input()).unwrap();
+        self.mint_deposit(engine, data, None);
     }
 
     /// Internal ETH withdraw ETH logic
@@ -351,12 +715,28 @@ impl EthConnectorContract {
         self.save_ft_contract();
     }
 
-    /// Record used proof as hash key
-    fn record_proof(&mut self, key: &str) {
-        sdk::log!(&format!("Record proof: {}", key));
+    /// Record used proof by its hash, with the deposit which consumed it
+    fn record_proof(&mut self, key: &RawH256, used_proof: &UsedProof) {
+        sdk::log!(&format!("Record proof: {}", hex::encode(key)));
+
+        assert!(!self.check_used_proof(key), "ERR_PROOF_EXIST");
+        sdk::save_contract(&Self::used_proof_key(key), used_proof);
+
+        if Self::get_last_deposit_block().map_or(true, |last| used_proof.block_number > last) {
+            sdk::write_storage(
+                &Self::get_contract_key(&EthConnectorStorageId::LastDepositBlock),
+                &used_proof.block_number.to_le_bytes(),
+            );
+        }
+    }
 
-        assert!(!self.check_used_event(key), "ERR_PROOF_EXIST");
-        self.save_used_event(key);
+    /// Returns the latest Ethereum block of the deposits, which is the latest
+    /// block known to have been reached on Ethereum
+    fn get_last_deposit_block() -> Option<u64> {
+        sdk::read_u64(&Self::get_contract_key(
+            &EthConnectorStorageId::LastDepositBlock,
+        ))
+        .ok()
     }
 
     ///  Mint nETH tokens
@@ -367,6 +747,12 @@ impl EthConnectorContract {
             self.ft.accounts_insert(&owner_id, 0);
         }
         self.ft.internal_deposit_eth_to_near(&owner_id, amount);
+        FtEvent::Mint {
+            owner_id: owner_id.as_ref(),
+            amount,
+            memo: None,
+        }
+        .emit();
     }
 
     ///  Mint ETH tokens
@@ -377,6 +763,12 @@ impl EthConnectorContract {
             hex::encode(owner_id)
         ));
         self.ft.internal_deposit_eth_to_aurora(owner_id, amount);
+        Self::update_evm_balances(amount, 0);
+        ConnectorEvent::EvmMint {
+            address: owner_id,
+            amount,
+        }
+        .emit();
     }
 
     /// Burn ETH tokens
@@ -387,50 +779,389 @@ impl EthConnectorContract {
             hex::encode(address)
         ));
         self.ft.internal_withdraw_eth_from_aurora(address, amount);
+        Self::update_evm_balances(0, amount);
+        ConnectorEvent::EvmBurn { address, amount }.emit();
     }
 
     /// Withdraw nETH from NEAR accounts
-    /// NOTE: it should be without any log data
     pub fn withdraw_eth_from_near(&mut self) {
         self.assert_not_paused(PAUSE_WITHDRAW);
 
         sdk::assert_one_yocto();
+        Self::assert_predecessor_not_frozen();
         let args = WithdrawCallArgs::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        let fee = args.fee.unwrap_or(0);
+        assert!(
+            fee == 0 || args.amount > fee,
+            "{}",
+            ERR_NOT_ENOUGH_BALANCE_FOR_FEE,
+        );
+        let limits = Self::get_bridge_limits();
+        self.assert_supply_drift_within(&limits);
         let res = WithdrawResult {
             recipient_id: args.recipient_address,
             amount: args.amount,
             eth_custodian_address: self.contract.eth_custodian_address,
-        }
-        .try_to_vec()
-        .unwrap();
+        };
         // Burn tokens to recipient
         let predecessor_account_id = AccountId::try_from(sdk::predecessor_account_id()).unwrap();
         self.ft
             .internal_withdraw_eth_from_near(&predecessor_account_id, args.amount);
         // Save new contract data
         self.save_ft_contract();
-        sdk::return_output(&res[..]);
+        let mut bridged = Self::get_bridged_amounts();
+        bridged.withdrawn = bridged.withdrawn.saturating_add(args.amount);
+        Self::save_bridged_amounts(&bridged);
+        FtEvent::Burn {
+            owner_id: predecessor_account_id.as_ref(),
+            amount: args.amount,
+            memo: None,
+        }
+        .emit();
+        ConnectorEvent::WithdrawalRequested {
+            sender_id: predecessor_account_id.as_ref(),
+            recipient_address: args.recipient_address,
+            amount: args.amount,
+            fee,
+            eth_custodian_address: self.contract.eth_custodian_address,
+        }
+        .emit();
+
+        // The withdrawals from Aurora are made by the current account, on behalf
+        // of the EVM sender given by the exit precompile
+        let refund = match args.sender {
+            Some(sender) if predecessor_account_id.as_bytes() == &sdk::current_account_id()[..] => {
+                WithdrawRefund::Aurora(sender)
+            }
+            _ => WithdrawRefund::Near(predecessor_account_id),
+        };
+        if Self::use_withdraw_caps(&limits, &refund, &args) {
+            sdk::return_output(&Self::withdraw_output(res, fee));
+        } else {
+            // Withdrawals over the caps are delayed, which leaves time to pause them
+            let delay = limits.queue_delay_blocks.expect(ERR_WITHDRAW_CAP_EXCEEDED);
+            self.queue_withdrawal(res, fee, refund, delay);
+        }
+    }
+
+    /// Serializes the withdraw result, in the legacy layout unless a fee is paid
+    fn withdraw_output(result: WithdrawResult, fee: Balance) -> Vec<u8> {
+        if fee == 0 {
+            result.try_to_vec().unwrap()
+        } else {
+            WithdrawWithFeeResult { result, fee }.try_to_vec().unwrap()
+        }
+    }
+
+    /// Records the withdrawal in the usage of the rolling window, unless it is
+    /// over the caps
+    fn use_withdraw_caps(
+        limits: &BridgeLimits,
+        refund: &WithdrawRefund,
+        args: &WithdrawCallArgs,
+    ) -> bool {
+        if limits.period_blocks == 0 {
+            return true;
+        }
+        let (source, caps, account) = match refund {
+            WithdrawRefund::Near(account_id) => (
+                WithdrawSource::Near,
+                &limits.withdraw_from_near,
+                account_id.as_bytes(),
+            ),
+            WithdrawRefund::Aurora(sender) => (
+                WithdrawSource::Aurora,
+                &limits.withdraw_from_aurora,
+                &sender[..],
+            ),
+        };
+
+        let block_index = sdk::block_index();
+        let bucket_blocks = (limits.period_blocks / WITHDRAW_USAGE_BUCKETS).max(1);
+        let mut usages = Vec::with_capacity(2);
+        for (key, cap) in crate::prelude::vec![
+            (Self::withdraw_usage_key(source, &[]), caps.global),
+            (Self::withdraw_usage_key(source, account), caps.per_account),
+        ] {
+            let mut usage = Self::get_withdraw_usage(&key, block_index, limits.period_blocks);
+            let used = usage.iter().fold(args.amount, |used, (_, amount)| {
+                used.saturating_add(*amount)
+            });
+            if cap.map_or(false, |cap| used > cap) {
+                return false;
+            }
+            // The withdrawals of a bucket are counted until the last of them
+            // leaves the window, which may only overestimate the usage
+            match usage.last_mut() {
+                Some((block, amount)) if *block / bucket_blocks == block_index / bucket_blocks => {
+                    *block = block_index;
+                    *amount = amount.saturating_add(args.amount);
+                }
+                _ => usage.push((block_index, args.amount)),
+            }
+            usages.push((key, usage));
+        }
+        for (key, usage) in usages {
+            sdk::save_contract(&key, &usage);
+        }
+        true
+    }
+
+    /// Key of the usage of the global caps if `account` is empty, or of the caps
+    /// of `account` otherwise
+    fn withdraw_usage_key(source: WithdrawSource, account: &[u8]) -> Vec<u8> {
+        let mut key = Self::get_contract_key(&EthConnectorStorageId::WithdrawUsage);
+        key.push(source as u8);
+        key.extend_from_slice(account);
+        key
+    }
+
+    /// Returns the amounts withdrawn in the rolling window ending at `block_index`,
+    /// with the last block they were withdrawn at
+    fn get_withdraw_usage(key: &[u8], block_index: u64, period_blocks: u64) -> Vec<(u64, Balance)> {
+        let mut usage = sdk::read_storage(key)
+            .and_then(|data| Vec::<(u64, Balance)>::try_from_slice(&data).ok())
+            .unwrap_or_default();
+        usage.retain(|(block, _)| block.saturating_add(period_blocks) > block_index);
+        usage
+    }
+
+    fn pending_withdrawal_key(id: u64) -> Vec<u8> {
+        let mut key = Self::get_contract_key(&EthConnectorStorageId::PendingWithdrawal);
+        key.extend_from_slice(&id.to_le_bytes());
+        key
+    }
+
+    /// Queues the withdrawal, and returns its id
+    fn queue_withdrawal(
+        &self,
+        result: WithdrawResult,
+        fee: Balance,
+        refund: WithdrawRefund,
+        delay: u64,
+    ) {
+        let nonce_key = Self::get_contract_key(&EthConnectorStorageId::PendingWithdrawalNonce);
+        let id = sdk::read_u64(&nonce_key).unwrap_or(0);
+        sdk::write_storage(&nonce_key, &(id + 1).to_le_bytes());
+
+        let release_height = sdk::block_index().saturating_add(delay);
+        sdk::log!(&format!(
+            "Withdrawal {} is queued until block {}",
+            id, release_height
+        ));
+        sdk::save_contract(
+            &Self::pending_withdrawal_key(id),
+            &PendingWithdrawal {
+                result,
+                release_height,
+                fee,
+                refund,
+            },
+        );
+        sdk::return_output(&id.try_to_vec().unwrap());
+    }
+
+    /// Releases a queued withdrawal once its delay is over, returning its result
+    pub fn release_withdrawal(&mut self, args: ReleaseWithdrawalArgs) {
+        self.assert_not_paused(PAUSE_WITHDRAW);
+        self.assert_supply_drift_within(&Self::get_bridge_limits());
+
+        let key = Self::pending_withdrawal_key(args.id);
+        let pending = sdk::read_storage(&key)
+            .and_then(|data| PendingWithdrawal::try_from_slice(&data).ok())
+            .expect("ERR_PENDING_WITHDRAWAL_NOT_FOUND");
+        assert!(
+            sdk::block_index() >= pending.release_height,
+            "ERR_WITHDRAWAL_NOT_RELEASED"
+        );
+        sdk::remove_storage(&key);
+        sdk::return_output(&Self::withdraw_output(pending.result, pending.fee));
+    }
+
+    /// Cancels a queued withdrawal, refunding the account which made it
+    pub fn cancel_withdrawal(&mut self, args: CancelWithdrawalArgs) {
+        let key = Self::pending_withdrawal_key(args.id);
+        let pending = sdk::read_storage(&key)
+            .and_then(|data| PendingWithdrawal::try_from_slice(&data).ok())
+            .expect("ERR_PENDING_WITHDRAWAL_NOT_FOUND");
+        sdk::remove_storage(&key);
+
+        let amount = pending.result.amount;
+        let refund_id = match &pending.refund {
+            WithdrawRefund::Near(account_id) => {
+                self.mint_eth_on_near(account_id.clone(), amount);
+                account_id.to_string()
+            }
+            WithdrawRefund::Aurora(sender) => {
+                // The withdrawals from Aurora burned the tokens of the current account
+                let current_account_id = AccountId::try_from(sdk::current_account_id()).unwrap();
+                self.mint_eth_on_near(current_account_id, amount);
+                self.mint_eth_on_aurora(*sender, amount);
+                hex::encode(sender)
+            }
+        };
+        self.save_ft_contract();
+        let mut bridged = Self::get_bridged_amounts();
+        bridged.withdrawn = bridged.withdrawn.saturating_sub(amount);
+        Self::save_bridged_amounts(&bridged);
+        ConnectorEvent::WithdrawalCancelled {
+            id: args.id,
+            refund_id: &refund_id,
+            amount,
+        }
+        .emit();
+    }
+
+    /// Keeps the sum of the EVM balances up to date with the ETH minted and
+    /// burned on Aurora by the bridge. The transfers inside the EVM don't change
+    /// the sum, so they don't need to be tracked.
+    fn update_evm_balances(minted: Balance, burned: Balance) {
+        let evm_balances = Self::get_evm_balances()
+            .saturating_add(U256::from(minted))
+            .saturating_sub(U256::from(burned));
+        Self::save_evm_balances(evm_balances);
+    }
+
+    fn save_evm_balances(evm_balances: U256) {
+        sdk::write_storage(
+            &Self::get_contract_key(&EthConnectorStorageId::EvmBalances),
+            &Wei::new(evm_balances).to_bytes(),
+        );
+    }
+
+    /// Returns the sum of the EVM balances, as last reported to `reconcile_supply`
+    /// and updated with the ETH minted and burned by the bridge since
+    pub fn get_evm_balances() -> U256 {
+        sdk::read_storage(&Self::get_contract_key(&EthConnectorStorageId::EvmBalances))
+            .map(|value| U256::from_big_endian(&value))
+            .unwrap_or_else(U256::zero)
+    }
+
+    fn get_bridged_amounts() -> BridgedAmounts {
+        sdk::read_storage(&Self::get_contract_key(
+            &EthConnectorStorageId::BridgedAmounts,
+        ))
+        .map(|data| BridgedAmounts::try_from_slice(&data).expect(ERR_FAILED_PARSE))
+        .unwrap_or_default()
+    }
+
+    fn save_bridged_amounts(bridged: &BridgedAmounts) {
+        sdk::save_contract(
+            &Self::get_contract_key(&EthConnectorStorageId::BridgedAmounts),
+            bridged,
+        );
+    }
+
+    /// Checks the supply against the EVM balances and the bridged amounts
+    pub fn get_supply_audit(&self) -> SupplyAudit {
+        let evm_balances = Self::get_evm_balances();
+        let aurora_supply = U256::from(self.ft.total_eth_supply_on_aurora);
+        let evm_drift = if evm_balances > aurora_supply {
+            evm_balances - aurora_supply
+        } else {
+            aurora_supply - evm_balances
+        };
+
+        let bridged = Self::get_bridged_amounts();
+        let locked = bridged
+            .deposited
+            .saturating_sub(bridged.withdrawn)
+            .saturating_sub(bridged.burned);
+        let near_supply = self.ft.total_eth_supply_on_near;
+
+        SupplyAudit {
+            total_eth_supply_on_near: near_supply,
+            total_eth_supply_on_aurora: self.ft.total_eth_supply_on_aurora,
+            evm_balances: crate::prelude::u256_to_arr(&evm_balances),
+            deposited: bridged.deposited,
+            withdrawn: bridged.withdrawn,
+            burned: bridged.burned,
+            evm_drift: if evm_drift > U256::from(u128::MAX) {
+                u128::MAX
+            } else {
+                evm_drift.as_u128()
+            },
+            bridge_drift: if near_supply > locked {
+                near_supply - locked
+            } else {
+                locked - near_supply
+            },
+        }
+    }
+
+    fn assert_supply_drift_within(&self, limits: &BridgeLimits) {
+        if let Some(max_supply_drift) = limits.max_supply_drift {
+            let audit = self.get_supply_audit();
+            assert!(
+                audit.evm_drift <= max_supply_drift && audit.bridge_drift <= max_supply_drift,
+                "{}",
+                ERR_SUPPLY_DRIFT_ABOVE_TOLERANCE
+            );
+        }
+    }
+
+    /// Records the sum of the EVM balances recomputed off-chain from the state,
+    /// and returns the audit against it, so any drift is reported, not erased.
+    ///
+    /// The current supply on NEAR is taken as the baseline of the bridged amounts
+    /// only if they were never recorded, i.e. once for a connector deployed before
+    /// the audit was tracked, which must be reconciled before its first deposit
+    /// or withdrawal.
+    pub fn reconcile_supply(&self, args: ReconcileSupplyArgs) -> SupplyAudit {
+        Self::save_evm_balances(U256::from_big_endian(&args.evm_balances));
+        let bridged_amounts_key = Self::get_contract_key(&EthConnectorStorageId::BridgedAmounts);
+        if !sdk::storage_has_key(&bridged_amounts_key) {
+            Self::save_bridged_amounts(&BridgedAmounts {
+                deposited: self.ft.total_eth_supply_on_near,
+                ..Default::default()
+            });
+        }
+
+        let audit = self.get_supply_audit();
+        sdk::log!(&format!(
+            "Supply reconciled, EVM drift: {}, bridge drift: {}",
+            audit.evm_drift, audit.bridge_drift
+        ));
+        audit
+    }
+
+    /// Returns the bridge limits
+    pub fn get_bridge_limits() -> BridgeLimits {
+        sdk::read_storage(&Self::get_contract_key(
+            &EthConnectorStorageId::BridgeLimits,
+        ))
+        .map(|data| BridgeLimits::try_from_slice(&data).expect(ERR_FAILED_PARSE))
+        .unwrap_or_default()
+    }
+
+    /// Sets the bridge limits. The usage of the current period is kept.
+    pub fn set_bridge_limits(args: BridgeLimits) {
+        sdk::save_contract(
+            &Self::get_contract_key(&EthConnectorStorageId::BridgeLimits),
+            &args,
+        );
     }
 
     /// Returns total ETH supply on NEAR (nETH as NEP-141 token)
     pub fn ft_total_eth_supply_on_near(&self) {
         let total_supply = self.ft.ft_total_eth_supply_on_near();
         sdk::log!(&format!("Total ETH supply on NEAR: {}", total_supply));
-        sdk::return_output(format!("\"{}\"", total_supply.to_string()).as_bytes());
+        sdk::return_output(&JsonValue::from(total_supply).to_bytes());
     }
 
     /// Returns total ETH supply on Aurora (ETH in Aurora EVM)
     pub fn ft_total_eth_supply_on_aurora(&self) {
         let total_supply = self.ft.ft_total_eth_supply_on_aurora();
         sdk::log!(&format!("Total ETH supply on Aurora: {}", total_supply));
-        sdk::return_output(format!("\"{}\"", total_supply.to_string()).as_bytes());
+        sdk::return_output(&JsonValue::from(total_supply).to_bytes());
     }
 
     /// Return balance of nETH (ETH on Near)
     pub fn ft_balance_of(&self) {
-        let args = BalanceOfCallArgs::from(
+        let args = BalanceOfCallArgs::try_from(
             parse_json(&sdk::read_input()).expect_utf8(ERR_FAILED_PARSE.as_bytes()),
-        );
+        )
+        .sdk_unwrap();
 
         let balance = self.ft.ft_balance_of(&args.account_id);
         sdk::log!(&format!(
@@ -438,7 +1169,7 @@ impl EthConnectorContract {
             args.account_id, balance
         ));
 
-        sdk::return_output(format!("\"{}\"", balance.to_string()).as_bytes());
+        sdk::return_output(&JsonValue::from(balance).to_bytes());
     }
 
     /// Return balance of ETH (ETH in Aurora EVM)
@@ -453,15 +1184,17 @@ impl EthConnectorContract {
             hex::encode(args.address),
             balance
         ));
-        sdk::return_output(format!("\"{}\"", balance.to_string()).as_bytes());
+        sdk::return_output(&JsonValue::from(balance).to_bytes());
     }
 
     /// Transfer between NEAR accounts
     pub fn ft_transfer(&mut self) {
         sdk::assert_one_yocto();
-        let args = TransferCallArgs::from(
+        Self::assert_predecessor_not_frozen();
+        let args = TransferCallArgs::try_from(
             parse_json(&sdk::read_input()).expect_utf8(ERR_FAILED_PARSE.as_bytes()),
-        );
+        )
+        .sdk_unwrap();
         self.ft
             .ft_transfer(&args.receiver_id, args.amount, &args.memo);
         self.save_ft_contract();
@@ -487,23 +1220,24 @@ impl EthConnectorContract {
         ));
         // `ft_resolve_transfer` can change `total_supply` so we should save the contract
         self.save_ft_contract();
-        sdk::return_output(format!("\"{}\"", amount.to_string()).as_bytes());
+        sdk::return_output(&JsonValue::from(amount).to_bytes());
     }
 
     /// FT transfer call from sender account (invoker account) to receiver
     /// We starting early checking for message data to avoid `ft_on_transfer` call panics
     /// But we don't check relayer exists. If relayer doesn't exist we simply not mint/burn the amount of the fee
     pub fn ft_transfer_call(&mut self, args: TransferCallCallArgs) {
+        Self::assert_predecessor_not_frozen();
         sdk::log!(&format!(
             "Transfer call to {} amount {}",
             args.receiver_id, args.amount,
         ));
         // Verify message data before `ft_on_transfer` call to avoid verification panics
         if args.receiver_id.as_bytes() == &sdk::current_account_id()[..] {
-            let message_data = self.parse_on_transfer_message(&args.msg);
+            let message_data = self.parse_on_transfer_message(&args.msg).sdk_unwrap();
             // Check is transfer amount > fee
             assert!(
-                args.amount > message_data.fee.as_u128(),
+                U256::from(args.amount) > message_data.fee,
                 "{}",
                 ERR_NOT_ENOUGH_BALANCE_FOR_FEE,
             );
@@ -530,9 +1264,10 @@ impl EthConnectorContract {
 
     /// FT storage deposit logic
     pub fn storage_deposit(&mut self) {
-        let args = StorageDepositCallArgs::from(
+        let args = StorageDepositCallArgs::try_from(
             parse_json(&sdk::read_input()).expect_utf8(ERR_FAILED_PARSE.as_bytes()),
-        );
+        )
+        .sdk_unwrap();
 
         let res = self
             .ft
@@ -544,41 +1279,112 @@ impl EthConnectorContract {
     /// FT storage withdraw
     pub fn storage_withdraw(&mut self) {
         sdk::assert_one_yocto();
-        let args = StorageWithdrawCallArgs::from(
+        Self::assert_predecessor_not_frozen();
+        let args = StorageWithdrawCallArgs::try_from(
             parse_json(&sdk::read_input()).expect_utf8(ERR_FAILED_PARSE.as_bytes()),
-        );
+        )
+        .sdk_unwrap();
         let res = self.ft.storage_withdraw(args.amount);
         self.save_ft_contract();
         sdk::return_output(&res.to_json_bytes());
     }
 
+    /// FT storage unregister. With `force`, the remaining balance of the account
+    /// is burned.
+    pub fn storage_unregister(&mut self) {
+        Self::assert_predecessor_not_frozen();
+        let args = StorageUnregisterCallArgs::try_from(
+            parse_json(&sdk::read_input()).expect_utf8(ERR_FAILED_PARSE.as_bytes()),
+        )
+        .sdk_unwrap();
+        // The nETH of the current account backs the ETH on Aurora
+        assert!(
+            sdk::predecessor_account_id() != sdk::current_account_id(),
+            "ERR_CANNOT_UNREGISTER_CURRENT_ACCOUNT"
+        );
+        let unregistered = match self.ft.internal_storage_unregister(args.force) {
+            Some((account_id, balance)) => {
+                self.save_ft_contract();
+                if balance > 0 {
+                    let mut bridged = Self::get_bridged_amounts();
+                    bridged.burned = bridged.burned.saturating_add(balance);
+                    Self::save_bridged_amounts(&bridged);
+                    FtEvent::Burn {
+                        owner_id: account_id.as_ref(),
+                        amount: balance,
+                        memo: Some("storage_unregister"),
+                    }
+                    .emit();
+                }
+                true
+            }
+            None => false,
+        };
+        sdk::return_output(&JsonValue::from(unregistered).to_bytes());
+    }
+
+    /// Returns the NEP-145 storage balance bounds
+    pub fn storage_balance_bounds(&self) {
+        sdk::return_output(&self.ft.storage_balance_bounds().to_json_bytes());
+    }
+
     /// Get balance of storage
     pub fn storage_balance_of(&self) {
-        let args = StorageBalanceOfCallArgs::from(
+        let args = StorageBalanceOfCallArgs::try_from(
             parse_json(&sdk::read_input()).expect_utf8(ERR_FAILED_PARSE.as_bytes()),
-        );
-        sdk::return_output(&self.ft.storage_balance_of(&args.account_id).to_json_bytes());
+        )
+        .sdk_unwrap();
+        let storage_balance = match self.ft.storage_balance_of(&args.account_id) {
+            Some(storage_balance) => storage_balance.to_json_bytes(),
+            // Unregistered accounts have no storage balance
+            None => JsonValue::Null.to_bytes(),
+        };
+        sdk::return_output(&storage_balance);
     }
 
     /// ft_on_transfer callback function
-    pub fn ft_on_transfer(&mut self, engine: &Engine, args: &NEP141FtOnTransferArgs) {
+    pub fn ft_on_transfer(&mut self, engine: &mut Engine, args: &NEP141FtOnTransferArgs) {
         sdk::log!("Call ft_on_transfer");
-        // Parse message with specific rules
-        let message_data = self.parse_on_transfer_message(&args.msg);
+        // Parse message with specific rules, refunding the deposit if the
+        // versioned message is invalid
+        let output_on_fail = JsonValue::from(args.amount).to_bytes();
+        let message_data =
+            unwrap_res_or_finish!(self.parse_on_transfer_message(&args.msg), &output_on_fail);
 
         // Special case when predecessor_account_id is current_account_id
         let fee = message_data.fee.as_u128();
-        // Mint fee to relayer
-        let relayer = engine.get_relayer(message_data.relayer.as_bytes());
-        match (fee, relayer) {
+        self.mint_deposit_on_aurora(
+            engine,
+            message_data.recipient,
+            args.amount,
+            fee,
+            &message_data.relayer,
+        );
+        self.save_ft_contract();
+        if let Some(call) = &message_data.call {
+            let sender = near_account_to_evm_address(args.sender_id.as_bytes());
+            engine.deposit_call(sender, call);
+        }
+        sdk::return_output(&JsonValue::from(0u128).to_bytes());
+    }
+
+    /// Mint the deposit on Aurora, with the fee minted to the relayer if it is
+    /// registered
+    fn mint_deposit_on_aurora(
+        &mut self,
+        engine: &Engine,
+        recipient: EthAddress,
+        amount: Balance,
+        fee: Balance,
+        relayer: &AccountId,
+    ) {
+        match (fee, engine.get_relayer(relayer.as_bytes())) {
             (fee, Some(H160(evm_relayer_address))) if fee > 0 => {
-                self.mint_eth_on_aurora(message_data.recipient, args.amount - fee);
+                self.mint_eth_on_aurora(recipient, amount - fee);
                 self.mint_eth_on_aurora(evm_relayer_address, fee);
             }
-            _ => self.mint_eth_on_aurora(message_data.recipient, args.amount),
+            _ => self.mint_eth_on_aurora(recipient, amount),
         }
-        self.save_ft_contract();
-        sdk::return_output("\"0\"".as_bytes());
     }
 
     /// Get accounts counter for statistics.
@@ -595,26 +1401,151 @@ impl EthConnectorContract {
         );
     }
 
-    /// Generate key for used events from Prood
-    fn used_event_key(&self, key: &str) -> Vec<u8> {
-        let mut v = Self::get_contract_key(&EthConnectorStorageId::UsedEvent).to_vec();
-        v.extend_from_slice(key.as_bytes());
+    /// Generate key for used proofs from the hash of the Proof
+    fn used_proof_key(key: &RawH256) -> Vec<u8> {
+        let mut v = Self::get_contract_key(&EthConnectorStorageId::UsedProofs);
+        v.extend_from_slice(key);
         v
     }
 
-    /// Save already used event proof as hash key
-    fn save_used_event(&self, key: &str) {
-        sdk::save_contract(&self.used_event_key(key), &0u8);
+    /// Generate key for used events recorded before they were stored by hash
+    fn legacy_used_event_key(key: &RawH256) -> Vec<u8> {
+        Self::used_event_key(&legacy_proof_key(key))
+    }
+
+    /// Generate key for used events from their legacy proof key
+    fn used_event_key(proof_key: &str) -> Vec<u8> {
+        let mut v = Self::get_contract_key(&EthConnectorStorageId::UsedEvent);
+        v.extend_from_slice(proof_key.as_bytes());
+        v
     }
 
-    /// Check is event of proof already used
-    fn check_used_event(&self, key: &str) -> bool {
-        sdk::storage_has_key(&self.used_event_key(key))
+    /// Check is proof already used. The legacy keys are only looked up until
+    /// the used proofs are migrated.
+    fn check_used_proof(&self, key: &RawH256) -> bool {
+        sdk::storage_has_key(&Self::used_proof_key(key))
+            || (!Self::are_used_proofs_migrated()
+                && sdk::storage_has_key(&Self::legacy_used_event_key(key)))
+    }
+
+    fn are_used_proofs_migrated() -> bool {
+        sdk::storage_has_key(&Self::get_contract_key(
+            &EthConnectorStorageId::UsedProofsMigrated,
+        ))
     }
 
     /// Checks whether the provided proof was already used
     pub fn is_used_proof(&self, proof: Proof) -> bool {
-        self.check_used_event(&proof.get_key())
+        self.check_used_proof(&proof.get_key())
+    }
+
+    /// Returns the deposit which consumed the proof with the given hash. The
+    /// proofs used before their migration have no deposit recorded.
+    pub fn get_used_proof(key: &RawH256) -> Option<UsedProof> {
+        sdk::read_storage(&Self::used_proof_key(key))
+            .map(|data| UsedProof::try_from_slice(&data).expect(ERR_FAILED_PARSE))
+    }
+
+    /// Stores used proofs recorded with their legacy key by their hash, with
+    /// the deposits which consumed them, and removes their legacy key. The hash
+    /// is derived from the proof, which must be valid and of the block of the
+    /// deposit, so that a proof colliding with a legacy key (see
+    /// `legacy_proof_key`) can't be migrated in place of the one which was used.
+    pub fn migrate_used_proofs(args: Vec<MigrateUsedProofArgs>) {
+        assert!(!Self::are_used_proofs_migrated(), ERR_USED_PROOFS_MIGRATED);
+        for arg in args {
+            let key = arg.proof.get_key();
+            let legacy_key = Self::legacy_used_event_key(&key);
+            assert!(sdk::storage_has_key(&legacy_key), "ERR_PROOF_NOT_FOUND");
+            let verified = arg.proof.verify().sdk_unwrap();
+            assert_eq!(
+                verified.block_number, arg.used_proof.block_number,
+                "ERR_INVALID_USED_PROOF"
+            );
+
+            let key = Self::used_proof_key(&key);
+            assert!(!sdk::storage_has_key(&key), "ERR_PROOF_EXIST");
+            sdk::save_contract(&key, &arg.used_proof);
+            sdk::remove_storage(&legacy_key);
+        }
+    }
+
+    /// Stops looking up the legacy keys of the used proofs, once they are all
+    /// migrated and no deposit scheduled before the proofs were keyed by their
+    /// hash is left to finish. Such deposits are rejected from then on.
+    pub fn finish_used_proofs_migration() {
+        assert!(!Self::are_used_proofs_migrated(), ERR_USED_PROOFS_MIGRATED);
+        sdk::write_storage(
+            &Self::get_contract_key(&EthConnectorStorageId::UsedProofsMigrated),
+            &[1],
+        );
+    }
+
+    fn freeze_key(target: &FreezeTarget) -> Vec<u8> {
+        match target {
+            FreezeTarget::NearAccount(account_id) => {
+                eth_connector_key(EthConnectorStorageId::FrozenAccounts, account_id.as_bytes())
+            }
+            FreezeTarget::EvmAddress(address) => {
+                eth_connector_key(EthConnectorStorageId::FrozenEvmAddresses, &address[..])
+            }
+        }
+    }
+
+    /// Returns the freeze of the NEAR account or EVM address, if frozen
+    pub fn get_freeze(target: &FreezeTarget) -> Option<Freeze> {
+        sdk::read_storage(&Self::freeze_key(target))
+            .map(|data| Freeze::try_from_slice(&data).expect(ERR_FAILED_PARSE))
+    }
+
+    /// Returns whether the NEAR account or EVM address is frozen
+    pub fn is_frozen(target: &FreezeTarget) -> bool {
+        sdk::storage_has_key(&Self::freeze_key(target))
+    }
+
+    /// Freezes a NEAR account or EVM address. Unlike the paused flags, it
+    /// applies to the owner too.
+    pub fn freeze(args: FreezeArgs) {
+        let freeze = Freeze {
+            reason: args.reason,
+            frozen_at: sdk::block_index(),
+        };
+        sdk::save_contract(&Self::freeze_key(&args.target), &freeze);
+        ConnectorEvent::AccountFrozen {
+            target: &Self::freeze_target_id(&args.target),
+            reason: args.reason,
+        }
+        .emit();
+    }
+
+    /// Unfreezes a frozen NEAR account or EVM address
+    pub fn unfreeze(target: FreezeTarget) {
+        let key = Self::freeze_key(&target);
+        assert!(sdk::storage_has_key(&key), "ERR_NOT_FROZEN");
+        sdk::remove_storage(&key);
+        ConnectorEvent::AccountUnfrozen {
+            target: &Self::freeze_target_id(&target),
+        }
+        .emit();
+    }
+
+    fn freeze_target_id(target: &FreezeTarget) -> String {
+        match target {
+            FreezeTarget::NearAccount(account_id) => account_id.to_string(),
+            FreezeTarget::EvmAddress(address) => hex::encode(address),
+        }
+    }
+
+    /// Panics with "ERR_ACCOUNT_FROZEN" if the predecessor account is frozen.
+    /// The transactions of frozen EVM addresses are rejected by the engine, and
+    /// their exits by the exit precompiles.
+    fn assert_predecessor_not_frozen() {
+        let predecessor_account_id = AccountId::try_from(sdk::predecessor_account_id()).unwrap();
+        assert!(
+            !Self::is_frozen(&FreezeTarget::NearAccount(predecessor_account_id)),
+            "{}",
+            ERR_ACCOUNT_FROZEN
+        );
     }
 
     /// Get Eth connector paused flags
@@ -627,6 +1558,107 @@ impl EthConnectorContract {
         self.set_paused(args.paused_mask);
     }
 
+    /// Returns the custodians whose deposits are accepted
+    pub fn get_eth_custodians(&self) -> Vec<EthCustodian> {
+        match sdk::read_storage(&Self::get_contract_key(
+            &EthConnectorStorageId::EthCustodians,
+        )) {
+            Some(data) => Vec::try_from_slice(&data).expect(ERR_FAILED_PARSE),
+            // Only the initial custodian, before the first rotation
+            None => crate::prelude::vec![EthCustodian {
+                address: self.contract.eth_custodian_address,
+                activation_height: 0,
+                deprecation_height: None,
+            }],
+        }
+    }
+
+    /// Makes the given custodian the active one, and deprecates the previous ones
+    /// at `deprecation_height`, so that their in-flight deposits are still accepted.
+    /// The deprecation can't leave a gap before the activation of the new custodian,
+    /// nor be below the latest block of the deposits, which would reject deposits
+    /// of blocks Ethereum already reached.
+    pub fn rotate_eth_custodian(&mut self, args: RotateEthCustodianArgs) {
+        let address = validate_eth_address(args.eth_custodian_address).sdk_unwrap();
+        assert!(
+            args.deprecation_height >= args.activation_height,
+            "{}",
+            ERR_DEPRECATION_BELOW_ACTIVATION
+        );
+        if let Some(last_deposit_block) = Self::get_last_deposit_block() {
+            assert!(
+                args.deprecation_height > last_deposit_block,
+                "{}",
+                ERR_DEPRECATION_BELOW_LAST_DEPOSIT
+            );
+        }
+        let mut custodians = self.get_eth_custodians();
+        for custodian in custodians
+            .iter_mut()
+            .filter(|custodian| custodian.deprecation_height.is_none())
+        {
+            custodian.deprecation_height = Some(args.deprecation_height);
+        }
+        custodians.push(EthCustodian {
+            address,
+            activation_height: args.activation_height,
+            deprecation_height: None,
+        });
+        sdk::log!(&format!(
+            "Rotate custodian to {} from height {}",
+            hex::encode(address),
+            args.activation_height,
+        ));
+
+        self.contract.eth_custodian_address = address;
+        sdk::save_contract(
+            &Self::get_contract_key(&EthConnectorStorageId::Contract),
+            &self.contract,
+        );
+        sdk::save_contract(
+            &Self::get_contract_key(&EthConnectorStorageId::EthCustodians),
+            &custodians,
+        );
+    }
+
+    fn deposit_event_schema_key(eth_custodian_address: &EthAddress, topic0: &RawH256) -> Vec<u8> {
+        let mut key = Self::get_contract_key(&EthConnectorStorageId::DepositEventSchemas);
+        key.extend_from_slice(eth_custodian_address);
+        key.extend_from_slice(topic0);
+        key
+    }
+
+    /// Returns the schema of the deposit events of the custodian with the given
+    /// topic0, if accepted. A schema which wasn't set for the custodian is
+    /// accepted if it's accepted by default, i.e. only the basic schema.
+    pub fn get_deposit_event_schema(
+        eth_custodian_address: EthAddress,
+        topic0: RawH256,
+    ) -> Option<DepositedEventSchema> {
+        let schema = DepositedEventSchema::from_topic0(&topic0)?;
+        let accepted = sdk::read_storage(&Self::deposit_event_schema_key(
+            &eth_custodian_address,
+            &topic0,
+        ))
+        .map(|data| bool::try_from_slice(&data).expect(ERR_FAILED_PARSE))
+        .unwrap_or_else(|| schema.is_accepted_by_default());
+        if accepted {
+            Some(schema)
+        } else {
+            None
+        }
+    }
+
+    /// Accepts or rejects a schema of the deposit events of a custodian, e.g.
+    /// once it's upgraded on Ethereum
+    pub fn set_deposit_event_schema(args: SetDepositEventSchemaArgs) {
+        let address = validate_eth_address(args.eth_custodian_address).sdk_unwrap();
+        sdk::save_contract(
+            &Self::deposit_event_schema_key(&address, &args.schema.topic0()),
+            &args.accepted,
+        );
+    }
+
     /// Return metdata
     pub fn get_metadata() -> Option<FungibleTokenMetadata> {
         sdk::read_storage(&Self::get_contract_key(
@@ -634,6 +1666,37 @@ impl EthConnectorContract {
         ))
         .and_then(|data| FungibleTokenMetadata::try_from_slice(&data).ok())
     }
+
+    /// Sets the metadata of the nETH token, and increments its version
+    pub fn set_ft_metadata(metadata: FungibleTokenMetadata) {
+        metadata.validate().sdk_unwrap();
+        sdk::save_contract(
+            &Self::get_contract_key(&EthConnectorStorageId::FungibleTokenMetadata),
+            &metadata,
+        );
+
+        let version = Self::get_ft_metadata_version() + 1;
+        sdk::write_storage(
+            &Self::get_contract_key(&EthConnectorStorageId::FungibleTokenMetadataVersion),
+            &version.to_le_bytes(),
+        );
+        ConnectorEvent::FtMetadataUpdated {
+            version,
+            spec: &metadata.spec,
+            name: &metadata.name,
+            symbol: &metadata.symbol,
+            decimals: metadata.decimals,
+        }
+        .emit();
+    }
+
+    /// Returns the number of updates of the metadata by `set_ft_metadata`
+    pub fn get_ft_metadata_version() -> u64 {
+        sdk::read_u64(&Self::get_contract_key(
+            &EthConnectorStorageId::FungibleTokenMetadataVersion,
+        ))
+        .unwrap_or(0)
+    }
 }
 
 impl AdminControlled for EthConnectorContract {
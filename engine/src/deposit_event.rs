@@ -1,40 +1,162 @@
 use crate::log_entry::LogEntry;
-use crate::prelude::{vec, EthAddress, String, ToString, Vec, U256};
-use ethabi::{Event, EventParam, Hash, Log, ParamType, RawLog};
+use crate::prelude::{
+    format, vec, BorshDeserialize, BorshSerialize, EthAddress, RawH256, String, ToString, Vec, U256,
+};
+use ethabi::{Event, EventParam, Hash, Log, ParamType, RawLog, Token};
 
 pub const DEPOSITED_EVENT: &str = "Deposited";
 
 pub type EventParams = Vec<EventParam>;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositedEventError {
+    InvalidRlp,
+    UnknownSchema,
+    InvalidLog,
+    InvalidSender,
+    InvalidRecipient,
+    InvalidAmount,
+    InvalidFee,
+    InvalidAuroraRecipient,
+}
+
+impl AsRef<[u8]> for DepositedEventError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidRlp => b"ERR_DEPOSIT_EVENT_INVALID_RLP",
+            Self::UnknownSchema => b"ERR_DEPOSIT_EVENT_UNKNOWN_SCHEMA",
+            Self::InvalidLog => b"ERR_DEPOSIT_EVENT_INVALID_LOG",
+            Self::InvalidSender => b"ERR_DEPOSIT_EVENT_INVALID_SENDER",
+            Self::InvalidRecipient => b"ERR_DEPOSIT_EVENT_INVALID_RECIPIENT",
+            Self::InvalidAmount => b"ERR_DEPOSIT_EVENT_INVALID_AMOUNT",
+            Self::InvalidFee => b"ERR_DEPOSIT_EVENT_INVALID_FEE",
+            Self::InvalidAuroraRecipient => b"ERR_DEPOSIT_EVENT_INVALID_AURORA_RECIPIENT",
+        }
+    }
+}
+
+/// Schemas of the `Deposited` event of the custodian contract, which are told
+/// apart by their topic0.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepositedEventSchema {
+    /// `Deposited(address indexed sender, string recipient, uint256 amount, uint256 fee)`
+    Basic,
+    /// `Deposited(address indexed sender, string recipient, uint256 amount, uint256 fee, uint256 nonce)`
+    ///
+    /// The nonce is only part of the signature: the deposits are already
+    /// deduplicated by the key of their proof.
+    WithNonce,
+    /// `Deposited(address indexed sender, string recipient, uint256 amount, uint256 fee, address auroraRecipient)`
+    WithAuroraRecipient,
+}
+
+impl DepositedEventSchema {
+    pub const ALL: [Self; 3] = [Self::Basic, Self::WithNonce, Self::WithAuroraRecipient];
+
+    /// The schema of the events with the given topic0, if any
+    pub fn from_topic0(topic0: &RawH256) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .copied()
+            .find(|schema| &schema.topic0() == topic0)
+    }
+
+    /// Whether the schema is accepted for the custodians which didn't set it,
+    /// which is only the case of the schema of the original custodian contract
+    pub fn is_accepted_by_default(&self) -> bool {
+        matches!(self, Self::Basic)
+    }
+
+    pub fn event_params(&self) -> EventParams {
+        let mut params = vec![
+            event_param("sender", ParamType::Address, true),
+            event_param("recipient", ParamType::String, false),
+            event_param("amount", ParamType::Uint(256), false),
+            event_param("fee", ParamType::Uint(256), false),
+        ];
+        match self {
+            Self::Basic => {}
+            Self::WithNonce => params.push(event_param("nonce", ParamType::Uint(256), false)),
+            Self::WithAuroraRecipient => {
+                params.push(event_param("auroraRecipient", ParamType::Address, false))
+            }
+        }
+        params
+    }
+
+    pub fn event(&self) -> Event {
+        Event {
+            name: DEPOSITED_EVENT.to_string(),
+            inputs: self.event_params(),
+            anonymous: false,
+        }
+    }
+
+    /// The topic0 of the events of this schema, i.e. the hash of their signature
+    pub fn topic0(&self) -> RawH256 {
+        self.event().signature().0
+    }
+}
+
+fn event_param(name: &str, kind: ParamType, indexed: bool) -> EventParam {
+    EventParam {
+        name: name.to_string(),
+        kind,
+        indexed,
+    }
+}
+
 /// Ethereum event
 pub struct EthEvent {
     pub eth_custodian_address: EthAddress,
     pub log: Log,
 }
 
-#[allow(dead_code)]
 impl EthEvent {
-    /// Get Ethereum event from `log_entry_data`
-    pub fn fetch_log_entry_data(name: &str, params: EventParams, data: &[u8]) -> Self {
-        let event = Event {
-            name: name.to_string(),
-            inputs: params,
-            anonymous: false,
-        };
-        let log_entry: LogEntry = rlp::decode(data).expect("INVALID_RLP");
+    /// Get the Ethereum event from `log_entry_data`, with the schema accepted for
+    /// its address and topic0
+    pub fn fetch_log_entry_data<F>(
+        data: &[u8],
+        accepted_schema: F,
+    ) -> Result<(Self, DepositedEventSchema), DepositedEventError>
+    where
+        F: FnOnce(EthAddress, RawH256) -> Option<DepositedEventSchema>,
+    {
+        let log_entry: LogEntry = rlp::decode(data).map_err(|_| DepositedEventError::InvalidRlp)?;
         let eth_custodian_address = log_entry.address.0;
-        let topics = log_entry.topics.iter().map(|h| Hash::from(h.0)).collect();
+        let topic0 = log_entry
+            .topics
+            .first()
+            .ok_or(DepositedEventError::UnknownSchema)?
+            .0;
+        let schema = accepted_schema(eth_custodian_address, topic0)
+            .ok_or(DepositedEventError::UnknownSchema)?;
 
+        let topics = log_entry.topics.iter().map(|h| Hash::from(h.0)).collect();
         let raw_log = RawLog {
             topics,
             data: log_entry.data,
         };
-        let log = event.parse_log(raw_log).expect("Failed to parse event log");
+        let log = schema
+            .event()
+            .parse_log(raw_log)
+            .map_err(|_| DepositedEventError::InvalidLog)?;
 
-        Self {
-            eth_custodian_address,
-            log,
-        }
+        Ok((
+            Self {
+                eth_custodian_address,
+                log,
+            },
+            schema,
+        ))
+    }
+
+    fn param(&self, name: &str) -> Option<Token> {
+        self.log
+            .params
+            .iter()
+            .find(|param| param.name == name)
+            .map(|param| param.value.clone())
     }
 }
 
@@ -46,61 +168,162 @@ pub struct DepositedEvent {
     pub recipient: String,
     pub amount: U256,
     pub fee: U256,
+    /// Explicit recipient on Aurora, for the schemas with one
+    pub aurora_recipient: Option<EthAddress>,
 }
 
 impl DepositedEvent {
-    #[allow(dead_code)]
+    /// Parameters of the `Basic` schema
     pub fn event_params() -> EventParams {
-        vec![
-            EventParam {
-                name: "sender".to_string(),
-                kind: ParamType::Address,
-                indexed: true,
-            },
-            EventParam {
-                name: "recipient".to_string(),
-                kind: ParamType::String,
-                indexed: false,
-            },
-            EventParam {
-                name: "amount".to_string(),
-                kind: ParamType::Uint(256),
-                indexed: false,
-            },
-            EventParam {
-                name: "fee".to_string(),
-                kind: ParamType::Uint(256),
-                indexed: false,
-            },
-        ]
+        DepositedEventSchema::Basic.event_params()
     }
 
-    /// Parses raw Ethereum logs proof's entry data
-    pub fn from_log_entry_data(data: &[u8]) -> Self {
-        let event = EthEvent::fetch_log_entry_data(DEPOSITED_EVENT, Self::event_params(), data);
-        let sender = event.log.params[0]
-            .value
-            .clone()
-            .into_address()
-            .expect("INVALID_SENDER")
-            .0;
-        let recipient: String = event.log.params[1].value.clone().to_string();
-        let amount = event.log.params[2]
-            .value
-            .clone()
-            .into_uint()
-            .expect("INVALID_AMOUNT");
-        let fee = event.log.params[3]
-            .value
-            .clone()
-            .into_uint()
-            .expect("INVALID_FEE");
-        Self {
+    /// Parses raw Ethereum logs proof's entry data, if its schema is accepted
+    pub fn from_log_entry_data<F>(
+        data: &[u8],
+        accepted_schema: F,
+    ) -> Result<Self, DepositedEventError>
+    where
+        F: FnOnce(EthAddress, RawH256) -> Option<DepositedEventSchema>,
+    {
+        let (event, schema) = EthEvent::fetch_log_entry_data(data, accepted_schema)?;
+        let sender = match event.param("sender") {
+            Some(Token::Address(sender)) => sender.0,
+            _ => return Err(DepositedEventError::InvalidSender),
+        };
+        let recipient = match event.param("recipient") {
+            Some(Token::String(recipient)) => recipient,
+            _ => return Err(DepositedEventError::InvalidRecipient),
+        };
+        let amount = match event.param("amount") {
+            Some(Token::Uint(amount)) => amount,
+            _ => return Err(DepositedEventError::InvalidAmount),
+        };
+        let fee = match event.param("fee") {
+            Some(Token::Uint(fee)) => fee,
+            _ => return Err(DepositedEventError::InvalidFee),
+        };
+        let aurora_recipient = match (schema, event.param("auroraRecipient")) {
+            (DepositedEventSchema::WithAuroraRecipient, Some(Token::Address(address))) => {
+                Some(address.0)
+            }
+            (DepositedEventSchema::WithAuroraRecipient, _) => {
+                return Err(DepositedEventError::InvalidAuroraRecipient)
+            }
+            _ => None,
+        };
+        // The explicit Aurora recipient is given to the NEAR recipient as its message
+        let recipient = match aurora_recipient {
+            Some(_) if recipient.contains(':') => {
+                return Err(DepositedEventError::InvalidRecipient)
+            }
+            Some(address) => format!("{}:{}", recipient, hex::encode(address)),
+            None => recipient,
+        };
+
+        Ok(Self {
             eth_custodian_address: event.eth_custodian_address,
             sender,
             recipient,
             amount,
             fee,
-        }
+            aurora_recipient,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::{H160, H256};
+
+    const CUSTODIAN: EthAddress = [1u8; 20];
+
+    fn log_entry_data(schema: DepositedEventSchema, extra: Option<Token>) -> Vec<u8> {
+        let mut tokens = vec![
+            Token::String("aurora".to_string()),
+            Token::Uint(U256::from(100)),
+            Token::Uint(U256::from(10)),
+        ];
+        tokens.extend(extra);
+        let log_entry = LogEntry {
+            address: H160(CUSTODIAN),
+            topics: vec![H256(schema.topic0()), H256::from_low_u64_be(2)],
+            data: ethabi::encode(&tokens),
+        };
+        rlp::encode(&log_entry).to_vec()
+    }
+
+    fn accept_all(_: EthAddress, topic0: RawH256) -> Option<DepositedEventSchema> {
+        DepositedEventSchema::from_topic0(&topic0)
+    }
+
+    #[test]
+    fn test_decode_deposited_event_schemas() {
+        let data = log_entry_data(DepositedEventSchema::Basic, None);
+        let event = DepositedEvent::from_log_entry_data(&data, accept_all).unwrap();
+        assert_eq!(event.eth_custodian_address, CUSTODIAN);
+        assert_eq!(event.sender, H160::from_low_u64_be(2).0);
+        assert_eq!(event.recipient, "aurora");
+        assert_eq!(event.amount, U256::from(100));
+        assert_eq!(event.fee, U256::from(10));
+        assert_eq!(event.aurora_recipient, None);
+
+        let data = log_entry_data(
+            DepositedEventSchema::WithNonce,
+            Some(Token::Uint(U256::from(7))),
+        );
+        // The nonce is only decoded to parse the event, the proof key being
+        // what protects the deposits from replays
+        let event = DepositedEvent::from_log_entry_data(&data, accept_all).unwrap();
+        assert_eq!(event.amount, U256::from(100));
+        assert_eq!(event.aurora_recipient, None);
+
+        let data = log_entry_data(
+            DepositedEventSchema::WithAuroraRecipient,
+            Some(Token::Address([3u8; 20].into())),
+        );
+        let event = DepositedEvent::from_log_entry_data(&data, accept_all).unwrap();
+        assert_eq!(event.aurora_recipient, Some([3u8; 20]));
+        assert_eq!(event.recipient, format!("aurora:{}", "03".repeat(20)));
+    }
+
+    #[test]
+    fn test_decode_deposited_event_errors() {
+        let data = log_entry_data(DepositedEventSchema::WithNonce, Some(Token::Uint(7.into())));
+        let err = DepositedEvent::from_log_entry_data(&data, |_, _| None).unwrap_err();
+        assert_eq!(err, DepositedEventError::UnknownSchema);
+
+        let err = DepositedEvent::from_log_entry_data(&data[1..], accept_all).unwrap_err();
+        assert_eq!(err, DepositedEventError::InvalidRlp);
+
+        // The indexed sender is missing
+        let log_entry = LogEntry {
+            address: H160(CUSTODIAN),
+            topics: vec![H256(DepositedEventSchema::Basic.topic0())],
+            data: Vec::new(),
+        };
+        let data = rlp::encode(&log_entry).to_vec();
+        let err = DepositedEvent::from_log_entry_data(&data, accept_all).unwrap_err();
+        assert_eq!(err, DepositedEventError::InvalidLog);
+
+        // The NEAR recipient of an explicit Aurora recipient has no message
+        let mut data = log_entry_data(
+            DepositedEventSchema::WithAuroraRecipient,
+            Some(Token::Address([3u8; 20].into())),
+        );
+        let log_entry: LogEntry = rlp::decode(&data).unwrap();
+        let log_entry = LogEntry {
+            data: ethabi::encode(&[
+                Token::String("aurora:0102".to_string()),
+                Token::Uint(U256::from(100)),
+                Token::Uint(U256::from(10)),
+                Token::Address([3u8; 20].into()),
+            ]),
+            ..log_entry
+        };
+        data = rlp::encode(&log_entry).to_vec();
+        let err = DepositedEvent::from_log_entry_data(&data, accept_all).unwrap_err();
+        assert_eq!(err, DepositedEventError::InvalidRecipient);
     }
 }
@@ -1,5 +1,6 @@
 use crate::parameters::{
-    FunctionCallArgs, NEP141FtOnTransferArgs, ResultLog, SubmitResult, ViewCallArgs,
+    DepositCall, DepositMessage, FreezeTarget, FunctionCallArgs, NEP141FtOnTransferArgs,
+    NEP171NftOnTransferArgs, ResultLog, SubmitResult, ViewCallArgs,
 };
 use core::mem;
 use evm::backend::{Apply, ApplyBackend, Backend, Basic, Log};
@@ -9,14 +10,22 @@ use evm::{Config, CreateScheme, ExitError, ExitFatal, ExitReason};
 use crate::connector::EthConnectorContract;
 #[cfg(feature = "contract")]
 use crate::contract::current_address;
+use crate::events::EngineEvent;
+use crate::json::JsonValue;
 use crate::map::{BijectionMap, LookupMap};
 use crate::prelude::{
-    address_to_key, bytes_to_key, sdk, storage_to_key, u256_to_arr, AccountId, Address,
-    BorshDeserialize, BorshSerialize, KeyPrefix, KeyPrefixU8, PromiseCreateArgs, TryFrom, TryInto,
-    Vec, Wei, ERC20_MINT_SELECTOR, H256, U256,
+    address_to_key, bytes_to_key, precompiles, sdk, storage_to_key, u256_to_arr, vec, AccountId,
+    Address, Balance, BorshDeserialize, BorshSerialize, EvmCallbackArgs, KeyPrefix, KeyPrefixU8,
+    PromiseArgs, PromiseBatchArgs, PromiseCreateArgs, PromiseResult, PromiseWithCallbackArgs,
+    RefundCallArgs, ToString, TryFrom, TryInto, Vec, Wei, ERC20_MINT_SELECTOR, H256,
+    NATIVE_ERC20_ESCROW_KEY, U256,
 };
 
-use crate::parameters::{NewCallArgs, TransactionStatus};
+use crate::parameters::{
+    BridgedTokenPair, Erc20AutoDeployConfig, Erc20Metadata, NativeErc20DeployedCallbackArgs,
+    NewCallArgs, SyncErc20MetadataCallArgs, TransactionStatus,
+};
+use crate::prelude::precompiles::cross_contract_call::CrossContractCall;
 use crate::prelude::precompiles::native::{ExitToEthereum, ExitToNear};
 use crate::prelude::precompiles::Precompiles;
 
@@ -32,29 +41,6 @@ pub fn current_address() -> Address {
     sdk::types::near_account_to_evm_address("engine".as_bytes())
 }
 
-macro_rules! unwrap_res_or_finish {
-    ($e:expr, $output:expr) => {
-        match $e {
-            Ok(v) => v,
-            Err(_e) => {
-                #[cfg(feature = "log")]
-                sdk::log(crate::prelude::format!("{:?}", _e).as_str());
-                sdk::return_output($output);
-                return;
-            }
-        }
-    };
-}
-
-macro_rules! assert_or_finish {
-    ($e:expr, $output:expr) => {
-        if !$e {
-            sdk::return_output($output);
-            return;
-        }
-    };
-}
-
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct EngineError {
     pub kind: EngineErrorKind,
@@ -82,6 +68,8 @@ pub enum EngineErrorKind {
     EvmFatal(ExitFatal),
     /// Incorrect nonce.
     IncorrectNonce,
+    /// The sender was frozen in the eth-connector.
+    FrozenAddress,
 }
 
 impl EngineErrorKind {
@@ -113,6 +101,7 @@ impl EngineErrorKind {
             EvmFatal(ExitFatal::Other(m)) => m,
             EvmFatal(_) => unreachable!(), // unused misc
             IncorrectNonce => "ERR_INCORRECT_NONCE",
+            FrozenAddress => "ERR_ADDRESS_FROZEN",
         }
     }
 }
@@ -242,6 +231,179 @@ impl AsRef<[u8]> for RegisterTokenError {
     }
 }
 
+pub enum GetErc721FromNep171Error {
+    InvalidNep171AccountId,
+    Nep171NotFound,
+}
+
+impl AsRef<[u8]> for GetErc721FromNep171Error {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidNep171AccountId => b"ERR_INVALID_NEP171_ACCOUNT_ID",
+            Self::Nep171NotFound => b"ERR_NEP171_NOT_FOUND",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RegisterErc721TokenError {
+    InvalidNep171AccountId,
+    TokenAlreadyRegistered,
+}
+
+impl AsRef<[u8]> for RegisterErc721TokenError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidNep171AccountId => b"ERR_INVALID_NEP171_ACCOUNT_ID",
+            Self::TokenAlreadyRegistered => b"ERR_NEP171_TOKEN_ALREADY_REGISTERED",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DeployTokenError {
+    /// The deployment of the token contract could not be executed
+    Engine(EngineError),
+    /// The deployment of the token contract did not succeed
+    Failed(TransactionStatus),
+    RegisterErc20(RegisterTokenError),
+    RegisterErc721(RegisterErc721TokenError),
+}
+
+impl AsRef<[u8]> for DeployTokenError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Engine(e) => e.as_ref(),
+            Self::Failed(status) => status.as_ref(),
+            Self::RegisterErc20(e) => e.as_ref(),
+            Self::RegisterErc721(e) => e.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AutoDeployError {
+    /// Automatic deployment of ERC-20 tokens is not enabled
+    Disabled,
+    /// The NEP-141 token is in the deny-list
+    Denied,
+    /// The deployment of the NEP-141 token was not funded
+    NotFunded,
+    /// The attached NEAR does not cover the deployment fee
+    InsufficientFee,
+    /// The deployment of the NEP-141 token is already funded, or deployed
+    AlreadyFunded,
+    Deploy(DeployTokenError),
+}
+
+impl AsRef<[u8]> for AutoDeployError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::Disabled => b"ERR_AUTO_DEPLOY_DISABLED",
+            Self::Denied => b"ERR_AUTO_DEPLOY_DENIED",
+            Self::NotFunded => b"ERR_AUTO_DEPLOY_NOT_FUNDED",
+            Self::InsufficientFee => b"ERR_AUTO_DEPLOY_INSUFFICIENT_FEE",
+            Self::AlreadyFunded => b"ERR_AUTO_DEPLOY_ALREADY_FUNDED",
+            Self::Deploy(e) => e.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TokenMappingError {
+    TokenNotFound(GetErc20FromNep141Error),
+    /// The ERC-20 token still has a supply, and the change is not forced
+    NonZeroSupply,
+    /// The total supply of the ERC-20 token could not be read
+    TotalSupplyUnavailable,
+    /// The new ERC-20 token is already mapped to a NEP-141 token
+    Erc20AlreadyRegistered,
+    /// There is no contract at the new ERC-20 address
+    Erc20NotDeployed,
+}
+
+impl AsRef<[u8]> for TokenMappingError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::TokenNotFound(e) => e.as_ref(),
+            Self::NonZeroSupply => b"ERR_ERC20_NON_ZERO_SUPPLY",
+            Self::TotalSupplyUnavailable => b"ERR_ERC20_TOTAL_SUPPLY",
+            Self::Erc20AlreadyRegistered => b"ERR_ERC20_TOKEN_ALREADY_REGISTERED",
+            Self::Erc20NotDeployed => b"ERR_ERC20_NOT_DEPLOYED",
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RegisterNativeErc20Error {
+    /// No NEAR factory is set to deploy the NEP-141 token
+    FactoryNotSet,
+    InvalidNep141AccountId,
+    /// There is no contract at the ERC-20 address
+    Erc20NotDeployed,
+    /// The ERC-20 token is bridged from a NEP-141 token already
+    BridgedErc20,
+    Erc20AlreadyRegistered,
+    Nep141AlreadyRegistered,
+    /// The escrow contract of the native ERC-20 tokens could not be deployed
+    DeployEscrow(DeployTokenError),
+}
+
+impl AsRef<[u8]> for RegisterNativeErc20Error {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::FactoryNotSet => b"ERR_NATIVE_ERC20_FACTORY_NOT_SET",
+            Self::InvalidNep141AccountId => ERR_INVALID_NEP141_ACCOUNT_ID.as_bytes(),
+            Self::Erc20NotDeployed => b"ERR_ERC20_NOT_DEPLOYED",
+            Self::BridgedErc20 => b"ERR_ERC20_IS_BRIDGED",
+            Self::Erc20AlreadyRegistered => b"ERR_ERC20_TOKEN_ALREADY_REGISTERED",
+            Self::Nep141AlreadyRegistered => b"ERR_NEP141_TOKEN_ALREADY_REGISTERED",
+            Self::DeployEscrow(e) => e.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum SetErc20MetadataError {
+    TokenNotFound(GetErc20FromNep141Error),
+    Engine(EngineError),
+}
+
+impl AsRef<[u8]> for SetErc20MetadataError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::TokenNotFound(e) => e.as_ref(),
+            Self::Engine(e) => e.as_ref(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RefundError {
+    /// The exit precompile does not hold the ETH to refund
+    InsufficientExitBalance,
+    /// Overflow adding ETH to the refund address balance
+    BalanceOverflow,
+    /// The bridged tokens could not be minted back
+    MintFailed(EngineError),
+    /// There is no escrow to unlock the native ERC-20 tokens from
+    EscrowNotFound,
+    /// The native ERC-20 tokens could not be unlocked
+    UnlockFailed(EngineError),
+}
+
+impl AsRef<[u8]> for RefundError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InsufficientExitBalance => b"ERR_REFUND_INSUFFICIENT_EXIT_BALANCE",
+            Self::BalanceOverflow => b"ERR_BALANCE_OVERFLOW",
+            Self::MintFailed(e) => e.as_ref(),
+            Self::EscrowNotFound => b"ERR_NATIVE_ERC20_ESCROW_NOT_FOUND",
+            Self::UnlockFailed(e) => e.as_ref(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum EngineStateError {
     NotFound,
@@ -321,6 +483,45 @@ pub(crate) const CONFIG: &Config = &Config::istanbul();
 /// Key for storing the state of the engine.
 const STATE_KEY: &[u8; 5] = b"STATE";
 
+/// Selector of `setMetadata(string,string,uint8)` of the `EvmErc20` template.
+const ERC20_SET_METADATA_SELECTOR: &[u8] = &[0x37, 0xd2, 0xc2, 0xf4];
+
+/// NEAR gas attached to the `ft_metadata` call of a NEP-141 token.
+const FT_METADATA_GAS: u64 = 10_000_000_000_000;
+
+/// NEAR gas attached to the `set_erc20_metadata_callback`.
+const SET_ERC20_METADATA_GAS: u64 = 50_000_000_000_000;
+
+/// Selector of `totalSupply()` of ERC-20 tokens.
+const ERC20_TOTAL_SUPPLY_SELECTOR: &[u8] = &[0x18, 0x16, 0x0d, 0xdd];
+
+/// Key for storing the number of tokens in `KeyPrefix::Erc20TokenIndex`.
+const ERC20_TOKEN_COUNT_KEY: &[u8; 17] = b"ERC20_TOKEN_COUNT";
+
+/// Sub-prefixes of `KeyPrefix::Erc20TokenIndex`, which lists the NEP-141
+/// tokens mapped to an ERC-20 token. The list is kept dense by moving the
+/// last token in place of a removed one.
+const TOKEN_INDEX_POSITION: u8 = 0x0;
+const TOKEN_INDEX_ACCOUNT: u8 = 0x1;
+
+/// Key for storing the configuration of the automatic ERC-20 deployment.
+const ERC20_AUTO_DEPLOY_KEY: &[u8; 17] = b"ERC20_AUTO_DEPLOY";
+
+/// Key for storing the NEAR factory of the NEP-141 tokens of EVM-native ERC-20 tokens.
+const NATIVE_ERC20_FACTORY_KEY: &[u8; 20] = b"NATIVE_ERC20_FACTORY";
+
+/// Selector of `unlock(address,address,uint256)` of the `EvmErc20Escrow` contract.
+const ESCROW_UNLOCK_SELECTOR: &[u8] = &[0x59, 0x50, 0x8f, 0x8f];
+
+/// NEAR gas attached to the `deploy_bridge_token` call of the NEP-141 factory.
+const DEPLOY_BRIDGE_TOKEN_GAS: u64 = 100_000_000_000_000;
+
+/// NEAR gas attached to the `native_erc20_deployed_callback`.
+const NATIVE_ERC20_DEPLOYED_GAS: u64 = 10_000_000_000_000;
+
+/// NEAR gas attached to the `burn` call of the NEP-141 token of a native ERC-20 token.
+const FT_BURN_GAS: u64 = 10_000_000_000_000;
+
 impl Engine {
     pub fn new(origin: Address) -> Result<Self, EngineStateError> {
         Engine::get_state().map(|state| Self::new_with_state(state, origin))
@@ -582,6 +783,7 @@ impl Engine {
         gas_limit: u64,
         access_list: Vec<(Address, Vec<H256>)>, // See EIP-2930
     ) -> EngineResult<SubmitResult> {
+        Self::check_not_frozen(&origin)?;
         let executor_params = StackExecutorParams::new(gas_limit);
         let mut executor = executor_params.make_executor(self);
         let address = executor.create_address(CreateScheme::Legacy { caller: origin });
@@ -623,6 +825,7 @@ impl Engine {
         gas_limit: u64,
         access_list: Vec<(Address, Vec<H256>)>, // See EIP-2930
     ) -> EngineResult<SubmitResult> {
+        Self::check_not_frozen(&origin)?;
         let executor_params = StackExecutorParams::new(gas_limit);
         let mut executor = executor_params.make_executor(self);
         let (exit_reason, result) =
@@ -647,6 +850,42 @@ impl Engine {
         Ok(SubmitResult::new(status, used_gas, logs))
     }
 
+    /// Calls the EVM callback of a promise scheduled through the cross-contract
+    /// call precompile with the ABI-encoded result of that promise.
+    ///
+    /// The callback is called by the contract which scheduled the promise,
+    /// allowing the callback to authenticate the call using `msg.sender`.
+    pub fn evm_callback(&mut self, args: EvmCallbackArgs) -> EngineResult<SubmitResult> {
+        let result = match sdk::promise_results_count() {
+            1 => sdk::promise_result(0),
+            _ => sdk::panic_utf8(b"ERR_PROMISE_COUNT"),
+        };
+        let input = [
+            &args.selector[..],
+            &precompiles::promise_result::abi_encode(result),
+        ]
+        .concat();
+
+        self.call(
+            Address(args.sender),
+            Address(args.callback_address),
+            Wei::zero(),
+            input,
+            args.gas_limit,
+            Vec::new(),
+        )
+    }
+
+    /// Rejects the calls and deployments of the addresses frozen in the
+    /// eth-connector, which uses the nonce like the other failed transactions.
+    fn check_not_frozen(origin: &Address) -> EngineResult<()> {
+        if EthConnectorContract::is_frozen(&FreezeTarget::EvmAddress(origin.0)) {
+            Engine::increment_nonce(origin);
+            return Err(EngineErrorKind::FrozenAddress.with_gas_used(0));
+        }
+        Ok(())
+    }
+
     pub fn increment_nonce(address: &Address) {
         let account_nonce = Self::get_nonce(address);
         let new_nonce = account_nonce.saturating_add(U256::one());
@@ -701,11 +940,508 @@ impl Engine {
             }
             Ok(_) => return Err(RegisterTokenError::TokenAlreadyRegistered),
         }
+        if Self::get_native_erc20_from_nep141(nep141_token).is_some() {
+            return Err(RegisterTokenError::TokenAlreadyRegistered);
+        }
 
         Self::nep141_erc20_map().insert(nep141_token, erc20_token);
+        Self::add_to_token_index(nep141_token);
+        EngineEvent::BridgedTokenRegistered {
+            nep141: &crate::prelude::String::from_utf8_lossy(nep141_token),
+            erc20: erc20_token.try_into().unwrap_or_default(),
+        }
+        .emit();
+        Ok(())
+    }
+
+    /// Removes the mapping of the NEP-141 token `nep141`, returning the ERC-20
+    /// token it was mapped to. Unless `force` is set, the ERC-20 token must
+    /// not have any supply left, as it could not be exited anymore.
+    pub fn unregister_token(
+        &mut self,
+        nep141: &[u8],
+        force: bool,
+    ) -> Result<Address, TokenMappingError> {
+        let erc20_token = Self::get_erc20_from_nep141(nep141)
+            .map(|erc20_token| Address::from_slice(&erc20_token))
+            .map_err(TokenMappingError::TokenNotFound)?;
+        if !force {
+            self.check_no_supply(erc20_token)?;
+        }
+
+        Self::nep141_erc20_map().remove_left(nep141);
+        Self::remove_from_token_index(nep141);
+        EngineEvent::BridgedTokenUnregistered {
+            nep141: &crate::prelude::String::from_utf8_lossy(nep141),
+            erc20: erc20_token.0,
+        }
+        .emit();
+
+        Ok(erc20_token)
+    }
+
+    /// Maps the NEP-141 token `nep141` to the already deployed ERC-20 token
+    /// `erc20_token` instead, returning the previous ERC-20 token. Unless
+    /// `force` is set, the previous ERC-20 token must not have any supply left.
+    pub fn remap_token(
+        &mut self,
+        nep141: &[u8],
+        erc20_token: Address,
+        force: bool,
+    ) -> Result<Address, TokenMappingError> {
+        let previous_erc20_token = Self::get_erc20_from_nep141(nep141)
+            .map(|erc20_token| Address::from_slice(&erc20_token))
+            .map_err(TokenMappingError::TokenNotFound)?;
+        if Self::nep141_erc20_map()
+            .lookup_right(erc20_token.as_bytes())
+            .is_some()
+            || Self::get_nep141_from_native_erc20(&erc20_token).is_some()
+        {
+            return Err(TokenMappingError::Erc20AlreadyRegistered);
+        }
+        if Self::get_code_size(&erc20_token) == 0 {
+            return Err(TokenMappingError::Erc20NotDeployed);
+        }
+        if !force {
+            self.check_no_supply(previous_erc20_token)?;
+        }
+
+        Self::nep141_erc20_map().remove_left(nep141);
+        Self::nep141_erc20_map().insert(nep141, erc20_token.as_bytes());
+        EngineEvent::BridgedTokenRemapped {
+            nep141: &crate::prelude::String::from_utf8_lossy(nep141),
+            previous_erc20: previous_erc20_token.0,
+            erc20: erc20_token.0,
+        }
+        .emit();
+
+        Ok(previous_erc20_token)
+    }
+
+    fn check_no_supply(&self, erc20_token: Address) -> Result<(), TokenMappingError> {
+        let total_supply = match self.view(
+            current_address(),
+            erc20_token,
+            Wei::zero(),
+            ERC20_TOTAL_SUPPLY_SELECTOR.to_vec(),
+            u64::MAX,
+        ) {
+            Ok(TransactionStatus::Succeed(output)) if output.len() == 32 => {
+                U256::from_big_endian(&output)
+            }
+            _ => return Err(TokenMappingError::TotalSupplyUnavailable),
+        };
+        if !total_supply.is_zero() {
+            return Err(TokenMappingError::NonZeroSupply);
+        }
+        Ok(())
+    }
+
+    /// Returns up to `limit` mapped token pairs, starting at `from_index`.
+    pub fn get_bridged_tokens(from_index: u64, limit: u64) -> Vec<BridgedTokenPair> {
+        let end = Self::token_count().min(from_index.saturating_add(limit));
+        (from_index..end)
+            .filter_map(|position| {
+                let nep141 = sdk::read_storage(&Self::token_position_key(position))?;
+                let erc20 = Self::nep141_erc20_map().lookup_left(&nep141)?;
+                Some(BridgedTokenPair {
+                    nep141: AccountId::try_from(nep141.as_slice()).ok()?,
+                    erc20: erc20.as_slice().try_into().ok()?,
+                })
+            })
+            .collect()
+    }
+
+    /// Adds the NEP-141 tokens registered before the index existed to it, so
+    /// that they are listed by `get_bridged_tokens`. The tokens which are
+    /// already listed are skipped.
+    pub fn index_bridged_tokens(nep141s: &[AccountId]) -> Result<(), TokenMappingError> {
+        for nep141 in nep141s {
+            let erc20 = Self::get_erc20_from_nep141(nep141.as_bytes())
+                .map_err(TokenMappingError::TokenNotFound)?;
+            if sdk::storage_has_key(&Self::token_account_key(nep141.as_bytes())) {
+                continue;
+            }
+            Self::add_to_token_index(nep141.as_bytes());
+            EngineEvent::BridgedTokenIndexed {
+                nep141: nep141.as_ref(),
+                erc20: erc20.as_slice().try_into().unwrap_or_default(),
+            }
+            .emit();
+        }
+        Ok(())
+    }
+
+    fn token_count() -> u64 {
+        sdk::read_u64(&bytes_to_key(KeyPrefix::Config, ERC20_TOKEN_COUNT_KEY)).unwrap_or(0)
+    }
+
+    fn set_token_count(count: u64) {
+        sdk::write_storage(
+            &bytes_to_key(KeyPrefix::Config, ERC20_TOKEN_COUNT_KEY),
+            &count.to_le_bytes(),
+        );
+    }
+
+    fn token_position_key(position: u64) -> Vec<u8> {
+        bytes_to_key(
+            KeyPrefix::Erc20TokenIndex,
+            &[&[TOKEN_INDEX_POSITION], &position.to_be_bytes()[..]].concat(),
+        )
+    }
+
+    fn token_account_key(nep141: &[u8]) -> Vec<u8> {
+        bytes_to_key(
+            KeyPrefix::Erc20TokenIndex,
+            &[&[TOKEN_INDEX_ACCOUNT], nep141].concat(),
+        )
+    }
+
+    fn add_to_token_index(nep141: &[u8]) {
+        let count = Self::token_count();
+        sdk::write_storage(&Self::token_position_key(count), nep141);
+        sdk::write_storage(&Self::token_account_key(nep141), &count.to_le_bytes());
+        Self::set_token_count(count + 1);
+    }
+
+    fn remove_from_token_index(nep141: &[u8]) {
+        let position = match sdk::read_u64(&Self::token_account_key(nep141)) {
+            Ok(position) => position,
+            // Tokens registered before the index existed are only listed
+            // once backfilled by `index_bridged_tokens`.
+            Err(_) => return,
+        };
+        let last = Self::token_count() - 1;
+        if position != last {
+            let last_key = Self::token_position_key(last);
+            // The last position is always set, since the index is dense.
+            let last_nep141 = sdk::read_storage(&last_key).unwrap();
+            sdk::write_storage(&Self::token_position_key(position), &last_nep141);
+            sdk::write_storage(
+                &Self::token_account_key(&last_nep141),
+                &position.to_le_bytes(),
+            );
+        }
+        sdk::remove_storage(&Self::token_position_key(last));
+        sdk::remove_storage(&Self::token_account_key(nep141));
+        Self::set_token_count(last);
+    }
+
+    /// Deploys the bundled `EvmErc20` contract, administered by the engine,
+    /// and maps it to the NEP-141 token `nep141`.
+    pub fn deploy_erc20_token(&mut self, nep141: &[u8]) -> Result<Address, DeployTokenError> {
+        let erc20_contract = include_bytes!("../../etc/eth-contracts/res/EvmErc20.bin");
+        let deploy_args = ethabi::encode(&[
+            ethabi::Token::String("Empty".to_string()),
+            ethabi::Token::String("EMPTY".to_string()),
+            ethabi::Token::Uint(ethabi::Uint::from(0)),
+            ethabi::Token::Address(current_address()),
+        ]);
+
+        let address = self.deploy_token_contract(erc20_contract, &deploy_args)?;
+        sdk::log!(crate::prelude::format!("Deployed ERC-20 in Aurora at: {:#?}", address).as_str());
+        self.register_token(address.as_bytes(), nep141)
+            .map_err(DeployTokenError::RegisterErc20)?;
+        // `register_token` validated the account id already
+        Self::sync_erc20_metadata(&AccountId::try_from(nep141).unwrap());
+
+        Ok(address)
+    }
+
+    /// Fetches the `ft_metadata` of the NEP-141 token `nep141`, which the
+    /// `set_erc20_metadata_callback` sets on its ERC-20 token.
+    pub fn sync_erc20_metadata(nep141: &AccountId) {
+        let promise = PromiseWithCallbackArgs {
+            base: PromiseCreateArgs {
+                target_account_id: nep141.clone(),
+                method: "ft_metadata".to_string(),
+                args: Vec::new(),
+                attached_balance: 0,
+                attached_gas: FT_METADATA_GAS,
+            },
+            callback: PromiseCreateArgs {
+                target_account_id: AccountId::try_from(sdk::current_account_id()).unwrap(),
+                method: "set_erc20_metadata_callback".to_string(),
+                args: SyncErc20MetadataCallArgs {
+                    nep141: nep141.clone(),
+                }
+                .try_to_vec()
+                .unwrap(),
+                attached_balance: 0,
+                attached_gas: SET_ERC20_METADATA_GAS,
+            },
+        };
+        Self::schedule_promise(PromiseArgs::Callback(promise));
+    }
+
+    /// Deploys the bundled `EvmErc721` contract, administered by the engine,
+    /// and maps it to the NEP-171 token `nep171`.
+    pub fn deploy_erc721_token(&mut self, nep171: &[u8]) -> Result<Address, DeployTokenError> {
+        let erc721_contract = include_bytes!("../../etc/eth-contracts/res/EvmErc721.bin");
+        let deploy_args = ethabi::encode(&[
+            ethabi::Token::String("Empty".to_string()),
+            ethabi::Token::String("EMPTY".to_string()),
+            ethabi::Token::Address(current_address()),
+        ]);
+
+        let address = self.deploy_token_contract(erc721_contract, &deploy_args)?;
+        sdk::log!(
+            crate::prelude::format!("Deployed ERC-721 in Aurora at: {:#?}", address).as_str()
+        );
+        self.register_erc721_token(address.as_bytes(), nep171)
+            .map_err(DeployTokenError::RegisterErc721)?;
+
+        Ok(address)
+    }
+
+    /// Deploys the token contract `code` with the ABI-encoded constructor
+    /// arguments, returning its address.
+    fn deploy_token_contract(
+        &mut self,
+        code: &[u8],
+        deploy_args: &[u8],
+    ) -> Result<Address, DeployTokenError> {
+        let result = self
+            .deploy_code_with_input([code, deploy_args].concat())
+            .map_err(DeployTokenError::Engine)?;
+        match result.status {
+            TransactionStatus::Succeed(ret) => Ok(Address::from_slice(&ret)),
+            other => Err(DeployTokenError::Failed(other)),
+        }
+    }
+
+    pub fn set_erc20_auto_deploy_config(config: &Erc20AutoDeployConfig) {
+        sdk::write_storage(
+            &bytes_to_key(KeyPrefix::Config, ERC20_AUTO_DEPLOY_KEY),
+            &config.try_to_vec().expect("ERR_SER"),
+        );
+    }
+
+    /// Automatic deployment is disabled unless configured.
+    pub fn get_erc20_auto_deploy_config() -> Erc20AutoDeployConfig {
+        sdk::read_storage(&bytes_to_key(KeyPrefix::Config, ERC20_AUTO_DEPLOY_KEY))
+            .and_then(|bytes| Erc20AutoDeployConfig::try_from_slice(&bytes).ok())
+            .unwrap_or_default()
+    }
+
+    /// Adds (or removes) the NEP-141 token `nep141` to the deny-list of the
+    /// automatic ERC-20 deployment.
+    pub fn set_erc20_auto_deploy_denied(nep141: &[u8], denied: bool) {
+        let key = bytes_to_key(KeyPrefix::Erc20AutoDeployDenyList, nep141);
+        if denied {
+            sdk::write_storage(&key, &[1]);
+        } else {
+            sdk::remove_storage(&key);
+        }
+    }
+
+    pub fn is_erc20_auto_deploy_denied(nep141: &[u8]) -> bool {
+        sdk::storage_has_key(&bytes_to_key(KeyPrefix::Erc20AutoDeployDenyList, nep141))
+    }
+
+    /// Pays the deployment fee of the ERC-20 token of the NEP-141 token `nep141`
+    /// in advance with the attached `deposit` of NEAR, which covers the storage
+    /// of the token. The token is deployed on its first deposit.
+    pub fn fund_erc20_auto_deploy(nep141: &[u8], deposit: Balance) -> Result<(), AutoDeployError> {
+        let config = Self::get_erc20_auto_deploy_config();
+        if !config.enabled {
+            return Err(AutoDeployError::Disabled);
+        }
+        if Self::is_erc20_auto_deploy_denied(nep141) {
+            return Err(AutoDeployError::Denied);
+        }
+        if Self::is_erc20_auto_deploy_funded(nep141) || Self::get_erc20_from_nep141(nep141).is_ok()
+        {
+            return Err(AutoDeployError::AlreadyFunded);
+        }
+        if deposit < config.deploy_fee {
+            return Err(AutoDeployError::InsufficientFee);
+        }
+        sdk::write_storage(
+            &bytes_to_key(KeyPrefix::Erc20AutoDeployFunding, nep141),
+            &deposit.to_le_bytes(),
+        );
         Ok(())
     }
 
+    pub fn is_erc20_auto_deploy_funded(nep141: &[u8]) -> bool {
+        sdk::storage_has_key(&bytes_to_key(KeyPrefix::Erc20AutoDeployFunding, nep141))
+    }
+
+    pub fn register_erc721_token(
+        &mut self,
+        erc721_token: &[u8],
+        nep171_token: &[u8],
+    ) -> Result<(), RegisterErc721TokenError> {
+        match Self::get_erc721_from_nep171(nep171_token) {
+            Err(GetErc721FromNep171Error::Nep171NotFound) => (),
+            Err(GetErc721FromNep171Error::InvalidNep171AccountId) => {
+                return Err(RegisterErc721TokenError::InvalidNep171AccountId);
+            }
+            Ok(_) => return Err(RegisterErc721TokenError::TokenAlreadyRegistered),
+        }
+
+        Self::nep171_erc721_map().insert(nep171_token, erc721_token);
+        Ok(())
+    }
+
+    pub fn set_native_erc20_factory(factory: &AccountId) {
+        sdk::write_storage(
+            &bytes_to_key(KeyPrefix::Config, NATIVE_ERC20_FACTORY_KEY),
+            factory.as_bytes(),
+        );
+    }
+
+    pub fn get_native_erc20_factory() -> Option<AccountId> {
+        sdk::read_storage(&bytes_to_key(KeyPrefix::Config, NATIVE_ERC20_FACTORY_KEY))
+            .and_then(|factory| AccountId::try_from(factory.as_slice()).ok())
+    }
+
+    pub fn get_native_erc20_escrow() -> Option<Address> {
+        sdk::read_storage(&bytes_to_key(KeyPrefix::Config, NATIVE_ERC20_ESCROW_KEY))
+            .map(|escrow| Address::from_slice(&escrow))
+    }
+
+    /// Returns the escrow of the native ERC-20 tokens, deploying the bundled
+    /// `EvmErc20Escrow` contract, administered by the engine, the first time.
+    fn native_erc20_escrow(&mut self) -> Result<Address, DeployTokenError> {
+        if let Some(escrow) = Self::get_native_erc20_escrow() {
+            return Ok(escrow);
+        }
+
+        let escrow_contract = include_bytes!("../../etc/eth-contracts/res/EvmErc20Escrow.bin");
+        let deploy_args = ethabi::encode(&[ethabi::Token::Address(current_address())]);
+        let escrow = self.deploy_token_contract(escrow_contract, &deploy_args)?;
+        sdk::log!(crate::prelude::format!(
+            "Deployed native ERC-20 escrow in Aurora at: {:#?}",
+            escrow
+        )
+        .as_str());
+        sdk::write_storage(
+            &bytes_to_key(KeyPrefix::Config, NATIVE_ERC20_ESCROW_KEY),
+            escrow.as_bytes(),
+        );
+
+        Ok(escrow)
+    }
+
+    /// Bridges the EVM-native ERC-20 token `erc20_token` to NEAR, either with
+    /// the already deployed NEP-141 token `nep141`, or with a new NEP-141
+    /// token deployed by the factory, paid with `deposit`. In the latter case
+    /// the tokens are mapped by the `native_erc20_deployed_callback`, once the
+    /// NEP-141 token is deployed. Returns the NEP-141 token.
+    pub fn register_native_erc20(
+        &mut self,
+        erc20_token: Address,
+        nep141: Option<AccountId>,
+        deposit: u128,
+    ) -> Result<AccountId, RegisterNativeErc20Error> {
+        if Self::get_code_size(&erc20_token) == 0 {
+            return Err(RegisterNativeErc20Error::Erc20NotDeployed);
+        }
+        if Self::nep141_erc20_map()
+            .lookup_right(erc20_token.as_bytes())
+            .is_some()
+        {
+            return Err(RegisterNativeErc20Error::BridgedErc20);
+        }
+        if Self::get_nep141_from_native_erc20(&erc20_token).is_some() {
+            return Err(RegisterNativeErc20Error::Erc20AlreadyRegistered);
+        }
+        self.native_erc20_escrow()
+            .map_err(RegisterNativeErc20Error::DeployEscrow)?;
+
+        if let Some(nep141) = nep141 {
+            Self::insert_native_erc20(erc20_token, &nep141)?;
+            return Ok(nep141);
+        }
+
+        let factory =
+            Self::get_native_erc20_factory().ok_or(RegisterNativeErc20Error::FactoryNotSet)?;
+        let address = hex::encode(erc20_token.as_bytes());
+        let nep141 = AccountId::try_from(crate::prelude::format!("{}.{}", address, factory))
+            .map_err(|_| RegisterNativeErc20Error::InvalidNep141AccountId)?;
+        let promise = PromiseWithCallbackArgs {
+            base: PromiseCreateArgs {
+                target_account_id: factory,
+                method: "deploy_bridge_token".to_string(),
+                args: JsonValue::object(vec![("address", address.into())]).to_bytes(),
+                attached_balance: deposit,
+                attached_gas: DEPLOY_BRIDGE_TOKEN_GAS,
+            },
+            callback: PromiseCreateArgs {
+                target_account_id: AccountId::try_from(sdk::current_account_id()).unwrap(),
+                method: "native_erc20_deployed_callback".to_string(),
+                args: NativeErc20DeployedCallbackArgs {
+                    nep141: nep141.clone(),
+                    erc20: erc20_token.0,
+                }
+                .try_to_vec()
+                .unwrap(),
+                attached_balance: 0,
+                attached_gas: NATIVE_ERC20_DEPLOYED_GAS,
+            },
+        };
+        Self::schedule_promise(PromiseArgs::Callback(promise));
+
+        Ok(nep141)
+    }
+
+    /// Maps the EVM-native ERC-20 token `erc20_token` to the NEP-141 token `nep141`.
+    pub fn insert_native_erc20(
+        erc20_token: Address,
+        nep141: &AccountId,
+    ) -> Result<(), RegisterNativeErc20Error> {
+        if Self::get_nep141_from_native_erc20(&erc20_token).is_some() {
+            return Err(RegisterNativeErc20Error::Erc20AlreadyRegistered);
+        }
+        if Self::get_native_erc20_from_nep141(nep141.as_bytes()).is_some()
+            || Self::get_erc20_from_nep141(nep141.as_bytes()).is_ok()
+        {
+            return Err(RegisterNativeErc20Error::Nep141AlreadyRegistered);
+        }
+
+        Self::native_erc20_nep141_map().insert(erc20_token.as_bytes(), nep141.as_bytes());
+        sdk::log!(crate::prelude::format!(
+            "Registered native token {:?} mapped to {}",
+            erc20_token,
+            nep141
+        )
+        .as_str());
+
+        Ok(())
+    }
+
+    pub fn get_nep141_from_native_erc20(erc20_token: &Address) -> Option<AccountId> {
+        Self::native_erc20_nep141_map()
+            .lookup_left(erc20_token.as_bytes())
+            .and_then(|nep141| AccountId::try_from(nep141.as_slice()).ok())
+    }
+
+    pub fn get_native_erc20_from_nep141(nep141: &[u8]) -> Option<Address> {
+        Self::native_erc20_nep141_map()
+            .lookup_right(nep141)
+            .map(|erc20_token| Address::from_slice(&erc20_token))
+    }
+
+    /// Allows (or forbids) `address` to schedule NEAR promises using the
+    /// cross-contract call precompile.
+    pub fn set_cross_contract_call_permission(address: &Address, allowed: bool) {
+        let key = address_to_key(KeyPrefix::CrossContractCallPermission, address);
+        if allowed {
+            sdk::write_storage(&key, &[1]);
+        } else {
+            sdk::remove_storage(&key);
+        }
+    }
+
+    pub fn has_cross_contract_call_permission(address: &Address) -> bool {
+        sdk::storage_has_key(&address_to_key(
+            KeyPrefix::CrossContractCallPermission,
+            address,
+        ))
+    }
+
     pub fn get_erc20_from_nep141(
         nep141_account_id: &[u8],
     ) -> Result<Vec<u8>, GetErc20FromNep141Error> {
@@ -717,6 +1453,17 @@ impl Engine {
             .ok_or(GetErc20FromNep141Error::Nep141NotFound)
     }
 
+    pub fn get_erc721_from_nep171(
+        nep171_account_id: &[u8],
+    ) -> Result<Vec<u8>, GetErc721FromNep171Error> {
+        AccountId::try_from(nep171_account_id)
+            .map_err(|_| GetErc721FromNep171Error::InvalidNep171AccountId)?;
+
+        Self::nep171_erc721_map()
+            .lookup_left(nep171_account_id)
+            .ok_or(GetErc721FromNep171Error::Nep171NotFound)
+    }
+
     /// Transfers an amount from a given sender to a receiver, provided that
     /// the have enough in their balance.
     ///
@@ -733,6 +1480,9 @@ impl Engine {
     }
 
     /// Mint tokens for recipient on a particular ERC20 token
+    /// The message is either a versioned `DepositMessage`, which may carry an
+    /// EVM call made once the tokens are minted, or the legacy recipient
+    /// address optionally followed by a fee, taken from the deposited tokens.
     /// This function should return the amount of tokens unused,
     /// which will be always all (<amount>) if there is any problem
     /// with the input, or 0 if tokens were minted successfully.
@@ -743,21 +1493,22 @@ impl Engine {
     /// IMPORTANT: This function should not panic, otherwise it won't
     /// be possible to return the tokens to the sender.
     pub fn receive_erc20_tokens(&mut self, args: &NEP141FtOnTransferArgs) {
-        let str_amount = crate::prelude::format!("\"{}\"", args.amount);
-        let output_on_fail = str_amount.as_bytes();
+        let json_amount = JsonValue::from(args.amount).to_bytes();
+        let output_on_fail = json_amount.as_slice();
 
-        // Parse message to determine recipient and fee
-        let (recipient, fee) = {
-            // Message format:
+        let message = if DepositMessage::is_versioned(&args.msg) {
+            unwrap_res_or_finish!(DepositMessage::parse(&args.msg), output_on_fail)
+        } else {
+            // Legacy message format:
             //      Recipient of the transaction - 40 characters (Address in hex)
-            //      Fee to be paid in ETH (Optional) - 64 characters (Encoded in big endian / hex)
+            //      Fee to be paid from the deposit (Optional) - 64 characters (Encoded in big endian / hex)
             let mut message = args.msg.as_bytes();
             assert_or_finish!(message.len() >= 40, output_on_fail);
 
-            let recipient = Address(unwrap_res_or_finish!(
+            let recipient = unwrap_res_or_finish!(
                 hex::decode(&message[..40]).unwrap().as_slice().try_into(),
                 output_on_fail
-            ));
+            );
             message = &message[40..];
 
             let fee = if message.is_empty() {
@@ -769,90 +1520,390 @@ impl Engine {
                 )
             };
 
-            (recipient, fee)
+            DepositMessage {
+                recipient,
+                fee,
+                call: None,
+            }
         };
+        let recipient = Address(message.recipient);
 
         let token = sdk::predecessor_account_id();
-        let erc20_token = Address(unwrap_res_or_finish!(
-            unwrap_res_or_finish!(Self::get_erc20_from_nep141(&token), output_on_fail)
-                .as_slice()
-                .try_into(),
-            output_on_fail
-        ));
+        let mut amount = U256::from(args.amount);
+
+        // The relayer is paid from the deposit, if registered. The fee is checked
+        // before the ERC-20 token may be deployed, so that a refunded deposit
+        // never deploys it.
+        let relayer_address = self.get_relayer(sdk::signer_account_id().as_slice());
+        let mut relayer_fee = None;
+        if let Some(relayer_address) = relayer_address.filter(|_| !message.fee.is_zero()) {
+            amount =
+                unwrap_res_or_finish!(amount.checked_sub(message.fee).ok_or(()), output_on_fail);
+            relayer_fee = Some((relayer_address, message.fee));
+        }
 
-        if fee != U256::from(0) {
-            let relayer_account_id = sdk::signer_account_id();
-            let relayer_address = unwrap_res_or_finish!(
-                self.get_relayer(relayer_account_id.as_slice()).ok_or(()),
+        let erc20_token = match Self::get_erc20_from_nep141(&token) {
+            Err(GetErc20FromNep141Error::Nep141NotFound) => {
+                unwrap_res_or_finish!(self.auto_deploy_erc20_token(&token), output_on_fail)
+            }
+            result => Address(unwrap_res_or_finish!(
+                unwrap_res_or_finish!(result, output_on_fail)
+                    .as_slice()
+                    .try_into(),
                 output_on_fail
-            );
+            )),
+        };
 
+        unwrap_res_or_finish!(
+            self.mint_bridged_token(erc20_token, recipient, amount),
+            output_on_fail
+        );
+        if let Some((relayer_address, fee)) = relayer_fee {
             unwrap_res_or_finish!(
-                self.transfer(
-                    recipient,
-                    relayer_address,
-                    Wei::new_u64(fee.as_u64()),
-                    u64::MAX,
-                ),
+                self.mint_bridged_token(erc20_token, relayer_address, fee),
                 output_on_fail
             );
         }
 
-        let selector = ERC20_MINT_SELECTOR;
+        // The `sender_id` is not authenticated, since any NEP-141 may call
+        // `ft_on_transfer`, so the call is made by the address of the token
+        // account, which only the token can make calls from.
+        if let Some(call) = &message.call {
+            let sender = sdk::types::near_account_to_evm_address(&token);
+            self.deposit_call(sender, call);
+        }
+
+        // TODO(marX)
+        // Everything succeed so return "0"
+        sdk::return_output(&JsonValue::from(0u128).to_bytes());
+    }
+
+    /// Makes the EVM call following a deposit from `sender`, which is either the
+    /// address of the NEP-141 token account which sent the tokens, or the recipient
+    /// of a deposit from Ethereum. The deposit is kept if it fails.
+    pub fn deposit_call(&mut self, sender: Address, call: &DepositCall) {
+        let contract = Address(call.contract);
+        let succeeded = match self.call(
+            sender,
+            contract,
+            Wei::zero(),
+            call.input.clone(),
+            call.gas_limit,
+            Vec::new(),
+        ) {
+            Ok(result) => result.status.is_ok(),
+            Err(_) => false,
+        };
+        if !succeeded {
+            sdk::log!(crate::prelude::format!("Deposit call to {:?} failed", contract).as_str());
+        }
+    }
+
+    /// Deploys the ERC-20 token of the unmapped NEP-141 `nep141` on its first
+    /// deposit, if enabled by the `Erc20AutoDeployConfig`, the token is not
+    /// denied, and its deployment was funded with `fund_erc20_auto_deploy`.
+    /// The funding is consumed by the deployment.
+    fn auto_deploy_erc20_token(&mut self, nep141: &[u8]) -> Result<Address, AutoDeployError> {
+        let config = Self::get_erc20_auto_deploy_config();
+        if !config.enabled {
+            return Err(AutoDeployError::Disabled);
+        }
+        if Self::is_erc20_auto_deploy_denied(nep141) {
+            return Err(AutoDeployError::Denied);
+        }
+        if !Self::is_erc20_auto_deploy_funded(nep141) {
+            return Err(AutoDeployError::NotFunded);
+        }
+
+        let erc20_token = self
+            .deploy_erc20_token(nep141)
+            .map_err(AutoDeployError::Deploy)?;
+        sdk::remove_storage(&bytes_to_key(KeyPrefix::Erc20AutoDeployFunding, nep141));
+
+        Ok(erc20_token)
+    }
+
+    /// Receive the NEP-141 token of an EVM-native ERC-20 token, unlocking the
+    /// ERC-20 tokens from the escrow to the recipient, and burn them.
+    ///
+    /// The message is a `DepositMessage` or a legacy recipient, as in
+    /// `receive_erc20_tokens`, without a fee or a call. The output is the
+    /// amount of tokens unused.
+    ///
+    /// IMPORTANT: This function should not panic, otherwise it won't
+    /// be possible to return the tokens to the sender.
+    pub fn receive_native_erc20_tokens(&mut self, args: &NEP141FtOnTransferArgs) {
+        let json_amount = JsonValue::from(args.amount).to_bytes();
+        let output_on_fail = json_amount.as_slice();
+
+        let message = if DepositMessage::is_versioned(&args.msg) {
+            unwrap_res_or_finish!(DepositMessage::parse(&args.msg), output_on_fail)
+        } else {
+            // Legacy message format:
+            //      Recipient of the transaction - 40 characters (Address in hex)
+            let message = args.msg.as_bytes();
+            assert_or_finish!(message.len() == 40, output_on_fail);
+            let recipient = unwrap_res_or_finish!(
+                unwrap_res_or_finish!(hex::decode(message), output_on_fail)
+                    .as_slice()
+                    .try_into(),
+                output_on_fail
+            );
+
+            DepositMessage {
+                recipient,
+                fee: U256::zero(),
+                call: None,
+            }
+        };
+        // The tokens are unlocked from the escrow, which neither pays relayers
+        // nor makes calls, so such deposits are returned.
+        assert_or_finish!(
+            message.fee.is_zero() && message.call.is_none(),
+            output_on_fail
+        );
+        let recipient = Address(message.recipient);
+
+        let token = sdk::predecessor_account_id();
+        let erc20_token = unwrap_res_or_finish!(
+            Self::get_native_erc20_from_nep141(&token).ok_or(()),
+            output_on_fail
+        );
+        let escrow =
+            unwrap_res_or_finish!(Self::get_native_erc20_escrow().ok_or(()), output_on_fail);
+
+        unwrap_res_or_finish!(
+            self.unlock_native_erc20(escrow, erc20_token, recipient, args.amount.into()),
+            output_on_fail
+        );
+
+        // The received tokens are burned, since they are unlocked here.
+        let promise = PromiseCreateArgs {
+            // `get_native_erc20_from_nep141` found the token, so it is a valid account id
+            target_account_id: AccountId::try_from(token).unwrap(),
+            method: "burn".to_string(),
+            args: JsonValue::object(vec![("amount", args.amount.into())]).to_bytes(),
+            attached_balance: 1,
+            attached_gas: FT_BURN_GAS,
+        };
+        Self::schedule_promise(PromiseArgs::Create(promise));
+
+        // Everything succeed so return "0"
+        sdk::return_output(&JsonValue::from(0u128).to_bytes());
+    }
+
+    /// Transfers `amount` of the native ERC-20 token `erc20_token` locked in
+    /// the escrow to `recipient`.
+    fn unlock_native_erc20(
+        &mut self,
+        escrow: Address,
+        erc20_token: Address,
+        recipient: Address,
+        amount: U256,
+    ) -> EngineResult<()> {
         let tail = ethabi::encode(&[
+            ethabi::Token::Address(erc20_token),
             ethabi::Token::Address(recipient),
-            ethabi::Token::Uint(args.amount.into()),
+            ethabi::Token::Uint(amount),
         ]);
 
-        let erc20_admin_address = current_address();
+        self.admin_call(escrow, [ESCROW_UNLOCK_SELECTOR, tail.as_slice()].concat())
+    }
+
+    /// Receive an NFT from a NEP-171 token contract and mint the mapped
+    /// ERC-721 token to the recipient.
+    ///
+    /// The ERC-721 token id is the keccak hash of the NEP-171 token id, which
+    /// is stored to be able to transfer the token back when it exits.
+    ///
+    /// The output is a JSON boolean, `true` returning the token to its previous
+    /// owner as specified by NEP-171.
+    ///
+    /// IMPORTANT: This function should not panic, otherwise it won't
+    /// be possible to return the token to the sender.
+    pub fn receive_erc721_token(&mut self, args: &NEP171NftOnTransferArgs) {
+        let return_token = JsonValue::from(true).to_bytes();
+        let output_on_fail = return_token.as_slice();
+
+        // Message format:
+        //      Recipient of the token - 40 characters (Address in hex)
+        let message = args.msg.as_bytes();
+        assert_or_finish!(message.len() == 40, output_on_fail);
+        let recipient = Address(unwrap_res_or_finish!(
+            unwrap_res_or_finish!(hex::decode(message), output_on_fail)
+                .as_slice()
+                .try_into(),
+            output_on_fail
+        ));
+
+        let token = sdk::predecessor_account_id();
+        let erc721_token = Address(unwrap_res_or_finish!(
+            unwrap_res_or_finish!(Self::get_erc721_from_nep171(&token), output_on_fail)
+                .as_slice()
+                .try_into(),
+            output_on_fail
+        ));
+
+        let token_id = U256::from_big_endian(sdk::keccak(args.token_id.as_bytes()).as_bytes());
+        Self::erc721_token_id_map().insert_raw(
+            &[erc721_token.as_bytes(), &u256_to_arr(&token_id)].concat(),
+            args.token_id.as_bytes(),
+        );
+
         unwrap_res_or_finish!(
-            self.call(
-                erc20_admin_address,
-                erc20_token,
-                Wei::zero(),
-                [selector, tail.as_slice()].concat(),
-                u64::MAX,
-                Vec::new(), // TODO: are there values we should put here?
-            )
-            .and_then(|submit_result| {
-                match submit_result.status {
-                    TransactionStatus::Succeed(_) => Ok(()),
-                    TransactionStatus::Revert(bytes) => {
-                        let error_message = crate::prelude::format!(
-                            "Reverted with message: {}",
-                            crate::prelude::String::from_utf8_lossy(&bytes)
-                        );
-                        Err(EngineError {
-                            kind: EngineErrorKind::EvmError(ExitError::Other(
-                                crate::prelude::Cow::from(error_message),
-                            )),
-                            gas_used: submit_result.gas_used,
-                        })
-                    }
-                    TransactionStatus::OutOfFund => Err(EngineError {
-                        kind: EngineErrorKind::EvmError(ExitError::OutOfFund),
-                        gas_used: submit_result.gas_used,
-                    }),
-                    TransactionStatus::OutOfOffset => Err(EngineError {
-                        kind: EngineErrorKind::EvmError(ExitError::OutOfOffset),
-                        gas_used: submit_result.gas_used,
-                    }),
-                    TransactionStatus::OutOfGas => Err(EngineError {
-                        kind: EngineErrorKind::EvmError(ExitError::OutOfGas),
-                        gas_used: submit_result.gas_used,
-                    }),
-                    TransactionStatus::CallTooDeep => Err(EngineError {
-                        kind: EngineErrorKind::EvmError(ExitError::CallTooDeep),
-                        gas_used: submit_result.gas_used,
-                    }),
-                }
-            }),
+            self.mint_bridged_token(erc721_token, recipient, token_id),
             output_on_fail
         );
 
-        // TODO(marX)
-        // Everything succeed so return "0"
-        sdk::return_output(b"\"0\"");
+        // Everything succeed so keep the token
+        sdk::return_output(&JsonValue::from(false).to_bytes());
+    }
+
+    /// Mints `value` of the bridged token `token` to `recipient`, using the
+    /// admin rights the engine holds over the bridged tokens. Both the ERC-20
+    /// and the ERC-721 templates expose `mint(address,uint256)`, `value` being
+    /// the amount and the token id respectively.
+    fn mint_bridged_token(
+        &mut self,
+        token: Address,
+        recipient: Address,
+        value: U256,
+    ) -> EngineResult<()> {
+        let selector = ERC20_MINT_SELECTOR;
+        let tail = ethabi::encode(&[
+            ethabi::Token::Address(recipient),
+            ethabi::Token::Uint(value),
+        ]);
+
+        self.admin_call(token, [selector, tail.as_slice()].concat())
+    }
+
+    /// Sets the name, symbol and decimals of the ERC-20 token mapped to the
+    /// NEP-141 token `nep141`.
+    pub fn set_erc20_metadata(
+        &mut self,
+        nep141: &[u8],
+        metadata: &Erc20Metadata,
+    ) -> Result<(), SetErc20MetadataError> {
+        let erc20_token =
+            Self::get_erc20_from_nep141(nep141).map_err(SetErc20MetadataError::TokenNotFound)?;
+        let input = [
+            ERC20_SET_METADATA_SELECTOR,
+            &ethabi::encode(&[
+                ethabi::Token::String(metadata.name.clone()),
+                ethabi::Token::String(metadata.symbol.clone()),
+                ethabi::Token::Uint(U256::from(metadata.decimals)),
+            ]),
+        ]
+        .concat();
+
+        self.admin_call(Address::from_slice(&erc20_token), input)
+            .map_err(SetErc20MetadataError::Engine)
+    }
+
+    /// Calls the bridged token `token` as its admin, the engine.
+    fn admin_call(&mut self, token: Address, input: Vec<u8>) -> EngineResult<()> {
+        let admin_address = current_address();
+        self.call(
+            admin_address,
+            token,
+            Wei::zero(),
+            input,
+            u64::MAX,
+            Vec::new(), // TODO: are there values we should put here?
+        )
+        .and_then(|submit_result| match submit_result.status {
+            TransactionStatus::Succeed(_) => Ok(()),
+            TransactionStatus::Revert(bytes) => {
+                let error_message = crate::prelude::format!(
+                    "Reverted with message: {}",
+                    crate::prelude::String::from_utf8_lossy(&bytes)
+                );
+                Err(EngineError {
+                    kind: EngineErrorKind::EvmError(ExitError::Other(crate::prelude::Cow::from(
+                        error_message,
+                    ))),
+                    gas_used: submit_result.gas_used,
+                })
+            }
+            TransactionStatus::OutOfFund => Err(EngineError {
+                kind: EngineErrorKind::EvmError(ExitError::OutOfFund),
+                gas_used: submit_result.gas_used,
+            }),
+            TransactionStatus::OutOfOffset => Err(EngineError {
+                kind: EngineErrorKind::EvmError(ExitError::OutOfOffset),
+                gas_used: submit_result.gas_used,
+            }),
+            TransactionStatus::OutOfGas => Err(EngineError {
+                kind: EngineErrorKind::EvmError(ExitError::OutOfGas),
+                gas_used: submit_result.gas_used,
+            }),
+            TransactionStatus::CallTooDeep => Err(EngineError {
+                kind: EngineErrorKind::EvmError(ExitError::CallTooDeep),
+                gas_used: submit_result.gas_used,
+            }),
+        })
+    }
+
+    /// Reverts the effects of an exit precompile whose NEAR promise failed,
+    /// or the unused part of an exit done with `ft_transfer_call`.
+    /// Burned ERC-20 tokens are minted back, locked native ERC-20 tokens are
+    /// unlocked, while exited ETH is moved from the exit precompile back to
+    /// the refund address.
+    pub fn refund_on_error(
+        &mut self,
+        args: RefundCallArgs,
+        result: PromiseResult,
+    ) -> Result<(), RefundError> {
+        let recipient = Address(args.recipient_address);
+        let amount = U256::from_big_endian(&args.amount);
+        let amount = match result {
+            // `ft_transfer_call` resolves to the amount used by the receiver,
+            // the rest was returned to the engine by `ft_resolve_transfer`.
+            PromiseResult::Successful(value) if args.transfer_call => {
+                let used_amount = crate::json::parse_json(&value)
+                    .and_then(|used_amount| u128::try_from(&used_amount).ok());
+                match used_amount {
+                    Some(used_amount) => amount.saturating_sub(U256::from(used_amount)),
+                    // Without a valid result the tokens are assumed to be used.
+                    None => U256::zero(),
+                }
+            }
+            PromiseResult::Successful(_) => U256::zero(),
+            PromiseResult::NotReady | PromiseResult::Failed => amount,
+        };
+        if amount.is_zero() {
+            return Ok(());
+        }
+
+        match args.erc20_address {
+            Some(erc20_address) if args.native_erc20 => {
+                let escrow = Self::get_native_erc20_escrow().ok_or(RefundError::EscrowNotFound)?;
+                self.unlock_native_erc20(escrow, Address(erc20_address), recipient, amount)
+                    .map_err(RefundError::UnlockFailed)?
+            }
+            Some(erc20_address) => self
+                .mint_bridged_token(Address(erc20_address), recipient, amount)
+                .map_err(RefundError::MintFailed)?,
+            None => {
+                let exit_address = Address(args.exit_address);
+                let amount = Wei::new(amount);
+                let exit_balance = Self::get_balance(&exit_address)
+                    .checked_sub(amount)
+                    .ok_or(RefundError::InsufficientExitBalance)?;
+                Self::set_balance(&exit_address, &exit_balance);
+                Self::add_balance(&recipient, amount).map_err(|_| RefundError::BalanceOverflow)?;
+            }
+        }
+
+        EngineEvent::ExitRefunded {
+            recipient: args.recipient_address,
+            erc20_address: args.erc20_address,
+            amount,
+        }
+        .emit();
+
+        Ok(())
     }
 
     pub fn nep141_erc20_map() -> BijectionMap<
@@ -862,12 +1913,42 @@ impl Engine {
         Default::default()
     }
 
+    pub fn nep171_erc721_map() -> BijectionMap<
+        { KeyPrefix::Nep171Erc721Map as KeyPrefixU8 },
+        { KeyPrefix::Erc721Nep171Map as KeyPrefixU8 },
+    > {
+        Default::default()
+    }
+
+    /// Maps the EVM-native ERC-20 tokens to their NEP-141 token.
+    pub fn native_erc20_nep141_map() -> BijectionMap<
+        { KeyPrefix::NativeErc20Nep141Map as KeyPrefixU8 },
+        { KeyPrefix::Nep141NativeErc20Map as KeyPrefixU8 },
+    > {
+        Default::default()
+    }
+
+    /// Maps the id of each bridged ERC-721 token, prefixed by the ERC-721
+    /// address, to the id of the NEP-171 token.
+    fn erc721_token_id_map() -> LookupMap<{ KeyPrefix::Erc721TokenIdMap as KeyPrefixU8 }> {
+        Default::default()
+    }
+
     fn filter_promises_from_logs<T: IntoIterator<Item = Log>>(logs: T) -> Vec<ResultLog> {
-        logs.into_iter()
+        let mut storage_deposit: Balance = 0;
+        let logs = logs
+            .into_iter()
             .filter_map(|log| {
-                if log.address == ExitToNear::ADDRESS || log.address == ExitToEthereum::ADDRESS {
+                if log.address == ExitToNear::ADDRESS
+                    || log.address == ExitToEthereum::ADDRESS
+                    || log.address == CrossContractCall::ADDRESS
+                {
                     if log.topics.is_empty() {
-                        if let Ok(promise) = PromiseCreateArgs::try_from_slice(&log.data) {
+                        if let Ok(promise) = PromiseArgs::try_from_slice(&log.data) {
+                            if log.address == ExitToNear::ADDRESS {
+                                storage_deposit = storage_deposit
+                                    .saturating_add(Self::exit_storage_deposit(&promise));
+                            }
                             Self::schedule_promise(promise);
                         }
                         // do not pass on these "internal logs" to caller
@@ -882,10 +1963,75 @@ impl Engine {
                     Some(log.into())
                 }
             })
-            .collect()
+            .collect();
+
+        // The storage deposits of all the exits are paid with the NEAR attached
+        // to the transaction, never with the balance of the engine.
+        if storage_deposit > sdk::attached_deposit() {
+            sdk::panic_utf8(b"ERR_STORAGE_DEPOSIT_NOT_ATTACHED");
+        }
+        logs
     }
 
-    fn schedule_promise(promise: PromiseCreateArgs) {
+    /// YoctoNEAR attached to the `storage_deposit` calls of an exit promise.
+    fn exit_storage_deposit(promise: &PromiseArgs) -> Balance {
+        let batch = match promise {
+            PromiseArgs::Batch(batch) => batch,
+            PromiseArgs::BatchCallback(promise) => &promise.base,
+            _ => return 0,
+        };
+        batch
+            .actions
+            .iter()
+            .filter(|action| action.method == "storage_deposit")
+            .fold(0, |total: Balance, action| {
+                total.saturating_add(action.attached_balance)
+            })
+    }
+
+    fn schedule_promise(promise: PromiseArgs) {
+        match promise {
+            PromiseArgs::Create(promise) => {
+                Self::schedule_base_promise(&promise);
+            }
+            PromiseArgs::Callback(promise) => {
+                let base_id = Self::schedule_base_promise(&promise.base);
+                Self::schedule_callback(base_id, &promise.callback);
+            }
+            PromiseArgs::Batch(promise) => {
+                Self::schedule_batch_promise(&promise);
+            }
+            PromiseArgs::BatchCallback(promise) => {
+                let base_id = Self::schedule_batch_promise(&promise.base);
+                Self::schedule_callback(base_id, &promise.callback);
+            }
+        }
+    }
+
+    fn schedule_batch_promise(promise: &PromiseBatchArgs) -> u64 {
+        let promise_id = sdk::promise_batch_create(promise.target_account_id.as_bytes());
+        for action in promise.actions.iter() {
+            #[cfg(feature = "log")]
+            sdk::log_utf8(
+                crate::prelude::format!(
+                    "Call contract: {}.{}",
+                    promise.target_account_id,
+                    action.method
+                )
+                .as_bytes(),
+            );
+            sdk::promise_batch_action_function_call(
+                promise_id,
+                action.method.as_bytes(),
+                action.args.as_slice(),
+                action.attached_balance,
+                action.attached_gas,
+            );
+        }
+        promise_id
+    }
+
+    fn schedule_base_promise(promise: &PromiseCreateArgs) -> u64 {
         #[cfg(feature = "log")]
         sdk::log_utf8(
             crate::prelude::format!(
@@ -901,7 +2047,27 @@ impl Engine {
             promise.args.as_slice(),
             promise.attached_balance,
             promise.attached_gas,
+        )
+    }
+
+    fn schedule_callback(base_id: u64, callback: &PromiseCreateArgs) -> u64 {
+        #[cfg(feature = "log")]
+        sdk::log_utf8(
+            crate::prelude::format!(
+                "Callback contract: {}.{}",
+                callback.target_account_id,
+                callback.method
+            )
+            .as_bytes(),
         );
+        sdk::promise_then(
+            base_id,
+            callback.target_account_id.as_bytes(),
+            callback.method.as_bytes(),
+            callback.args.as_slice(),
+            callback.attached_balance,
+            callback.attached_gas,
+        )
     }
 }
 
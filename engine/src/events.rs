@@ -0,0 +1,492 @@
+//! NEP-297 events of the nETH fungible token, of the bridge operations and of the engine.
+//!
+//! The events are logged as `EVENT_JSON:{"data", "event", "standard", "version"}`
+//! whether the `log` feature is enabled or not, so that indexers can follow them.
+use crate::json::JsonValue;
+use crate::prelude::{format, sdk, vec, Balance, EthAddress, String, ToString, Vec, U256};
+
+const EVENT_JSON_PREFIX: &str = "EVENT_JSON:";
+const FT_STANDARD: &str = "nep141";
+const FT_VERSION: &str = "1.0.0";
+const CONNECTOR_STANDARD: &str = "aurora-eth-connector";
+const CONNECTOR_VERSION: &str = "1.0.0";
+const ENGINE_STANDARD: &str = "aurora-engine";
+const ENGINE_VERSION: &str = "1.0.0";
+
+/// Events of the nETH fungible token (NEP-141)
+pub enum FtEvent<'a> {
+    Mint {
+        owner_id: &'a str,
+        amount: Balance,
+        memo: Option<&'a str>,
+    },
+    Burn {
+        owner_id: &'a str,
+        amount: Balance,
+        memo: Option<&'a str>,
+    },
+    Transfer {
+        old_owner_id: &'a str,
+        new_owner_id: &'a str,
+        amount: Balance,
+        memo: Option<&'a str>,
+    },
+}
+
+impl FtEvent<'_> {
+    pub fn emit(&self) {
+        sdk::log(&self.to_json_string());
+    }
+
+    fn to_json_string(&self) -> String {
+        let (event, mut data, memo) = match self {
+            Self::Mint {
+                owner_id,
+                amount,
+                memo,
+            } => (
+                "ft_mint",
+                vec![("owner_id", string(owner_id)), ("amount", balance(*amount))],
+                memo,
+            ),
+            Self::Burn {
+                owner_id,
+                amount,
+                memo,
+            } => (
+                "ft_burn",
+                vec![("owner_id", string(owner_id)), ("amount", balance(*amount))],
+                memo,
+            ),
+            Self::Transfer {
+                old_owner_id,
+                new_owner_id,
+                amount,
+                memo,
+            } => (
+                "ft_transfer",
+                vec![
+                    ("old_owner_id", string(old_owner_id)),
+                    ("new_owner_id", string(new_owner_id)),
+                    ("amount", balance(*amount)),
+                ],
+                memo,
+            ),
+        };
+        if let Some(memo) = memo {
+            data.push(("memo", string(memo)));
+        }
+        event_json(FT_STANDARD, FT_VERSION, event, data)
+    }
+}
+
+/// Events of the bridge operations of the eth-connector
+pub enum ConnectorEvent<'a> {
+    /// The tokens of a deposit were minted
+    DepositFinalized {
+        new_owner_id: &'a str,
+        amount: Balance,
+        fee: Balance,
+        relayer_id: &'a str,
+        proof_key: &'a str,
+    },
+    /// The tokens of a withdrawal were burned, for the recipient to be paid on Ethereum
+    WithdrawalRequested {
+        sender_id: &'a str,
+        recipient_address: EthAddress,
+        amount: Balance,
+        /// Part of `amount` paid to the relayer which finalizes the withdrawal
+        fee: Balance,
+        eth_custodian_address: EthAddress,
+    },
+    /// ETH was minted to an EVM address, backed by the nETH of the current account
+    EvmMint {
+        address: EthAddress,
+        amount: Balance,
+    },
+    /// ETH was burned from an EVM address
+    EvmBurn {
+        address: EthAddress,
+        amount: Balance,
+    },
+    /// A queued withdrawal was cancelled, refunding the NEAR account or the EVM
+    /// address (hex) which made it
+    WithdrawalCancelled {
+        id: u64,
+        refund_id: &'a str,
+        amount: Balance,
+    },
+    /// The metadata of the nETH token was updated
+    FtMetadataUpdated {
+        version: u64,
+        spec: &'a str,
+        name: &'a str,
+        symbol: &'a str,
+        decimals: u8,
+    },
+    /// A NEAR account or an EVM address (hex) was frozen
+    AccountFrozen { target: &'a str, reason: u32 },
+    /// A NEAR account or an EVM address (hex) was unfrozen
+    AccountUnfrozen { target: &'a str },
+}
+
+impl ConnectorEvent<'_> {
+    pub fn emit(&self) {
+        sdk::log(&self.to_json_string());
+    }
+
+    fn to_json_string(&self) -> String {
+        let (event, data) = match self {
+            Self::DepositFinalized {
+                new_owner_id,
+                amount,
+                fee,
+                relayer_id,
+                proof_key,
+            } => (
+                "deposit_finalized",
+                vec![
+                    ("new_owner_id", string(new_owner_id)),
+                    ("amount", balance(*amount)),
+                    ("fee", balance(*fee)),
+                    ("relayer_id", string(relayer_id)),
+                    ("proof_key", string(proof_key)),
+                ],
+            ),
+            Self::WithdrawalRequested {
+                sender_id,
+                recipient_address,
+                amount,
+                fee,
+                eth_custodian_address,
+            } => (
+                "withdrawal_requested",
+                vec![
+                    ("sender_id", string(sender_id)),
+                    ("recipient_address", string(&hex::encode(recipient_address))),
+                    ("amount", balance(*amount)),
+                    ("fee", balance(*fee)),
+                    (
+                        "eth_custodian_address",
+                        string(&hex::encode(eth_custodian_address)),
+                    ),
+                ],
+            ),
+            Self::EvmMint { address, amount } => (
+                "evm_mint",
+                vec![
+                    ("address", string(&hex::encode(address))),
+                    ("amount", balance(*amount)),
+                ],
+            ),
+            Self::EvmBurn { address, amount } => (
+                "evm_burn",
+                vec![
+                    ("address", string(&hex::encode(address))),
+                    ("amount", balance(*amount)),
+                ],
+            ),
+            Self::WithdrawalCancelled {
+                id,
+                refund_id,
+                amount,
+            } => (
+                "withdrawal_cancelled",
+                vec![
+                    ("id", JsonValue::U64(*id)),
+                    ("refund_id", string(refund_id)),
+                    ("amount", balance(*amount)),
+                ],
+            ),
+            Self::FtMetadataUpdated {
+                version,
+                spec,
+                name,
+                symbol,
+                decimals,
+            } => (
+                "ft_metadata_updated",
+                vec![
+                    ("version", JsonValue::U64(*version)),
+                    ("spec", string(spec)),
+                    ("name", string(name)),
+                    ("symbol", string(symbol)),
+                    ("decimals", JsonValue::U64(*decimals as u64)),
+                ],
+            ),
+            Self::AccountFrozen { target, reason } => (
+                "account_frozen",
+                vec![
+                    ("target", string(target)),
+                    ("reason", JsonValue::U64(*reason as u64)),
+                ],
+            ),
+            Self::AccountUnfrozen { target } => {
+                ("account_unfrozen", vec![("target", string(target))])
+            }
+        };
+        event_json(CONNECTOR_STANDARD, CONNECTOR_VERSION, event, data)
+    }
+}
+
+/// Events of the engine
+pub enum EngineEvent<'a> {
+    /// The exit of an ERC-20 token, or of ETH if `erc20_address` is `None`,
+    /// was refunded since its NEAR promise failed or left a part unused
+    ExitRefunded {
+        recipient: EthAddress,
+        erc20_address: Option<EthAddress>,
+        amount: U256,
+    },
+    /// A NEP-141 token was mapped to an ERC-20 token
+    BridgedTokenRegistered { nep141: &'a str, erc20: EthAddress },
+    /// The mapping of a NEP-141 token was removed
+    BridgedTokenUnregistered { nep141: &'a str, erc20: EthAddress },
+    /// A NEP-141 token was mapped to another ERC-20 token
+    BridgedTokenRemapped {
+        nep141: &'a str,
+        previous_erc20: EthAddress,
+        erc20: EthAddress,
+    },
+    /// A NEP-141 token registered before the bridged-token index existed was
+    /// added to it
+    BridgedTokenIndexed { nep141: &'a str, erc20: EthAddress },
+}
+
+impl EngineEvent<'_> {
+    pub fn emit(&self) {
+        sdk::log(&self.to_json_string());
+    }
+
+    fn to_json_string(&self) -> String {
+        let (event, data) = match self {
+            Self::ExitRefunded {
+                recipient,
+                erc20_address,
+                amount,
+            } => (
+                "exit_refunded",
+                vec![
+                    ("recipient", string(&hex::encode(recipient))),
+                    (
+                        "erc20_address",
+                        erc20_address
+                            .map_or(JsonValue::Null, |address| string(&hex::encode(address))),
+                    ),
+                    ("amount", string(&amount.to_string())),
+                ],
+            ),
+            Self::BridgedTokenRegistered { nep141, erc20 } => (
+                "bridged_token_registered",
+                vec![
+                    ("nep141", string(nep141)),
+                    ("erc20", string(&hex::encode(erc20))),
+                ],
+            ),
+            Self::BridgedTokenUnregistered { nep141, erc20 } => (
+                "bridged_token_unregistered",
+                vec![
+                    ("nep141", string(nep141)),
+                    ("erc20", string(&hex::encode(erc20))),
+                ],
+            ),
+            Self::BridgedTokenRemapped {
+                nep141,
+                previous_erc20,
+                erc20,
+            } => (
+                "bridged_token_remapped",
+                vec![
+                    ("nep141", string(nep141)),
+                    ("previous_erc20", string(&hex::encode(previous_erc20))),
+                    ("erc20", string(&hex::encode(erc20))),
+                ],
+            ),
+            Self::BridgedTokenIndexed { nep141, erc20 } => (
+                "bridged_token_indexed",
+                vec![
+                    ("nep141", string(nep141)),
+                    ("erc20", string(&hex::encode(erc20))),
+                ],
+            ),
+        };
+        event_json(ENGINE_STANDARD, ENGINE_VERSION, event, data)
+    }
+}
+
+fn string(value: &str) -> JsonValue {
+    JsonValue::from(value)
+}
+
+/// Balances are strings, since they don't fit in JSON numbers
+fn balance(value: Balance) -> JsonValue {
+    JsonValue::from(value)
+}
+
+fn event_json(standard: &str, version: &str, event: &str, data: Vec<(&str, JsonValue)>) -> String {
+    let event = JsonValue::object(vec![
+        ("standard", string(standard)),
+        ("version", string(version)),
+        ("event", string(event)),
+        ("data", JsonValue::Array(vec![JsonValue::object(data)])),
+    ]);
+    format!("{}{}", EVENT_JSON_PREFIX, event)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::parse_json;
+
+    fn parse_event(log: &str) -> JsonValue {
+        let json = log.strip_prefix(EVENT_JSON_PREFIX).unwrap();
+        parse_json(json.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn test_ft_event_json() {
+        let log = FtEvent::Transfer {
+            old_owner_id: "alice.near",
+            new_owner_id: "bob.near",
+            amount: u128::MAX,
+            memo: Some("say \"hi\""),
+        }
+        .to_json_string();
+        assert_eq!(
+            log,
+            format!(
+                r#"EVENT_JSON:{{"data": [{{"amount": "{}", "memo": "say \"hi\"", "new_owner_id": "bob.near", "old_owner_id": "alice.near"}}], "event": "ft_transfer", "standard": "nep141", "version": "1.0.0"}}"#,
+                u128::MAX
+            )
+        );
+
+        let event = parse_event(
+            &FtEvent::Mint {
+                owner_id: "alice.near",
+                amount: 10,
+                memo: None,
+            }
+            .to_json_string(),
+        );
+        assert_eq!(event.string("event").unwrap(), "ft_mint");
+        match event {
+            JsonValue::Object(fields) => match fields.get("data") {
+                Some(JsonValue::Array(data)) => {
+                    assert_eq!(data[0].string("owner_id").unwrap(), "alice.near");
+                    assert_eq!(data[0].u128("amount").unwrap(), 10);
+                    assert!(data[0].string("memo").is_err());
+                }
+                _ => panic!("Invalid event data"),
+            },
+            _ => panic!("Invalid event"),
+        }
+    }
+
+    #[test]
+    fn test_connector_event_json() {
+        let event = parse_event(
+            &ConnectorEvent::WithdrawalRequested {
+                sender_id: "alice.near",
+                recipient_address: [1u8; 20],
+                amount: 100,
+                fee: 10,
+                eth_custodian_address: [2u8; 20],
+            }
+            .to_json_string(),
+        );
+        assert_eq!(event.string("standard").unwrap(), CONNECTOR_STANDARD);
+        assert_eq!(event.string("event").unwrap(), "withdrawal_requested");
+        match event {
+            JsonValue::Object(fields) => match fields.get("data") {
+                Some(JsonValue::Array(data)) => {
+                    assert_eq!(data[0].string("sender_id").unwrap(), "alice.near");
+                    assert_eq!(
+                        data[0].string("recipient_address").unwrap(),
+                        "01".repeat(20)
+                    );
+                    assert_eq!(
+                        data[0].string("eth_custodian_address").unwrap(),
+                        "02".repeat(20)
+                    );
+                    assert_eq!(data[0].u128("amount").unwrap(), 100);
+                    assert_eq!(data[0].u128("fee").unwrap(), 10);
+                }
+                _ => panic!("Invalid event data"),
+            },
+            _ => panic!("Invalid event"),
+        }
+
+        // The EVM balances are not NEP-141 balances
+        let log = ConnectorEvent::EvmMint {
+            address: [1u8; 20],
+            amount: 100,
+        }
+        .to_json_string();
+        assert_eq!(
+            log,
+            format!(
+                r#"EVENT_JSON:{{"data": [{{"address": "{}", "amount": "100"}}], "event": "evm_mint", "standard": "{}", "version": "{}"}}"#,
+                "01".repeat(20),
+                CONNECTOR_STANDARD,
+                CONNECTOR_VERSION
+            )
+        );
+    }
+
+    #[test]
+    fn test_engine_event_json() {
+        let log = EngineEvent::ExitRefunded {
+            recipient: [1u8; 20],
+            erc20_address: None,
+            amount: U256::MAX,
+        }
+        .to_json_string();
+        assert_eq!(
+            log,
+            format!(
+                r#"EVENT_JSON:{{"data": [{{"amount": "{}", "erc20_address": null, "recipient": "{}"}}], "event": "exit_refunded", "standard": "{}", "version": "{}"}}"#,
+                U256::MAX,
+                "01".repeat(20),
+                ENGINE_STANDARD,
+                ENGINE_VERSION
+            )
+        );
+
+        let event = parse_event(
+            &EngineEvent::ExitRefunded {
+                recipient: [1u8; 20],
+                erc20_address: Some([2u8; 20]),
+                amount: U256::from(100),
+            }
+            .to_json_string(),
+        );
+        assert_eq!(event.string("standard").unwrap(), ENGINE_STANDARD);
+        match event {
+            JsonValue::Object(fields) => match fields.get("data") {
+                Some(JsonValue::Array(data)) => {
+                    assert_eq!(data[0].string("erc20_address").unwrap(), "02".repeat(20));
+                    assert_eq!(data[0].u128("amount").unwrap(), 100);
+                }
+                _ => panic!("Invalid event data"),
+            },
+            _ => panic!("Invalid event"),
+        }
+
+        let log = EngineEvent::BridgedTokenRemapped {
+            nep141: "token.near",
+            previous_erc20: [1u8; 20],
+            erc20: [2u8; 20],
+        }
+        .to_json_string();
+        assert_eq!(
+            log,
+            format!(
+                r#"EVENT_JSON:{{"data": [{{"erc20": "{}", "nep141": "token.near", "previous_erc20": "{}"}}], "event": "bridged_token_remapped", "standard": "{}", "version": "{}"}}"#,
+                "02".repeat(20),
+                "01".repeat(20),
+                ENGINE_STANDARD,
+                ENGINE_VERSION
+            )
+        );
+    }
+}
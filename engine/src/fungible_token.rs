@@ -1,5 +1,6 @@
 use crate::connector::NO_DEPOSIT;
 use crate::engine::Engine;
+use crate::events::FtEvent;
 use crate::json::{parse_json, JsonValue};
 use crate::parameters::{NEP141FtOnTransferArgs, ResolveTransferCallArgs, StorageBalance};
 use crate::prelude::account_id::AccountId;
@@ -11,6 +12,11 @@ use crate::prelude::{
 
 const GAS_FOR_RESOLVE_TRANSFER: Gas = 5_000_000_000_000;
 const GAS_FOR_FT_ON_TRANSFER: Gas = 10_000_000_000_000;
+/// The `spec` of the NEP-148 metadata
+pub const FT_METADATA_SPEC: &str = "ft-1.0.0";
+pub const FT_METADATA_MAX_DECIMALS: u8 = 24;
+/// Maximum length in bytes of the icon data URL
+pub const FT_METADATA_MAX_ICON_LENGTH: usize = 8 * 1024;
 
 #[derive(Debug, Default, BorshDeserialize, BorshSerialize)]
 pub struct FungibleToken {
@@ -25,12 +31,14 @@ pub struct FungibleToken {
 }
 
 #[derive(BorshDeserialize, BorshSerialize, Clone)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug, PartialEq))]
 pub struct FungibleTokenMetadata {
     pub spec: String,
     pub name: String,
     pub symbol: String,
     pub icon: Option<String>,
     pub reference: Option<String>,
+    /// SHA-256 hash of the JSON of `reference`, so exactly 32 bytes
     pub reference_hash: Option<[u8; 32]>,
     pub decimals: u8,
 }
@@ -49,6 +57,71 @@ impl Default for FungibleTokenMetadata {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FtMetadataError {
+    InvalidSpec,
+    EmptyName,
+    EmptySymbol,
+    InvalidDecimals,
+    InvalidIcon,
+    IconTooLarge,
+    MissingReferenceHash,
+    InvalidReferenceHash,
+}
+
+impl AsRef<[u8]> for FtMetadataError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidSpec => b"ERR_FT_METADATA_INVALID_SPEC",
+            Self::EmptyName => b"ERR_FT_METADATA_EMPTY_NAME",
+            Self::EmptySymbol => b"ERR_FT_METADATA_EMPTY_SYMBOL",
+            Self::InvalidDecimals => b"ERR_FT_METADATA_INVALID_DECIMALS",
+            Self::InvalidIcon => b"ERR_FT_METADATA_INVALID_ICON",
+            Self::IconTooLarge => b"ERR_FT_METADATA_ICON_TOO_LARGE",
+            Self::MissingReferenceHash => b"ERR_FT_METADATA_MISSING_REFERENCE_HASH",
+            Self::InvalidReferenceHash => b"ERR_FT_METADATA_INVALID_REFERENCE_HASH",
+        }
+    }
+}
+
+impl FungibleTokenMetadata {
+    /// Checks the metadata against NEP-148. The icon must be a data URL, since
+    /// it's stored in the contract state.
+    pub fn validate(&self) -> Result<(), FtMetadataError> {
+        if self.spec != FT_METADATA_SPEC {
+            return Err(FtMetadataError::InvalidSpec);
+        }
+        if self.name.is_empty() {
+            return Err(FtMetadataError::EmptyName);
+        }
+        if self.symbol.is_empty() {
+            return Err(FtMetadataError::EmptySymbol);
+        }
+        if self.decimals > FT_METADATA_MAX_DECIMALS {
+            return Err(FtMetadataError::InvalidDecimals);
+        }
+        if let Some(icon) = &self.icon {
+            if !icon.starts_with("data:") {
+                return Err(FtMetadataError::InvalidIcon);
+            }
+            if icon.len() > FT_METADATA_MAX_ICON_LENGTH {
+                return Err(FtMetadataError::IconTooLarge);
+            }
+        }
+        // The hash is exactly 32 bytes by its type, so it's only left to check
+        // that it hashes a reference, and isn't a placeholder
+        match (&self.reference, &self.reference_hash) {
+            (Some(_), None) => return Err(FtMetadataError::MissingReferenceHash),
+            (None, Some(_)) => return Err(FtMetadataError::InvalidReferenceHash),
+            (Some(_), Some(hash)) if hash == &[0u8; 32] => {
+                return Err(FtMetadataError::InvalidReferenceHash)
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
 impl From<FungibleTokenMetadata> for JsonValue {
     fn from(metadata: FungibleTokenMetadata) -> Self {
         let mut kvs = BTreeMap::new();
@@ -165,7 +238,7 @@ impl FungibleToken {
         sender_id: &AccountId,
         receiver_id: &AccountId,
         amount: Balance,
-        #[allow(unused_variables)] memo: &Option<String>,
+        memo: &Option<String>,
     ) {
         assert_ne!(
             sender_id, receiver_id,
@@ -184,10 +257,13 @@ impl FungibleToken {
             sender_id,
             receiver_id
         ));
-        #[cfg(feature = "log")]
-        if let Some(memo) = memo {
-            sdk::log(&crate::prelude::format!("Memo: {}", memo));
+        FtEvent::Transfer {
+            old_owner_id: sender_id.as_ref(),
+            new_owner_id: receiver_id.as_ref(),
+            amount,
+            memo: memo.as_deref(),
         }
+        .emit();
     }
 
     pub fn internal_register_account(&mut self, account_id: &AccountId) {
@@ -230,13 +306,12 @@ impl FungibleToken {
         if &sender_id != receiver_id {
             self.internal_transfer_eth_on_near(&sender_id, receiver_id, amount, memo);
         }
-        let data1: String = NEP141FtOnTransferArgs {
+        let data1 = JsonValue::from(&NEP141FtOnTransferArgs {
             amount,
             msg,
             sender_id: sender_id.clone(),
-        }
-        .try_into()
-        .unwrap();
+        })
+        .to_bytes();
 
         let data2 = ResolveTransferCallArgs {
             receiver_id: receiver_id.clone(),
@@ -249,7 +324,7 @@ impl FungibleToken {
         let promise0 = sdk::promise_create(
             receiver_id.as_bytes(),
             b"ft_on_transfer",
-            data1.as_bytes(),
+            &data1,
             NO_DEPOSIT,
             GAS_FOR_FT_ON_TRANSFER,
         );
@@ -318,11 +393,24 @@ impl FungibleToken {
                         receiver_id,
                         sender_id
                     ));
+                    FtEvent::Transfer {
+                        old_owner_id: receiver_id.as_ref(),
+                        new_owner_id: sender_id.as_ref(),
+                        amount: refund_amount,
+                        memo: Some("refund"),
+                    }
+                    .emit();
                     (amount - refund_amount, 0)
                 } else {
                     // Sender's account was deleted, so we need to burn tokens.
                     self.total_eth_supply_on_near -= refund_amount;
                     sdk::log!("The account of the sender was deleted");
+                    FtEvent::Burn {
+                        owner_id: receiver_id.as_ref(),
+                        amount: refund_amount,
+                        memo: Some("refund"),
+                    }
+                    .emit();
                     (amount, refund_amount)
                 };
             }
@@ -352,8 +440,13 @@ impl FungibleToken {
             let balance = u128::try_from_slice(&balance[..]).unwrap();
             if balance == 0 || force {
                 self.accounts_remove(&account_id);
-                self.total_eth_supply_on_near -= balance;
-                let amount = self.storage_balance_bounds().min + 1;
+                self.total_eth_supply_on_near = self
+                    .total_eth_supply_on_near
+                    .checked_sub(balance)
+                    .expect("ERR_TOTAL_SUPPLY_OVERFLOW");
+                // Only the storage deposit actually paid by the account is refunded,
+                // accounts registered by transfers and deposits paid nothing
+                let amount = self.storage_deposit_remove(&account_id) + 1;
                 let promise0 = sdk::promise_batch_create(&account_id_key);
                 sdk::promise_batch_action_transfer(promise0, amount);
                 Some((account_id.clone(), balance))
@@ -389,9 +482,8 @@ impl FungibleToken {
         }
     }
 
-    pub fn storage_balance_of(&self, account_id: &AccountId) -> StorageBalance {
+    pub fn storage_balance_of(&self, account_id: &AccountId) -> Option<StorageBalance> {
         self.internal_storage_balance_of(account_id)
-            .unwrap_or_default()
     }
 
     // `registration_only` doesn't affect the implementation for vanilla fungible token.
@@ -417,6 +509,7 @@ impl FungibleToken {
             }
 
             self.internal_register_account(account_id);
+            self.storage_deposit_insert(account_id, min_balance);
             let refund = amount - min_balance;
             if refund > 0 {
                 let promise0 = sdk::promise_batch_create(&sdk::predecessor_account_id());
@@ -456,6 +549,8 @@ impl FungibleToken {
                 .checked_add(1)
                 .expect("ERR_ACCOUNTS_COUNTER_OVERFLOW");
             sdk::write_storage(&key, &accounts_counter.to_le_bytes());
+            // No storage deposit is paid, unless registered by `storage_deposit`
+            self.storage_deposit_insert(account_id, 0);
         }
         sdk::save_contract(&Self::account_to_key(account_id), &amount);
     }
@@ -478,6 +573,33 @@ impl FungibleToken {
         sdk::read_storage(&Self::account_to_key(account_id))
     }
 
+    /// Records the storage deposit paid for the registration of an account
+    fn storage_deposit_insert(&self, account_id: &AccountId, amount: Balance) {
+        sdk::save_contract(&Self::storage_deposit_to_key(account_id), &amount);
+    }
+
+    /// Removes the storage deposit paid for the registration of an account.
+    /// Accounts registered before the deposits were recorded are assumed to have
+    /// paid the minimum, as they were refunded before.
+    fn storage_deposit_remove(&self, account_id: &AccountId) -> Balance {
+        let key = Self::storage_deposit_to_key(account_id);
+        let amount = sdk::read_storage(&key)
+            .map(|amount| Balance::try_from_slice(&amount).unwrap())
+            .unwrap_or_else(|| self.storage_balance_bounds().min);
+        sdk::remove_storage(&key);
+        amount
+    }
+
+    /// Storage deposit key
+    fn storage_deposit_to_key(account_id: &AccountId) -> Vec<u8> {
+        let mut key = storage::bytes_to_key(
+            storage::KeyPrefix::EthConnector,
+            &[storage::EthConnectorStorageId::StorageDeposits as u8],
+        );
+        key.extend_from_slice(account_id.as_bytes());
+        key
+    }
+
     /// Fungible token key
     fn account_to_key(account_id: &AccountId) -> Vec<u8> {
         let mut key = storage::bytes_to_key(
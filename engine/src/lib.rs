@@ -10,6 +10,8 @@ extern crate alloc;
 #[cfg(not(feature = "std"))]
 extern crate core;
 
+#[macro_use]
+mod macros;
 mod map;
 #[cfg(feature = "meta-call")]
 pub mod meta_parsing;
@@ -22,8 +24,9 @@ pub mod admin_controlled;
 pub mod connector;
 pub mod deposit_event;
 pub mod engine;
+pub mod events;
 pub mod fungible_token;
-pub mod json;
+pub use aurora_engine_types::json;
 pub mod log_entry;
 mod prelude;
 
@@ -72,10 +75,17 @@ mod contract {
     #[cfg(feature = "evm_bully")]
     use crate::parameters::{BeginBlockArgs, BeginChainArgs};
     use crate::parameters::{
-        DeployErc20TokenArgs, FunctionCallArgs, GetErc20FromNep141CallArgs, GetStorageAtArgs,
-        InitCallArgs, IsUsedProofCallArgs, NEP141FtOnTransferArgs, NewCallArgs,
-        PauseEthConnectorCallArgs, SetContractDataCallArgs, SubmitResult, TransactionStatus,
-        TransferCallCallArgs, ViewCallArgs,
+        BridgeLimits, CancelWithdrawalArgs, CrossContractCallPermissionArgs, DeployErc20TokenArgs,
+        DeployErc721TokenArgs, DepositAndCall, Erc20AutoDeployConfig, Erc20AutoDeployDenyArgs,
+        Erc20Metadata, FreezeArgs, FreezeTarget, FunctionCallArgs, FundErc20AutoDeployArgs,
+        GetBridgedTokensArgs, GetErc20FromNep141CallArgs, GetErc721FromNep171CallArgs,
+        GetStorageAtArgs, IndexBridgedTokensArgs, InitCallArgs, IsUsedProofCallArgs,
+        MigrateUsedProofArgs, NEP141FtOnTransferArgs, NEP171NftOnTransferArgs,
+        NativeErc20DeployedCallbackArgs, NewCallArgs, PauseEthConnectorCallArgs,
+        ReconcileSupplyArgs, RegisterNativeErc20Args, ReleaseDepositArgs, ReleaseWithdrawalArgs,
+        RemapTokenArgs, RotateEthCustodianArgs, SetContractDataCallArgs, SetDepositEventSchemaArgs,
+        SetNativeErc20FactoryArgs, SubmitResult, SyncErc20MetadataCallArgs, TransactionStatus,
+        TransferCallCallArgs, UnregisterTokenArgs, ViewCallArgs,
     };
 
     use crate::json::parse_json;
@@ -84,7 +94,10 @@ mod contract {
     };
     use crate::prelude::storage::{bytes_to_key, KeyPrefix};
     use crate::prelude::types::{u256_to_arr, ERR_FAILED_PARSE};
-    use crate::prelude::{sdk, Address, ToString, TryFrom, TryInto, H160, H256, U256};
+    use crate::prelude::{
+        sdk, Address, EvmCallbackArgs, PromiseResult, RawH256, RefundCallArgs, ToString, TryFrom,
+        TryInto, Vec, H256, U256,
+    };
 
     const CODE_KEY: &[u8; 4] = b"CODE";
     const CODE_STAGE_KEY: &[u8; 10] = b"CODE_STAGE";
@@ -200,6 +213,37 @@ mod contract {
         // TODO: charge for storage
     }
 
+    /// Forwards the result of a promise scheduled through the cross-contract
+    /// call precompile to its EVM callback.
+    #[no_mangle]
+    pub extern "C" fn evm_callback() {
+        sdk::assert_private_call();
+
+        let args: EvmCallbackArgs = sdk::read_input_borsh().sdk_unwrap();
+        let mut engine = Engine::new(Address(args.sender)).sdk_unwrap();
+        Engine::evm_callback(&mut engine, args)
+            .map(|res| res.try_to_vec().sdk_expect("ERR_SERIALIZE"))
+            .sdk_process();
+    }
+
+    /// Callback of the exit precompile promises, reverting the exit if the
+    /// promise failed, or the part of it left unused by `ft_transfer_call`.
+    #[no_mangle]
+    pub extern "C" fn refund_on_error() {
+        sdk::assert_private_call();
+
+        // This function should only be called as the callback of
+        // exactly one promise.
+        if sdk::promise_results_count() != 1 {
+            sdk::panic_utf8(b"ERR_PROMISE_COUNT");
+        }
+        let result = sdk::promise_result(0);
+
+        let args: RefundCallArgs = sdk::read_input_borsh().sdk_unwrap();
+        let mut engine = Engine::new(current_address()).sdk_unwrap();
+        engine.refund_on_error(args, result).sdk_unwrap();
+    }
+
     /// Process signed Ethereum transaction.
     /// Must match CHAIN_ID to make sure it's signed for given chain vs replayed from another chain.
     #[no_mangle]
@@ -343,6 +387,129 @@ mod contract {
         );
     }
 
+    /// Allow or forbid an EVM address to schedule NEAR function calls through
+    /// the cross-contract call precompile.
+    #[no_mangle]
+    pub extern "C" fn set_cross_contract_call_permission() {
+        let state = Engine::get_state().sdk_unwrap();
+        require_owner_only(&state);
+
+        let args: CrossContractCallPermissionArgs = sdk::read_input_borsh().sdk_unwrap();
+        Engine::set_cross_contract_call_permission(&Address(args.address), args.allowed);
+    }
+
+    /// Configure the automatic deployment of the ERC-20 token of unmapped
+    /// NEP-141 tokens on their first deposit.
+    #[no_mangle]
+    pub extern "C" fn set_erc20_auto_deploy_config() {
+        let state = Engine::get_state().sdk_unwrap();
+        require_owner_only(&state);
+
+        let config: Erc20AutoDeployConfig = sdk::read_input_borsh().sdk_unwrap();
+        Engine::set_erc20_auto_deploy_config(&config);
+    }
+
+    /// Add or remove a NEP-141 token from the deny-list of the automatic
+    /// ERC-20 deployment.
+    #[no_mangle]
+    pub extern "C" fn set_erc20_auto_deploy_denied() {
+        let state = Engine::get_state().sdk_unwrap();
+        require_owner_only(&state);
+
+        let args: Erc20AutoDeployDenyArgs = sdk::read_input_borsh().sdk_unwrap();
+        Engine::set_erc20_auto_deploy_denied(args.nep141.as_bytes(), args.denied);
+    }
+
+    /// Pay in advance with the attached NEAR for the automatic deployment of
+    /// the ERC-20 token of a NEP-141 token, which is made on its first deposit.
+    #[no_mangle]
+    pub extern "C" fn fund_erc20_auto_deploy() {
+        let args: FundErc20AutoDeployArgs = sdk::read_input_borsh().sdk_unwrap();
+        Engine::fund_erc20_auto_deploy(args.nep141.as_bytes(), sdk::attached_deposit())
+            .sdk_unwrap();
+    }
+
+    /// Remove the mapping of a NEP-141 token to its ERC-20 token.
+    #[no_mangle]
+    pub extern "C" fn unregister_token() {
+        let state = Engine::get_state().sdk_unwrap();
+        require_owner_only(&state);
+
+        let args: UnregisterTokenArgs = sdk::read_input_borsh().sdk_unwrap();
+        let mut engine = Engine::new(predecessor_address()).sdk_unwrap();
+        engine
+            .unregister_token(args.nep141.as_bytes(), args.force)
+            .sdk_unwrap();
+    }
+
+    /// Map a NEP-141 token to another, already deployed, ERC-20 token.
+    #[no_mangle]
+    pub extern "C" fn remap_token() {
+        let state = Engine::get_state().sdk_unwrap();
+        require_owner_only(&state);
+
+        let args: RemapTokenArgs = sdk::read_input_borsh().sdk_unwrap();
+        let mut engine = Engine::new(predecessor_address()).sdk_unwrap();
+        engine
+            .remap_token(args.nep141.as_bytes(), Address(args.erc20), args.force)
+            .sdk_unwrap();
+    }
+
+    /// Add the NEP-141 tokens registered before the bridged-token index existed to it.
+    #[no_mangle]
+    pub extern "C" fn index_bridged_tokens() {
+        let state = Engine::get_state().sdk_unwrap();
+        require_owner_only(&state);
+
+        let args: IndexBridgedTokensArgs = sdk::read_input_borsh().sdk_unwrap();
+        Engine::index_bridged_tokens(&args.nep141s).sdk_unwrap();
+    }
+
+    /// Set the NEAR factory deploying the NEP-141 tokens of EVM-native ERC-20 tokens.
+    #[no_mangle]
+    pub extern "C" fn set_native_erc20_factory() {
+        let state = Engine::get_state().sdk_unwrap();
+        require_owner_only(&state);
+
+        let args: SetNativeErc20FactoryArgs = sdk::read_input_borsh().sdk_unwrap();
+        Engine::set_native_erc20_factory(&args.factory);
+    }
+
+    /// Bridge an EVM-native ERC-20 token to NEAR, reusing the given NEP-141
+    /// token or deploying a new one with the factory. The attached deposit
+    /// pays for the deployment.
+    #[no_mangle]
+    pub extern "C" fn register_native_erc20() {
+        let state = Engine::get_state().sdk_unwrap();
+        require_owner_only(&state);
+
+        let args: RegisterNativeErc20Args = sdk::read_input_borsh().sdk_unwrap();
+        let mut engine = Engine::new(predecessor_address()).sdk_unwrap();
+        let nep141 = engine
+            .register_native_erc20(Address(args.erc20), args.nep141, sdk::attached_deposit())
+            .sdk_unwrap();
+        sdk::return_output(nep141.as_bytes());
+    }
+
+    /// Callback of the NEP-141 factory, mapping the native ERC-20 token to
+    /// the deployed NEP-141 token.
+    #[no_mangle]
+    pub extern "C" fn native_erc20_deployed_callback() {
+        sdk::assert_private_call();
+
+        // This function should only be called as the callback of
+        // exactly one promise.
+        if sdk::promise_results_count() != 1 {
+            sdk::panic_utf8(b"ERR_PROMISE_COUNT");
+        }
+        if let PromiseResult::Successful(_) = sdk::promise_result(0) {
+            let args: NativeErc20DeployedCallbackArgs = sdk::read_input_borsh().sdk_unwrap();
+            Engine::insert_native_erc20(Address(args.erc20), &args.nep141).sdk_unwrap();
+        } else {
+            sdk::panic_utf8(b"ERR_NATIVE_ERC20_DEPLOY_FAILED");
+        }
+    }
+
     /// Allow receiving NEP141 tokens to the EVM contract.
     ///
     /// This function returns the amount of tokens to return to the sender.
@@ -357,9 +524,11 @@ mod contract {
             .try_into()
             .sdk_unwrap();
 
-        if sdk::predecessor_account_id() == sdk::current_account_id() {
-            let engine = Engine::new(predecessor_address()).sdk_unwrap();
-            EthConnectorContract::get_instance().ft_on_transfer(&engine, &args);
+        let token = sdk::predecessor_account_id();
+        if token == sdk::current_account_id() {
+            EthConnectorContract::get_instance().ft_on_transfer(&mut engine, &args);
+        } else if Engine::get_native_erc20_from_nep141(&token).is_some() {
+            engine.receive_native_erc20_tokens(&args);
         } else {
             engine.receive_erc20_tokens(&args);
         }
@@ -373,31 +542,74 @@ mod contract {
             DeployErc20TokenArgs::try_from_slice(&sdk::read_input()).sdk_expect("ERR_ARG_PARSE");
 
         let mut engine = Engine::new(predecessor_address()).sdk_unwrap();
+        let address = engine
+            .deploy_erc20_token(args.nep141.as_bytes())
+            .sdk_unwrap();
+        sdk::return_output(&address.as_bytes().try_to_vec().sdk_expect("ERR_SERIALIZE"));
+
+        // TODO: charge for storage
+    }
+
+    /// Update the name, symbol and decimals of a bridged ERC-20 token from the
+    /// `ft_metadata` of its NEP-141 token. Anybody may call it, because the
+    /// metadata can only come from the NEP-141 token itself.
+    #[no_mangle]
+    pub extern "C" fn sync_erc20_metadata() {
+        let args: SyncErc20MetadataCallArgs =
+            SyncErc20MetadataCallArgs::try_from_slice(&sdk::read_input())
+                .sdk_expect("ERR_ARG_PARSE");
+        Engine::get_erc20_from_nep141(args.nep141.as_bytes()).sdk_unwrap();
+        Engine::sync_erc20_metadata(&args.nep141);
+    }
+
+    /// Callback of `ft_metadata`, setting the metadata of the ERC-20 token.
+    #[no_mangle]
+    pub extern "C" fn set_erc20_metadata_callback() {
+        sdk::assert_private_call();
 
-        let erc20_admin_address = current_address();
-        let erc20_contract = include_bytes!("../../etc/eth-contracts/res/EvmErc20.bin");
-
-        let deploy_args = ethabi::encode(&[
-            ethabi::Token::String("Empty".to_string()),
-            ethabi::Token::String("EMPTY".to_string()),
-            ethabi::Token::Uint(ethabi::Uint::from(0)),
-            ethabi::Token::Address(erc20_admin_address),
-        ]);
-
-        let address = match Engine::deploy_code_with_input(
-            &mut engine,
-            (&[erc20_contract, deploy_args.as_slice()].concat()).to_vec(),
-        ) {
-            Ok(result) => match result.status {
-                TransactionStatus::Succeed(ret) => H160(ret.as_slice().try_into().unwrap()),
-                other => sdk::panic_utf8(other.as_ref()),
-            },
-            Err(e) => sdk::panic_utf8(e.as_ref()),
+        // This function should only be called as the callback of
+        // exactly one promise.
+        if sdk::promise_results_count() != 1 {
+            sdk::panic_utf8(b"ERR_PROMISE_COUNT");
+        }
+        let metadata: Erc20Metadata = match sdk::promise_result(0) {
+            PromiseResult::Successful(value) => parse_json(&value)
+                .sdk_expect("ERR_FT_METADATA")
+                .try_into()
+                .sdk_unwrap(),
+            _ => sdk::panic_utf8(b"ERR_FT_METADATA_FAILED"),
         };
 
-        sdk::log!(crate::prelude::format!("Deployed ERC-20 in Aurora at: {:#?}", address).as_str());
+        let args: SyncErc20MetadataCallArgs = sdk::read_input_borsh().sdk_unwrap();
+        let mut engine = Engine::new(current_address()).sdk_unwrap();
         engine
-            .register_token(address.as_bytes(), args.nep141.as_bytes())
+            .set_erc20_metadata(args.nep141.as_bytes(), &metadata)
+            .sdk_unwrap();
+    }
+
+    /// NEP-171 receiver, minting the ERC-721 token mapped to the received NFT.
+    #[no_mangle]
+    pub extern "C" fn nft_on_transfer() {
+        let mut engine = Engine::new(predecessor_address()).sdk_unwrap();
+
+        let args: NEP171NftOnTransferArgs = parse_json(sdk::read_input().as_slice())
+            .sdk_unwrap()
+            .try_into()
+            .sdk_unwrap();
+
+        engine.receive_erc721_token(&args);
+    }
+
+    /// Deploy ERC721 token mapped to a NEP171
+    #[no_mangle]
+    pub extern "C" fn deploy_erc721_token() {
+        // Id of the NEP171 token in Near
+        let args: DeployErc721TokenArgs =
+            DeployErc721TokenArgs::try_from_slice(&sdk::read_input()).sdk_expect("ERR_ARG_PARSE");
+
+        let mut engine = Engine::new(predecessor_address()).sdk_unwrap();
+        let address = engine
+            .deploy_erc721_token(args.nep171.as_bytes())
             .sdk_unwrap();
         sdk::return_output(&address.as_bytes().try_to_vec().sdk_expect("ERR_SERIALIZE"));
 
@@ -415,6 +627,13 @@ mod contract {
         sdk::return_output(&result.try_to_vec().sdk_expect("ERR_SERIALIZE"));
     }
 
+    #[no_mangle]
+    pub extern "C" fn get_cross_contract_call_permission() {
+        let address = sdk::read_input_arr20().sdk_unwrap();
+        let allowed = Engine::has_cross_contract_call_permission(&Address(address));
+        sdk::return_output(&allowed.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
     #[no_mangle]
     pub extern "C" fn get_block_hash() {
         let block_height = sdk::read_input_borsh().sdk_unwrap();
@@ -518,7 +737,17 @@ mod contract {
 
     #[no_mangle]
     pub extern "C" fn finish_deposit() {
-        EthConnectorContract::get_instance().finish_deposit();
+        let engine = Engine::new(predecessor_address()).sdk_unwrap();
+        EthConnectorContract::get_instance().finish_deposit(&engine);
+    }
+
+    /// Makes the EVM call of a deposit from Ethereum, once `finish_deposit` minted it
+    #[no_mangle]
+    pub extern "C" fn deposit_call() {
+        sdk::assert_private_call();
+        let args = DepositAndCall::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        let mut engine = Engine::new(predecessor_address()).sdk_unwrap();
+        engine.deposit_call(Address(args.recipient), &args.call);
     }
 
     #[no_mangle]
@@ -530,6 +759,51 @@ mod contract {
         sdk::return_output(&res[..]);
     }
 
+    #[no_mangle]
+    pub extern "C" fn get_used_proof() {
+        let key = RawH256::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        let used_proof = EthConnectorContract::get_used_proof(&key);
+        sdk::return_output(&used_proof.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn migrate_used_proofs() {
+        sdk::assert_private_call();
+        let args = Vec::<MigrateUsedProofArgs>::try_from_slice(&sdk::read_input())
+            .expect(ERR_FAILED_PARSE);
+
+        EthConnectorContract::migrate_used_proofs(args);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn finish_used_proofs_migration() {
+        sdk::assert_private_call();
+        EthConnectorContract::finish_used_proofs_migration();
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_freeze() {
+        let target = FreezeTarget::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        let freeze = EthConnectorContract::get_freeze(&target);
+        sdk::return_output(&freeze.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn freeze() {
+        sdk::assert_private_call();
+        let args = FreezeArgs::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+
+        EthConnectorContract::freeze(args);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn unfreeze() {
+        sdk::assert_private_call();
+        let target = FreezeTarget::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+
+        EthConnectorContract::unfreeze(target);
+    }
+
     #[no_mangle]
     pub extern "C" fn ft_total_supply() {
         EthConnectorContract::get_instance().ft_total_eth_supply_on_near();
@@ -571,9 +845,10 @@ mod contract {
         // Check is payable
         sdk::assert_one_yocto();
 
-        let args = TransferCallCallArgs::from(
+        let args = TransferCallCallArgs::try_from(
             parse_json(&sdk::read_input()).expect_utf8(ERR_FAILED_PARSE.as_bytes()),
-        );
+        )
+        .sdk_unwrap();
         EthConnectorContract::get_instance().ft_transfer_call(args);
     }
 
@@ -587,6 +862,16 @@ mod contract {
         EthConnectorContract::get_instance().storage_withdraw()
     }
 
+    #[no_mangle]
+    pub extern "C" fn storage_unregister() {
+        EthConnectorContract::get_instance().storage_unregister()
+    }
+
+    #[no_mangle]
+    pub extern "C" fn storage_balance_bounds() {
+        EthConnectorContract::get_instance().storage_balance_bounds()
+    }
+
     #[no_mangle]
     pub extern "C" fn storage_balance_of() {
         EthConnectorContract::get_instance().storage_balance_of()
@@ -608,6 +893,87 @@ mod contract {
         EthConnectorContract::get_instance().set_paused_flags(args);
     }
 
+    #[no_mangle]
+    pub extern "C" fn release_withdrawal() {
+        let args =
+            ReleaseWithdrawalArgs::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        EthConnectorContract::get_instance().release_withdrawal(args);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn cancel_withdrawal() {
+        sdk::assert_private_call();
+
+        let args =
+            CancelWithdrawalArgs::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        EthConnectorContract::get_instance().cancel_withdrawal(args);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn release_deposit() {
+        let args = ReleaseDepositArgs::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        EthConnectorContract::get_instance().release_deposit(args);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn finish_released_deposit() {
+        let engine = Engine::new(predecessor_address()).sdk_unwrap();
+        EthConnectorContract::get_instance().finish_released_deposit(&engine);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_bridge_limits() {
+        let limits = EthConnectorContract::get_bridge_limits();
+        sdk::return_output(&limits.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn set_bridge_limits() {
+        sdk::assert_private_call();
+
+        let args = BridgeLimits::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        EthConnectorContract::set_bridge_limits(args);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn set_deposit_event_schema() {
+        sdk::assert_private_call();
+
+        let args =
+            SetDepositEventSchemaArgs::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        EthConnectorContract::set_deposit_event_schema(args);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_supply_audit() {
+        let audit = EthConnectorContract::get_instance().get_supply_audit();
+        sdk::return_output(&audit.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn reconcile_supply() {
+        sdk::assert_private_call();
+
+        let args = ReconcileSupplyArgs::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        let audit = EthConnectorContract::get_instance().reconcile_supply(args);
+        sdk::return_output(&audit.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_eth_custodians() {
+        let custodians = EthConnectorContract::get_instance().get_eth_custodians();
+        sdk::return_output(&custodians.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn rotate_eth_custodian() {
+        sdk::assert_private_call();
+
+        let args =
+            RotateEthCustodianArgs::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        EthConnectorContract::get_instance().rotate_eth_custodian(args);
+    }
+
     #[no_mangle]
     pub extern "C" fn get_accounts_counter() {
         EthConnectorContract::get_instance().get_accounts_counter()
@@ -636,6 +1002,82 @@ mod contract {
         );
     }
 
+    #[no_mangle]
+    pub extern "C" fn get_bridged_tokens() {
+        let args: GetBridgedTokensArgs = sdk::read_input_borsh().sdk_unwrap();
+        let tokens = Engine::get_bridged_tokens(args.from_index, args.limit);
+        sdk::return_output(&tokens.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_erc20_auto_deploy_config() {
+        let config = Engine::get_erc20_auto_deploy_config();
+        sdk::return_output(&config.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn is_erc20_auto_deploy_denied() {
+        let denied = Engine::is_erc20_auto_deploy_denied(&sdk::read_input());
+        sdk::return_output(&denied.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn is_erc20_auto_deploy_funded() {
+        let funded = Engine::is_erc20_auto_deploy_funded(&sdk::read_input());
+        sdk::return_output(&funded.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_erc721_from_nep171() {
+        let args: GetErc721FromNep171CallArgs =
+            GetErc721FromNep171CallArgs::try_from_slice(&sdk::read_input())
+                .sdk_expect("ERR_ARG_PARSE");
+
+        sdk::return_output(
+            Engine::get_erc721_from_nep171(args.nep171.as_bytes())
+                .sdk_unwrap()
+                .as_slice(),
+        );
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_nep171_from_erc721() {
+        sdk::return_output(
+            Engine::nep171_erc721_map()
+                .lookup_right(sdk::read_input().as_slice())
+                .sdk_expect("ERC721_NOT_FOUND")
+                .as_slice(),
+        );
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_native_erc20_escrow() {
+        sdk::return_output(
+            Engine::get_native_erc20_escrow()
+                .sdk_expect("ERR_NATIVE_ERC20_ESCROW_NOT_FOUND")
+                .as_bytes(),
+        );
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_nep141_from_native_erc20() {
+        let address = sdk::read_input_arr20().sdk_unwrap();
+        sdk::return_output(
+            Engine::get_nep141_from_native_erc20(&Address(address))
+                .sdk_expect("NATIVE_ERC20_NOT_FOUND")
+                .as_bytes(),
+        );
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_native_erc20_from_nep141() {
+        sdk::return_output(
+            Engine::get_native_erc20_from_nep141(&sdk::read_input())
+                .sdk_expect("NEP141_NOT_FOUND")
+                .as_bytes(),
+        );
+    }
+
     #[no_mangle]
     pub extern "C" fn ft_metadata() {
         let metadata: FungibleTokenMetadata =
@@ -644,10 +1086,29 @@ mod contract {
         sdk::return_output(json_data.to_string().as_bytes())
     }
 
+    #[no_mangle]
+    pub extern "C" fn set_ft_metadata() {
+        // Only the owner can set the nETH metadata
+        sdk::assert_private_call();
+
+        let metadata =
+            FungibleTokenMetadata::try_from_slice(&sdk::read_input()).expect(ERR_FAILED_PARSE);
+        EthConnectorContract::set_ft_metadata(metadata);
+    }
+
+    #[no_mangle]
+    pub extern "C" fn get_ft_metadata_version() {
+        let version = EthConnectorContract::get_ft_metadata_version();
+        sdk::return_output(&version.try_to_vec().sdk_expect("ERR_SERIALIZE"));
+    }
+
     #[cfg(feature = "integration-test")]
     #[no_mangle]
-    pub extern "C" fn verify_log_entry() {
-        sdk::log!("Call from verify_log_entry");
+    pub extern "C" fn assert_ethclient_hash() {
+        sdk::log!("Call from assert_ethclient_hash");
+        // Same borsh arguments as the rainbow bridge EthProver
+        let _args: crate::parameters::AssertEthclientHashCallArgs =
+            sdk::read_input_borsh().sdk_expect("ERR_ARGS");
         let data = true.try_to_vec().unwrap();
         sdk::return_output(&data[..]);
     }
@@ -0,0 +1,22 @@
+macro_rules! unwrap_res_or_finish {
+    ($e:expr, $output:expr) => {
+        match $e {
+            Ok(v) => v,
+            Err(_e) => {
+                #[cfg(feature = "log")]
+                sdk::log(crate::prelude::format!("{:?}", _e).as_str());
+                sdk::return_output($output);
+                return;
+            }
+        }
+    };
+}
+
+macro_rules! assert_or_finish {
+    ($e:expr, $output:expr) => {
+        if !$e {
+            sdk::return_output($output);
+            return;
+        }
+    };
+}
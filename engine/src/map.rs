@@ -71,7 +71,6 @@ impl<const LR: KeyPrefixU8, const RL: KeyPrefixU8> BijectionMap<LR, RL> {
         Self::right_to_left().get_raw(value_right)
     }
 
-    #[allow(dead_code)]
     pub fn remove_left(&self, value_left: &[u8]) {
         if let Some(value_right) = Self::left_to_right().remove_raw(value_left) {
             Self::right_to_left().remove_raw(value_right.as_slice());
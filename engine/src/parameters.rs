@@ -1,10 +1,11 @@
 use crate::admin_controlled::PausedMask;
+use crate::deposit_event::DepositedEventSchema;
 use crate::fungible_token::FungibleTokenMetadata;
-use crate::json::{JsonError, JsonValue};
+use crate::json::{parse_json, JsonFieldError, JsonValue};
 use crate::prelude::account_id::AccountId;
 use crate::prelude::{
-    format, Balance, BorshDeserialize, BorshSerialize, EthAddress, RawAddress, RawH256, RawU256,
-    SdkUnwrap, String, ToString, TryFrom, Vec,
+    vec, Balance, BorshDeserialize, BorshSerialize, EthAddress, RawAddress, RawH256, RawU256,
+    String, TryFrom, TryInto, Vec, U256,
 };
 use crate::proof::Proof;
 use evm::backend::Log;
@@ -155,6 +156,132 @@ pub struct DeployErc20TokenArgs {
 /// Borsh-encoded parameters for `get_erc20_from_nep141` function.
 pub type GetErc20FromNep141CallArgs = DeployErc20TokenArgs;
 
+/// Borsh-encoded parameters for the `unregister_token` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct UnregisterTokenArgs {
+    pub nep141: AccountId,
+    /// Skip the check that the ERC-20 token has no supply left.
+    pub force: bool,
+}
+
+/// Borsh-encoded parameters for the `index_bridged_tokens` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct IndexBridgedTokensArgs {
+    /// NEP-141 tokens registered before the bridged-token index existed.
+    pub nep141s: Vec<AccountId>,
+}
+
+/// Borsh-encoded parameters for the `remap_token` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RemapTokenArgs {
+    pub nep141: AccountId,
+    /// ERC-20 token to map `nep141` to instead.
+    pub erc20: RawAddress,
+    /// Skip the check that the previous ERC-20 token has no supply left.
+    pub force: bool,
+}
+
+/// Borsh-encoded parameters for the `get_bridged_tokens` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct GetBridgedTokensArgs {
+    pub from_index: u64,
+    pub limit: u64,
+}
+
+/// Borsh-encoded NEP-141 and ERC-20 token pair returned by `get_bridged_tokens`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Eq, PartialEq)]
+pub struct BridgedTokenPair {
+    pub nep141: AccountId,
+    pub erc20: RawAddress,
+}
+
+/// Borsh-encoded parameters for the `sync_erc20_metadata` function, also used
+/// by the `set_erc20_metadata_callback` it schedules.
+pub type SyncErc20MetadataCallArgs = DeployErc20TokenArgs;
+
+/// Metadata set on a bridged ERC-20 token, read from the result of the
+/// NEP-141 `ft_metadata`.
+pub struct Erc20Metadata {
+    pub name: String,
+    pub symbol: String,
+    pub decimals: u8,
+}
+
+impl TryFrom<JsonValue> for Erc20Metadata {
+    type Error = JsonFieldError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            name: value.field("name")?,
+            symbol: value.field("symbol")?,
+            decimals: value.field("decimals")?,
+        })
+    }
+}
+
+/// Borsh-encoded configuration of the automatic deployment of the ERC-20
+/// token of an unmapped NEP-141 on its first `ft_on_transfer`.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Default, Eq, PartialEq)]
+pub struct Erc20AutoDeployConfig {
+    /// Whether unmapped NEP-141 tokens get their ERC-20 deployed on the first deposit.
+    pub enabled: bool,
+    /// YoctoNEAR paid with `fund_erc20_auto_deploy` for the deployment of the
+    /// ERC-20 token of a NEP-141, covering its storage.
+    pub deploy_fee: Balance,
+}
+
+/// Borsh-encoded parameters for the `fund_erc20_auto_deploy` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct FundErc20AutoDeployArgs {
+    pub nep141: AccountId,
+}
+
+/// Borsh-encoded parameters for the `set_erc20_auto_deploy_denied` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct Erc20AutoDeployDenyArgs {
+    pub nep141: AccountId,
+    /// Whether the ERC-20 of `nep141` may not be deployed automatically.
+    pub denied: bool,
+}
+
+/// Borsh-encoded parameters for `deploy_erc721_token` function.
+#[derive(BorshSerialize, BorshDeserialize, Debug, Eq, PartialEq)]
+pub struct DeployErc721TokenArgs {
+    pub nep171: AccountId,
+}
+
+/// Borsh-encoded parameters for `get_erc721_from_nep171` function.
+pub type GetErc721FromNep171CallArgs = DeployErc721TokenArgs;
+
+/// Borsh-encoded parameters for the `set_native_erc20_factory` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SetNativeErc20FactoryArgs {
+    /// NEAR factory deploying the NEP-141 tokens of EVM-native ERC-20 tokens.
+    pub factory: AccountId,
+}
+
+/// Borsh-encoded parameters for the `register_native_erc20` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RegisterNativeErc20Args {
+    /// EVM-native ERC-20 token to bridge to NEAR.
+    pub erc20: RawAddress,
+    /// Already deployed NEP-141 token to reuse, on which the engine must be
+    /// allowed to mint and burn. A new one is deployed by the factory if not set.
+    pub nep141: Option<AccountId>,
+}
+
+/// Borsh-encoded parameters for the `native_erc20_deployed_callback`.
+pub type NativeErc20DeployedCallbackArgs = BridgedTokenPair;
+
+/// Borsh-encoded parameters for the `set_cross_contract_call_permission` function.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CrossContractCallPermissionArgs {
+    /// EVM address of the contract calling the cross-contract call precompile.
+    pub address: RawAddress,
+    /// Whether the address is allowed to schedule NEAR promises.
+    pub allowed: bool,
+}
+
 /// Borsh-encoded parameters for the `get_storage_at` function.
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct GetStorageAtArgs {
@@ -205,27 +332,192 @@ pub struct NEP141FtOnTransferArgs {
 }
 
 impl TryFrom<JsonValue> for NEP141FtOnTransferArgs {
-    type Error = JsonError;
+    type Error = JsonFieldError;
 
     fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
         Ok(Self {
-            sender_id: AccountId::try_from(value.string("sender_id")?)
-                .map_err(|_| JsonError::InvalidString)?,
-            amount: value.u128("amount")?,
-            msg: value.string("msg")?,
+            sender_id: value.field("sender_id")?,
+            amount: value.field("amount")?,
+            msg: value.field("msg")?,
         })
     }
 }
 
-impl From<NEP141FtOnTransferArgs> for String {
-    fn from(value: NEP141FtOnTransferArgs) -> Self {
-        format!(
-            r#"{{"sender_id": "{}", "amount": "{}", "msg": "{}"}}"#,
-            value.sender_id,
-            value.amount,
-            // Escape message to avoid json injection attacks
-            value.msg.replace("\\", "\\\\").replace("\"", "\\\"")
-        )
+impl From<&NEP141FtOnTransferArgs> for JsonValue {
+    fn from(value: &NEP141FtOnTransferArgs) -> Self {
+        JsonValue::object(vec![
+            ("sender_id", (&value.sender_id).into()),
+            ("amount", value.amount.into()),
+            ("msg", value.msg.as_str().into()),
+        ])
+    }
+}
+
+/// Version of the `DepositMessage` format.
+pub const DEPOSIT_MESSAGE_VERSION: u64 = 1;
+
+/// EVM gas limit of the call following a deposit, unless given.
+pub const DEFAULT_DEPOSIT_CALL_GAS_LIMIT: u64 = 300_000;
+
+/// Maximum EVM gas limit of the call following a deposit, so that it fits in
+/// the NEAR gas of its receipt.
+pub const MAX_DEPOSIT_CALL_GAS_LIMIT: u64 = 800_000;
+
+/// Message of an `ft_on_transfer` deposit into Aurora in the versioned JSON
+/// format, as opposed to the legacy formats which are still accepted:
+/// `{"version": 1, "recipient": "<hex>", "fee": "<amount>", "fee_token": "deposit",
+/// "call": {"contract": "<hex>", "input": "<hex>", "gas_limit": <EVM gas>}}`.
+/// Only `version` and `recipient` are required. The fee is always taken from the
+/// deposited tokens, which is the only `fee_token` accepted.
+/// It is also the message of the deposits from Ethereum into Aurora, as in
+/// `aurora:<message>`, whose fee is always the one of the deposit event.
+pub struct DepositMessage {
+    /// EVM address receiving the deposited tokens.
+    pub recipient: RawAddress,
+    /// Fee paid to the relayer of the deposit.
+    pub fee: U256,
+    /// EVM call made once the tokens are deposited.
+    pub call: Option<DepositCall>,
+}
+
+/// EVM call following a deposit, made on behalf of the NEP-141 token account
+/// which sent the tokens, or of the recipient of a deposit from Ethereum.
+#[derive(BorshSerialize, BorshDeserialize)]
+#[cfg_attr(not(target_arch = "wasm32"), derive(Debug))]
+pub struct DepositCall {
+    pub contract: RawAddress,
+    pub input: Vec<u8>,
+    /// EVM gas limit of the call, at most `MAX_DEPOSIT_CALL_GAS_LIMIT`.
+    pub gas_limit: u64,
+}
+
+pub enum DepositMessageError {
+    InvalidJson,
+    Json(JsonFieldError),
+    UnsupportedVersion,
+    InvalidAddress,
+    InvalidHex,
+    InvalidFeeToken,
+    InvalidFee,
+    InvalidGasLimit,
+}
+
+impl AsRef<[u8]> for DepositMessageError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidJson => b"ERR_INVALID_DEPOSIT_MESSAGE",
+            Self::Json(e) => e.as_ref(),
+            Self::UnsupportedVersion => b"ERR_UNSUPPORTED_DEPOSIT_MESSAGE_VERSION",
+            Self::InvalidAddress => b"ERR_INVALID_ETH_ADDRESS",
+            Self::InvalidHex => b"ERR_INVALID_HEX",
+            Self::InvalidFeeToken => b"ERR_INVALID_FEE_TOKEN",
+            Self::InvalidFee => b"ERR_INVALID_FEE",
+            Self::InvalidGasLimit => b"ERR_INVALID_DEPOSIT_CALL_GAS_LIMIT",
+        }
+    }
+}
+
+impl core::fmt::Debug for DepositMessageError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(core::str::from_utf8(self.as_ref()).unwrap())
+    }
+}
+
+impl From<JsonFieldError> for DepositMessageError {
+    fn from(e: JsonFieldError) -> Self {
+        Self::Json(e)
+    }
+}
+
+impl DepositMessage {
+    /// Whether `msg` is in the versioned format, rather than a legacy one.
+    pub fn is_versioned(msg: &str) -> bool {
+        msg.starts_with('{')
+    }
+
+    pub fn parse(msg: &str) -> Result<Self, DepositMessageError> {
+        parse_json(msg.as_bytes())
+            .ok_or(DepositMessageError::InvalidJson)?
+            .try_into()
+    }
+}
+
+/// Decodes hex data, with or without the `0x` prefix.
+fn parse_hex(value: &str) -> Result<Vec<u8>, DepositMessageError> {
+    let value = value.strip_prefix("0x").unwrap_or(value);
+    hex::decode(value).map_err(|_| DepositMessageError::InvalidHex)
+}
+
+fn parse_hex_address(value: &str) -> Result<RawAddress, DepositMessageError> {
+    parse_hex(value)?
+        .as_slice()
+        .try_into()
+        .map_err(|_| DepositMessageError::InvalidAddress)
+}
+
+impl TryFrom<JsonValue> for DepositMessage {
+    type Error = DepositMessageError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        if value.field::<u64>("version")? != DEPOSIT_MESSAGE_VERSION {
+            return Err(DepositMessageError::UnsupportedVersion);
+        }
+        let recipient = parse_hex_address(&value.field::<String>("recipient")?)?;
+        // The fee is a decimal string, as the amounts of NEP-141 tokens.
+        let fee = match value.field::<Option<String>>("fee")? {
+            None => U256::zero(),
+            Some(fee) => U256::from_dec_str(&fee).map_err(|_| DepositMessageError::InvalidFee)?,
+        };
+        match value.field::<Option<String>>("fee_token")?.as_deref() {
+            None | Some("deposit") => (),
+            Some(_) => return Err(DepositMessageError::InvalidFeeToken),
+        }
+        let call = match value.field::<Option<JsonValue>>("call")? {
+            None => None,
+            Some(call) => {
+                let field = |key: &str| call.field::<String>(key).map_err(|e| e.at_field("call"));
+                let gas_limit = call
+                    .field::<Option<u64>>("gas_limit")
+                    .map_err(|e| e.at_field("call"))?
+                    .unwrap_or(DEFAULT_DEPOSIT_CALL_GAS_LIMIT);
+                if gas_limit > MAX_DEPOSIT_CALL_GAS_LIMIT {
+                    return Err(DepositMessageError::InvalidGasLimit);
+                }
+                Some(DepositCall {
+                    contract: parse_hex_address(&field("contract")?)?,
+                    input: parse_hex(&field("input")?)?,
+                    gas_limit,
+                })
+            }
+        };
+
+        Ok(Self {
+            recipient,
+            fee,
+            call,
+        })
+    }
+}
+
+/// Parameters of the `nft_on_transfer` function called by NEP-171 tokens
+/// on `nft_transfer_call`.
+pub struct NEP171NftOnTransferArgs {
+    pub sender_id: AccountId,
+    pub previous_owner_id: AccountId,
+    pub token_id: String,
+    pub msg: String,
+}
+
+impl TryFrom<JsonValue> for NEP171NftOnTransferArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(value: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sender_id: value.field("sender_id")?,
+            previous_owner_id: value.field("previous_owner_id")?,
+            token_id: value.field("token_id")?,
+            msg: value.field("msg")?,
+        })
     }
 }
 
@@ -246,14 +538,23 @@ pub struct IsUsedProofCallArgs {
 }
 
 /// withdraw result for eth-connector
-#[derive(BorshSerialize)]
-#[cfg_attr(not(target_arch = "wasm32"), derive(BorshDeserialize))]
+#[derive(BorshSerialize, BorshDeserialize)]
 pub struct WithdrawResult {
     pub amount: Balance,
     pub recipient_id: RawAddress,
     pub eth_custodian_address: RawAddress,
 }
 
+/// withdraw result for eth-connector of the withdrawals paying a fee, whose
+/// layout extends the one of `WithdrawResult` kept for the withdrawals without
+/// a fee
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct WithdrawWithFeeResult {
+    pub result: WithdrawResult,
+    /// Part of `amount` the custodian pays to the relayer which finalizes the withdrawal
+    pub fee: Balance,
+}
+
 /// Fungible token storage balance
 #[derive(Default)]
 pub struct StorageBalance {
@@ -263,13 +564,11 @@ pub struct StorageBalance {
 
 impl StorageBalance {
     pub fn to_json_bytes(&self) -> Vec<u8> {
-        format!(
-            "{{\"total\": \"{}\", \"available\": \"{}\"}}",
-            self.total.to_string(),
-            self.available.to_string()
-        )
-        .as_bytes()
-        .to_vec()
+        JsonValue::object(vec![
+            ("total", self.total.into()),
+            ("available", self.available.into()),
+        ])
+        .to_bytes()
     }
 }
 
@@ -284,6 +583,24 @@ pub struct ResolveTransferCallArgs {
 /// Finish deposit NEAR eth-connector call args
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct FinishDepositCallArgs {
+    pub new_owner_id: AccountId,
+    pub amount: Balance,
+    pub proof_key: RawH256,
+    pub relayer_id: AccountId,
+    pub fee: Balance,
+    pub msg: Option<Vec<u8>>,
+    /// Ethereum block of the proof
+    pub block_number: u64,
+    /// Recipient of the deposit event
+    pub recipient: String,
+    /// EVM call made by the recipient on Aurora once the tokens are minted
+    pub deposit_call: Option<DepositAndCall>,
+}
+
+/// Finish deposit NEAR eth-connector call args, as scheduled before the proofs
+/// were keyed by their hash. Parsed for the callbacks still in flight.
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct LegacyFinishDepositCallArgs {
     pub new_owner_id: AccountId,
     pub amount: Balance,
     pub proof_key: String,
@@ -292,6 +609,64 @@ pub struct FinishDepositCallArgs {
     pub msg: Option<Vec<u8>>,
 }
 
+/// Deposit from Ethereum into an EVM call, made by the recipient
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct DepositAndCall {
+    pub recipient: RawAddress,
+    pub call: DepositCall,
+}
+
+/// Deposit which consumed a bridge proof
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UsedProof {
+    /// Ethereum block of the proof
+    pub block_number: u64,
+    /// Recipient of the deposit event
+    pub recipient: String,
+    pub amount: Balance,
+    /// NEAR block the deposit was finalized in
+    pub finalized_at: u64,
+}
+
+/// Used proof stored with its legacy key, to be stored by its hash
+#[derive(BorshSerialize, BorshDeserialize, Clone)]
+pub struct MigrateUsedProofArgs {
+    /// Proof which was used, whose hash is the key it is stored with
+    pub proof: Proof,
+    pub used_proof: UsedProof,
+}
+
+/// NEAR account or EVM address which can be frozen in the eth-connector
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub enum FreezeTarget {
+    NearAccount(AccountId),
+    EvmAddress(RawAddress),
+}
+
+/// Freeze of a NEAR account or EVM address, which blocks its transfers,
+/// withdrawals and exits
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Freeze {
+    /// Code of the reason of the freeze, as defined by the owner
+    pub reason: u32,
+    /// NEAR block the target was frozen in
+    pub frozen_at: u64,
+}
+
+/// Freeze a NEAR account or EVM address
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FreezeArgs {
+    pub target: FreezeTarget,
+    pub reason: u32,
+}
+
+/// Prover `assert_ethclient_hash` call args
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AssertEthclientHashCallArgs {
+    pub block_number: u64,
+    pub expected_block_hash: RawH256,
+}
+
 /// Deposit ETH args
 #[derive(Default, BorshDeserialize, BorshSerialize, Clone)]
 pub struct DepositEthCallArgs {
@@ -329,14 +704,16 @@ pub struct TransferCallCallArgs {
     pub msg: String,
 }
 
-impl From<JsonValue> for TransferCallCallArgs {
-    fn from(v: JsonValue) -> Self {
-        Self {
-            receiver_id: AccountId::try_from(v.string("receiver_id").sdk_unwrap()).sdk_unwrap(),
-            amount: v.u128("amount").sdk_unwrap(),
-            memo: v.string("memo").ok(),
-            msg: v.string("msg").sdk_unwrap(),
-        }
+impl TryFrom<JsonValue> for TransferCallCallArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(v: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            receiver_id: v.field("receiver_id")?,
+            amount: v.field("amount")?,
+            memo: v.field("memo")?,
+            msg: v.field("msg")?,
+        })
     }
 }
 
@@ -346,11 +723,13 @@ pub struct StorageBalanceOfCallArgs {
     pub account_id: crate::prelude::account_id::AccountId,
 }
 
-impl From<JsonValue> for StorageBalanceOfCallArgs {
-    fn from(v: JsonValue) -> Self {
-        Self {
-            account_id: AccountId::try_from(v.string("account_id").sdk_unwrap()).sdk_unwrap(),
-        }
+impl TryFrom<JsonValue> for StorageBalanceOfCallArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(v: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            account_id: v.field("account_id")?,
+        })
     }
 }
 
@@ -361,14 +740,14 @@ pub struct StorageDepositCallArgs {
     pub registration_only: Option<bool>,
 }
 
-impl From<JsonValue> for StorageDepositCallArgs {
-    fn from(v: JsonValue) -> Self {
-        Self {
-            account_id: v
-                .string("account_id")
-                .map_or(None, |acc| AccountId::try_from(acc).ok()),
-            registration_only: v.bool("registration_only").ok(),
-        }
+impl TryFrom<JsonValue> for StorageDepositCallArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(v: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            account_id: v.field("account_id")?,
+            registration_only: v.field("registration_only")?,
+        })
     }
 }
 
@@ -378,11 +757,29 @@ pub struct StorageWithdrawCallArgs {
     pub amount: Option<u128>,
 }
 
-impl From<JsonValue> for StorageWithdrawCallArgs {
-    fn from(v: JsonValue) -> Self {
-        Self {
-            amount: v.u128("amount").ok(),
-        }
+impl TryFrom<JsonValue> for StorageWithdrawCallArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(v: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            amount: v.field("amount")?,
+        })
+    }
+}
+
+/// storage_unregister eth-connector call args
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct StorageUnregisterCallArgs {
+    pub force: Option<bool>,
+}
+
+impl TryFrom<JsonValue> for StorageUnregisterCallArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(v: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            force: v.field("force")?,
+        })
     }
 }
 
@@ -394,13 +791,15 @@ pub struct TransferCallArgs {
     pub memo: Option<String>,
 }
 
-impl From<JsonValue> for TransferCallArgs {
-    fn from(v: JsonValue) -> Self {
-        Self {
-            receiver_id: AccountId::try_from(v.string("receiver_id").sdk_unwrap()).sdk_unwrap(),
-            amount: v.u128("amount").sdk_unwrap(),
-            memo: v.string("memo").ok(),
-        }
+impl TryFrom<JsonValue> for TransferCallArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(v: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            receiver_id: v.field("receiver_id")?,
+            amount: v.field("amount")?,
+            memo: v.field("memo")?,
+        })
     }
 }
 
@@ -415,11 +814,13 @@ pub struct BalanceOfEthCallArgs {
     pub address: EthAddress,
 }
 
-impl From<JsonValue> for BalanceOfCallArgs {
-    fn from(v: JsonValue) -> Self {
-        Self {
-            account_id: AccountId::try_from(v.string("account_id").sdk_unwrap()).sdk_unwrap(),
-        }
+impl TryFrom<JsonValue> for BalanceOfCallArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(v: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            account_id: v.field("account_id")?,
+        })
     }
 }
 
@@ -428,24 +829,120 @@ pub struct RegisterRelayerCallArgs {
     pub address: EthAddress,
 }
 
+/// Rotate Ethereum custodian args
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct RotateEthCustodianArgs {
+    pub eth_custodian_address: String,
+    /// Ethereum block from which the deposits to the new custodian are accepted.
+    pub activation_height: u64,
+    /// Ethereum block from which the deposits to the previous custodians are rejected.
+    pub deprecation_height: u64,
+}
+
+/// Accept or reject a schema of the deposit events of a custodian
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct SetDepositEventSchemaArgs {
+    pub eth_custodian_address: String,
+    pub schema: DepositedEventSchema,
+    pub accepted: bool,
+}
+
+/// Caps on the withdrawals of a period
+#[derive(BorshSerialize, BorshDeserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct WithdrawCaps {
+    /// Cap on the withdrawals of all the accounts.
+    pub global: Option<Balance>,
+    /// Cap on the withdrawals of each account.
+    pub per_account: Option<Balance>,
+}
+
+/// Eth-connector bridge limits, none of them being set by default
+#[derive(BorshSerialize, BorshDeserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct BridgeLimits {
+    /// Length in blocks of the rolling window over which the withdrawals are capped.
+    pub period_blocks: u64,
+    /// Caps on the withdrawals of NEAR accounts, by account id.
+    pub withdraw_from_near: WithdrawCaps,
+    /// Caps on the withdrawals from Aurora, by EVM sender.
+    pub withdraw_from_aurora: WithdrawCaps,
+    /// Maximum amount of a single deposit.
+    pub max_deposit: Option<Balance>,
+    /// Delay in blocks before the withdrawals over the caps and the deposits
+    /// above the maximum are released, or `None` to reject them. Rejected
+    /// deposits do not consume their proof, so they can be retried.
+    pub queue_delay_blocks: Option<u64>,
+    /// Maximum drift of the supply audit above which the withdrawals are refused.
+    pub max_supply_drift: Option<Balance>,
+}
+
+/// Audit of the ETH supply, returned by `get_supply_audit`
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SupplyAudit {
+    pub total_eth_supply_on_near: Balance,
+    pub total_eth_supply_on_aurora: Balance,
+    /// Sum of the balances of the EVM accounts, as last reported to `reconcile_supply`
+    /// and updated with the ETH minted and burned by the bridge since
+    pub evm_balances: RawU256,
+    /// Total amount deposited from Ethereum
+    pub deposited: Balance,
+    /// Total amount withdrawn to Ethereum
+    pub withdrawn: Balance,
+    /// Total amount burned by unregistering accounts with a balance
+    pub burned: Balance,
+    /// Difference between the EVM balances and the supply on Aurora
+    pub evm_drift: Balance,
+    /// Difference between the supply on NEAR and the deposits minus the
+    /// withdrawals and burns. The ETH on Aurora is backed by the nETH of the current
+    /// account, so it's already part of the supply on NEAR.
+    pub bridge_drift: Balance,
+}
+
+/// Reconcile supply args
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReconcileSupplyArgs {
+    /// Sum of the balances of the EVM accounts, recomputed off-chain from the state
+    pub evm_balances: RawU256,
+}
+
+/// Release queued withdrawal args
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReleaseWithdrawalArgs {
+    pub id: u64,
+}
+
+/// Cancel queued withdrawal args
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct CancelWithdrawalArgs {
+    pub id: u64,
+}
+
+/// Release queued deposit args
+#[derive(BorshSerialize, BorshDeserialize)]
+pub struct ReleaseDepositArgs {
+    pub id: u64,
+}
+
 #[derive(BorshSerialize, BorshDeserialize)]
 pub struct PauseEthConnectorCallArgs {
     pub paused_mask: PausedMask,
 }
 
-impl From<JsonValue> for ResolveTransferCallArgs {
-    fn from(v: JsonValue) -> Self {
-        Self {
-            sender_id: AccountId::try_from(v.string("sender_id").sdk_unwrap()).sdk_unwrap(),
-            receiver_id: AccountId::try_from(v.string("receiver_id").sdk_unwrap()).sdk_unwrap(),
-            amount: v.u128("amount").sdk_unwrap(),
-        }
+impl TryFrom<JsonValue> for ResolveTransferCallArgs {
+    type Error = JsonFieldError;
+
+    fn try_from(v: JsonValue) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sender_id: v.field("sender_id")?,
+            receiver_id: v.field("receiver_id")?,
+            amount: v.field("amount")?,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::prelude::{format, ToString};
 
     #[test]
     fn test_view_call_fail() {
@@ -465,4 +962,123 @@ mod tests {
         let res = ViewCallArgs::try_from_slice(&bytes).unwrap();
         assert_eq!(x, res);
     }
+
+    #[test]
+    fn test_parse_deposit_message() {
+        let msg = r#"{"version": 1, "recipient": "0x0101010101010101010101010101010101010101"}"#;
+        assert!(DepositMessage::is_versioned(msg));
+        let message = DepositMessage::parse(msg).ok().unwrap();
+        assert_eq!(message.recipient, [1; 20]);
+        assert_eq!(message.fee, U256::zero());
+        assert!(message.call.is_none());
+
+        let msg = r#"{"version": 1, "recipient": "0202020202020202020202020202020202020202", "fee": "10", "fee_token": "deposit", "call": {"contract": "0303030303030303030303030303030303030303", "input": "0xabcd"}}"#;
+        let message = DepositMessage::parse(msg).ok().unwrap();
+        assert_eq!(message.recipient, [2; 20]);
+        assert_eq!(message.fee, U256::from(10));
+        let call = message.call.unwrap();
+        assert_eq!(call.contract, [3; 20]);
+        assert_eq!(call.input, vec![0xab, 0xcd]);
+        assert_eq!(call.gas_limit, DEFAULT_DEPOSIT_CALL_GAS_LIMIT);
+
+        let msg = r#"{"version": 1, "recipient": "0202020202020202020202020202020202020202", "call": {"contract": "0303030303030303030303030303030303030303", "input": "", "gas_limit": 500000}}"#;
+        let call = DepositMessage::parse(msg).ok().unwrap().call.unwrap();
+        assert_eq!(call.gas_limit, 500_000);
+
+        // The legacy format is not versioned
+        assert!(!DepositMessage::is_versioned(
+            "0101010101010101010101010101010101010101"
+        ));
+    }
+
+    #[test]
+    fn test_parse_deposit_message_fail() {
+        let recipient = "0101010101010101010101010101010101010101";
+        let cases = [
+            format!(r#"{{"version": 2, "recipient": "{}"}}"#, recipient),
+            format!(r#"{{"recipient": "{}"}}"#, recipient),
+            r#"{"version": 1, "recipient": "0101"}"#.to_string(),
+            format!(
+                r#"{{"version": 1, "recipient": "{}", "fee_token": "dai"}}"#,
+                recipient
+            ),
+            // The recipient cannot be made to pay the fee in ETH
+            format!(
+                r#"{{"version": 1, "recipient": "{}", "fee_token": "eth"}}"#,
+                recipient
+            ),
+            format!(
+                r#"{{"version": 1, "recipient": "{}", "fee": "-1"}}"#,
+                recipient
+            ),
+            format!(
+                r#"{{"version": 1, "recipient": "{}", "call": {{"contract": "{}", "input": "0xz"}}}}"#,
+                recipient, recipient
+            ),
+            // The gas limit of the call must fit in the NEAR gas of its receipt
+            format!(
+                r#"{{"version": 1, "recipient": "{}", "call": {{"contract": "{}", "input": "", "gas_limit": {}}}}}"#,
+                recipient,
+                recipient,
+                MAX_DEPOSIT_CALL_GAS_LIMIT + 1
+            ),
+            "{".to_string(),
+        ];
+        for msg in cases.iter() {
+            assert!(DepositMessage::parse(msg).is_err(), "{}", msg);
+        }
+    }
+
+    #[test]
+    fn test_parse_transfer_call_args() {
+        let json = parse_json(
+            br#"{"receiver_id": "bob.near", "amount": "10", "memo": null, "msg": "hi"}"#,
+        )
+        .unwrap();
+        let args = TransferCallCallArgs::try_from(json).unwrap();
+        assert_eq!(args.receiver_id.to_string(), "bob.near");
+        assert_eq!(args.amount, 10);
+        assert_eq!(args.memo, None);
+        assert_eq!(args.msg, "hi");
+
+        let cases = [
+            (
+                r#"{"receiver_id": "Bob", "amount": "10", "msg": ""}"#,
+                "ERR_FAILED_PARSE_ACCOUNT_ID at receiver_id",
+            ),
+            (
+                r#"{"receiver_id": "bob.near", "amount": 10, "msg": ""}"#,
+                "ERR_EXPECTED_STRING_GOT_NUMBER at amount",
+            ),
+            (
+                r#"{"receiver_id": "bob.near", "amount": "10", "memo": 1, "msg": ""}"#,
+                "ERR_FAILED_PARSE_STRING at memo",
+            ),
+            (
+                r#"{"receiver_id": "bob.near", "amount": "10"}"#,
+                "ERR_JSON_MISSING_VALUE at msg",
+            ),
+        ];
+        for (json, error) in cases.iter() {
+            let json = parse_json(json.as_bytes()).unwrap();
+            let err = TransferCallCallArgs::try_from(json).err().unwrap();
+            assert_eq!(err.as_ref(), error.as_bytes());
+        }
+    }
+
+    #[test]
+    fn test_ft_on_transfer_args_roundtrip() {
+        // The message can't inject fields in the JSON arguments
+        let msg = "\", \"amount\": \"1000\", \"msg\": \"\u{0}";
+        let args = NEP141FtOnTransferArgs {
+            sender_id: AccountId::try_from("alice.near".to_string()).unwrap(),
+            amount: 10,
+            msg: msg.to_string(),
+        };
+        let json = parse_json(&JsonValue::from(&args).to_bytes()).unwrap();
+        let parsed = NEP141FtOnTransferArgs::try_from(json).unwrap();
+        assert_eq!(parsed.sender_id, args.sender_id);
+        assert_eq!(parsed.amount, 10);
+        assert_eq!(parsed.msg, msg);
+    }
 }
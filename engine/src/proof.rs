@@ -1,4 +1,15 @@
-use crate::prelude::{sdk, BorshDeserialize, BorshSerialize, String, ToString, Vec};
+use crate::parameters::AssertEthclientHashCallArgs;
+use crate::prelude::{
+    sdk, BorshDeserialize, BorshSerialize, RawH256, String, ToString, TryFrom, Vec, H256,
+};
+use rlp::Rlp;
+
+/// Index of the receipts root in the RLP-encoded block header.
+const HEADER_RECEIPTS_ROOT_INDEX: usize = 5;
+/// Index of the block number in the RLP-encoded block header.
+const HEADER_NUMBER_INDEX: usize = 8;
+/// Index of the logs in the RLP-encoded receipt.
+const RECEIPT_LOGS_INDEX: usize = 3;
 
 #[derive(Default, BorshDeserialize, BorshSerialize, Clone)]
 #[cfg_attr(test, derive(serde::Deserialize, serde::Serialize))]
@@ -11,15 +22,283 @@ pub struct Proof {
     pub proof: Vec<Vec<u8>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProofError {
+    InvalidRlp,
+    LogEntryNotFound,
+    InvalidTrieProof,
+}
+
+impl AsRef<[u8]> for ProofError {
+    fn as_ref(&self) -> &[u8] {
+        match self {
+            Self::InvalidRlp => b"ERR_PROOF_INVALID_RLP",
+            Self::LogEntryNotFound => b"ERR_PROOF_LOG_ENTRY_NOT_FOUND",
+            Self::InvalidTrieProof => b"ERR_PROOF_INVALID_TRIE_PROOF",
+        }
+    }
+}
+
+impl From<rlp::DecoderError> for ProofError {
+    fn from(_: rlp::DecoderError) -> Self {
+        Self::InvalidRlp
+    }
+}
+
 impl Proof {
-    pub fn get_key(&self) -> String {
+    /// Key of the proof in the used proofs, which is unique per log entry
+    pub fn get_key(&self) -> RawH256 {
         let mut data = self.log_index.try_to_vec().unwrap();
         data.extend(self.receipt_index.try_to_vec().unwrap());
         data.extend(self.header_data.clone());
-        sdk::sha256(&data[..])
-            .0
-            .iter()
-            .map(|n| n.to_string())
-            .collect()
+        sdk::sha256(&data[..]).0
+    }
+
+    /// Verifies that the log entry is in the receipt, and that the receipt is in
+    /// the receipts trie of the block header. Only the block hash is left to be
+    /// verified by the prover.
+    pub fn verify(&self) -> Result<AssertEthclientHashCallArgs, ProofError> {
+        let header = Rlp::new(&self.header_data);
+        let receipts_root: H256 = header.val_at(HEADER_RECEIPTS_ROOT_INDEX)?;
+        let block_number: u64 = header.val_at(HEADER_NUMBER_INDEX)?;
+
+        // Typed receipts (EIP-2718) are prefixed with their type
+        let receipt = match self.receipt_data.first() {
+            Some(receipt_type) if *receipt_type <= 0x7f => Rlp::new(&self.receipt_data[1..]),
+            _ => Rlp::new(&self.receipt_data),
+        };
+        // The log index must not be truncated, otherwise the same log entry could be
+        // deposited again with the other log indexes it truncates from
+        let log_index =
+            usize::try_from(self.log_index).map_err(|_| ProofError::LogEntryNotFound)?;
+        let log_entry = receipt
+            .at(RECEIPT_LOGS_INDEX)?
+            .at(log_index)
+            .map_err(|_| ProofError::LogEntryNotFound)?;
+        if log_entry.as_raw() != self.log_entry_data.as_slice() {
+            return Err(ProofError::LogEntryNotFound);
+        }
+
+        let key = rlp::encode(&self.receipt_index);
+        verify_trie_proof(receipts_root, &key, &self.proof, &self.receipt_data)?;
+
+        Ok(AssertEthclientHashCallArgs {
+            block_number,
+            expected_block_hash: sdk::keccak(&self.header_data).0,
+        })
+    }
+}
+
+/// Key the used proofs were stored with before they were keyed by their hash,
+/// i.e. the concatenated decimal digits of its bytes.
+///
+/// NOTE: this encoding is not injective, e.g. the bytes `[1, 23]` and `[12, 3]`
+/// give the same digits. A proof colliding with a used legacy key is rejected
+/// as used, which fails safe, and only the proofs of the legacy keys can be
+/// migrated, since the keys are derived from them.
+pub fn legacy_proof_key(key: &RawH256) -> String {
+    key.iter().map(|n| n.to_string()).collect()
+}
+
+/// Verifies the Merkle-Patricia proof of `value` at `key` in the trie of `root`.
+///
+/// Every node of the proof must be referenced by its hash, which always holds in
+/// receipts tries since receipts are longer than 32 bytes.
+fn verify_trie_proof(
+    root: H256,
+    key: &[u8],
+    proof: &[Vec<u8>],
+    value: &[u8],
+) -> Result<(), ProofError> {
+    let key = to_nibbles(key);
+    let mut key_index = 0;
+    let mut expected_hash = root;
+
+    for (i, node) in proof.iter().enumerate() {
+        if sdk::keccak(node) != expected_hash {
+            return Err(ProofError::InvalidTrieProof);
+        }
+        let node = Rlp::new(node);
+        let is_last = i + 1 == proof.len();
+        let next = match node.item_count()? {
+            // Branch node
+            17 => {
+                if key_index == key.len() {
+                    return check_value(node.at(16)?.data()?, value, is_last);
+                }
+                let child = node.at(key[key_index] as usize)?;
+                key_index += 1;
+                child
+            }
+            // Leaf or extension node
+            2 => {
+                let (is_leaf, path) = decode_path(node.at(0)?.data()?)?;
+                if !key[key_index..].starts_with(&path) {
+                    return Err(ProofError::InvalidTrieProof);
+                }
+                key_index += path.len();
+                if is_leaf {
+                    if key_index != key.len() {
+                        return Err(ProofError::InvalidTrieProof);
+                    }
+                    return check_value(node.at(1)?.data()?, value, is_last);
+                }
+                node.at(1)?
+            }
+            _ => return Err(ProofError::InvalidTrieProof),
+        };
+
+        let next = next.data()?;
+        if next.len() != 32 {
+            return Err(ProofError::InvalidTrieProof);
+        }
+        expected_hash = H256::from_slice(next);
+    }
+
+    Err(ProofError::InvalidTrieProof)
+}
+
+fn check_value(node_value: &[u8], value: &[u8], is_last: bool) -> Result<(), ProofError> {
+    if is_last && node_value == value {
+        Ok(())
+    } else {
+        Err(ProofError::InvalidTrieProof)
+    }
+}
+
+fn to_nibbles(bytes: &[u8]) -> Vec<u8> {
+    bytes.iter().flat_map(|b| [b >> 4, b & 0x0f]).collect()
+}
+
+/// Decodes the hex-prefix encoded path of a leaf or extension node.
+fn decode_path(path: &[u8]) -> Result<(bool, Vec<u8>), ProofError> {
+    let (first, rest) = path.split_first().ok_or(ProofError::InvalidTrieProof)?;
+    let flag = first >> 4;
+    if flag > 3 {
+        return Err(ProofError::InvalidTrieProof);
+    }
+    let mut nibbles = Vec::with_capacity(2 * path.len());
+    if flag & 1 == 1 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(to_nibbles(rest));
+    Ok((flag >= 2, nibbles))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::log_entry::LogEntry;
+    use crate::prelude::{format, vec, H160};
+    use rlp::RlpStream;
+
+    const BLOCK_NUMBER: u64 = 42;
+
+    fn receipt(log_entries: &[LogEntry]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream.append(&1u8);
+        stream.append(&21_000u64);
+        stream.append(&vec![0u8; 256]);
+        stream.append_list::<LogEntry, _>(log_entries);
+        stream.out().to_vec()
+    }
+
+    fn leaf(path: u8, value: &[u8]) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(2);
+        stream.append(&vec![path]);
+        stream.append(&value.to_vec());
+        stream.out().to_vec()
+    }
+
+    fn header(receipts_root: H256) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(15);
+        for i in 0..15 {
+            match i {
+                HEADER_RECEIPTS_ROOT_INDEX => stream.append(&receipts_root),
+                HEADER_NUMBER_INDEX => stream.append(&BLOCK_NUMBER),
+                _ => stream.append_empty_data(),
+            };
+        }
+        stream.out().to_vec()
+    }
+
+    /// Proof of the log entry of the first of two receipts. The receipts trie is
+    /// a branch with the leaves of the keys 0x80 and 0x01.
+    fn build_proof() -> Proof {
+        let log_entry = LogEntry {
+            address: H160([1u8; 20]),
+            topics: vec![H256([2u8; 32])],
+            data: vec![3u8; 64],
+        };
+        let receipt_data = receipt(&[log_entry.clone()]);
+        let other_receipt = receipt(&[]);
+
+        let leaf0 = leaf(0x30, &receipt_data);
+        let leaf1 = leaf(0x31, &other_receipt);
+        let mut stream = RlpStream::new_list(17);
+        for i in 0..17 {
+            match i {
+                0 => stream.append(&sdk::keccak(&leaf1)),
+                8 => stream.append(&sdk::keccak(&leaf0)),
+                _ => stream.append_empty_data(),
+            };
+        }
+        let branch = stream.out().to_vec();
+
+        Proof {
+            log_index: 0,
+            log_entry_data: rlp::encode(&log_entry).to_vec(),
+            receipt_index: 0,
+            receipt_data,
+            header_data: header(sdk::keccak(&branch)),
+            proof: vec![branch, leaf0],
+        }
+    }
+
+    #[test]
+    fn test_legacy_proof_key() {
+        let mut key = [0u8; 32];
+        key[0] = 1;
+        key[1] = 23;
+        key[31] = 255;
+        assert_eq!(legacy_proof_key(&key), format!("123{}255", "0".repeat(29)));
+    }
+
+    #[test]
+    fn test_verify_proof() {
+        let proof = build_proof();
+        let result = proof.verify().unwrap();
+        assert_eq!(result.block_number, BLOCK_NUMBER);
+        assert_eq!(
+            result.expected_block_hash,
+            sdk::keccak(&proof.header_data).0
+        );
+    }
+
+    #[test]
+    fn test_verify_proof_fail() {
+        let mut proof = build_proof();
+        proof.log_index = 1;
+        assert_eq!(proof.verify(), Err(ProofError::LogEntryNotFound));
+
+        let mut proof = build_proof();
+        proof.log_index = 1 << 32;
+        assert_eq!(proof.verify(), Err(ProofError::LogEntryNotFound));
+
+        let mut proof = build_proof();
+        proof.log_entry_data[4] ^= 1;
+        assert_eq!(proof.verify(), Err(ProofError::LogEntryNotFound));
+
+        let mut proof = build_proof();
+        proof.receipt_index = 1;
+        assert_eq!(proof.verify(), Err(ProofError::InvalidTrieProof));
+
+        let mut proof = build_proof();
+        proof.proof.pop();
+        assert_eq!(proof.verify(), Err(ProofError::InvalidTrieProof));
+
+        let mut proof = build_proof();
+        proof.header_data = header(H256::zero());
+        assert_eq!(proof.verify(), Err(ProofError::InvalidTrieProof));
     }
 }
@@ -51,9 +51,35 @@ contract EvmErc20 is ERC20, AdminControlled, IExit {
     function withdrawToNear(bytes memory recipient, uint256 amount) external override {
         _burn(_msgSender(), amount);
 
+        // Flag 0x3: ERC-20 exit carrying the address to refund on failure
         bytes32 amount_b = bytes32(amount);
-        bytes memory input = abi.encodePacked("\x01", amount_b, recipient);
-        uint input_size = 1 + 32 + recipient.length;
+        bytes memory input = abi.encodePacked("\x03", _msgSender(), amount_b, recipient);
+        uint input_size = 1 + 20 + 32 + recipient.length;
+
+        assembly {
+            let res := call(gas(), 0xe9217bc70b7ed1f598ddd3199e80b093fa71124f, 0, add(input, 32), input_size, 0, 32)
+        }
+    }
+
+    function withdrawToNearAndCall(bytes memory recipient, uint256 amount, string memory message, string memory memo) external override {
+        _burn(_msgSender(), amount);
+
+        // Flag 0xb: ERC-20 exit sent with `ft_transfer_call`, carrying the address
+        // to refund on failure or with the amount left unused by the recipient
+        bytes32 amount_b = bytes32(amount);
+        bytes memory message_b = bytes(message);
+        bytes memory memo_b = bytes(memo);
+        bytes memory input = abi.encodePacked(
+            "\x0b",
+            _msgSender(),
+            uint32(message_b.length),
+            message_b,
+            uint32(memo_b.length),
+            memo_b,
+            amount_b,
+            recipient
+        );
+        uint input_size = input.length;
 
         assembly {
             let res := call(gas(), 0xe9217bc70b7ed1f598ddd3199e80b093fa71124f, 0, add(input, 32), input_size, 0, 32)
@@ -63,10 +89,11 @@ contract EvmErc20 is ERC20, AdminControlled, IExit {
     function withdrawToEthereum(address recipient, uint256 amount) external override {
         _burn(_msgSender(), amount);
 
+        // Flag 0x3: ERC-20 exit carrying the address to refund on failure
         bytes32 amount_b = bytes32(amount);
         bytes20 recipient_b = bytes20(recipient);
-        bytes memory input = abi.encodePacked("\x01", amount_b, recipient_b);
-        uint input_size = 1 + 32 + 20;
+        bytes memory input = abi.encodePacked("\x03", _msgSender(), amount_b, recipient_b);
+        uint input_size = 1 + 20 + 32 + 20;
 
         assembly {
             let res := call(gas(), 0xb0bd02f6a392af548bdf1cfaee5dfa0eefcc8eab, 0, add(input, 32), input_size, 0, 32)
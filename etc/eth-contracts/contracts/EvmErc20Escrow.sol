@@ -0,0 +1,44 @@
+// SPDX-License-Identifier: CC0-1.0
+pragma solidity ^0.8.0;
+
+import "@openzeppelin/contracts/token/ERC20/IERC20.sol";
+import "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+import "./AdminControlled.sol";
+
+
+/**
+ * @title EvmErc20Escrow
+ * @dev Holds the EVM-native ERC20 tokens bridged to NEAR, where they are minted on their
+ * NEP-141 token by the engine. The tokens are unlocked by the engine when they are burned
+ * on NEAR.
+ */
+contract EvmErc20Escrow is AdminControlled {
+    using SafeERC20 for IERC20;
+
+    constructor (address admin)
+        AdminControlled(admin, 0)
+    {
+    }
+
+    function lockToNear(address token, uint256 amount, bytes memory recipient) external {
+        // Only the amount actually received is bridged, for tokens taking a fee on transfers
+        uint256 balance = IERC20(token).balanceOf(address(this));
+        IERC20(token).safeTransferFrom(msg.sender, address(this), amount);
+        uint256 locked = IERC20(token).balanceOf(address(this)) - balance;
+
+        // Flag 0x22: native ERC-20 exit carrying the address to refund on failure
+        bytes32 amount_b = bytes32(locked);
+        bytes memory input = abi.encodePacked("\x22", msg.sender, token, amount_b, recipient);
+        uint input_size = 1 + 20 + 20 + 32 + recipient.length;
+
+        // The lock is reverted if the token cannot be exited, e.g. when it is not mapped
+        assembly {
+            let res := call(gas(), 0xe9217bc70b7ed1f598ddd3199e80b093fa71124f, 0, add(input, 32), input_size, 0, 32)
+            if iszero(res) { revert(0, 0) }
+        }
+    }
+
+    function unlock(address token, address to, uint256 amount) external onlyAdmin {
+        IERC20(token).safeTransfer(to, amount);
+    }
+}
@@ -0,0 +1,56 @@
+// SPDX-License-Identifier: CC0-1.0
+pragma solidity ^0.8.0;
+
+import "@openzeppelin/contracts/token/ERC721/ERC721.sol";
+import "./AdminControlled.sol";
+
+
+/**
+ * @title EvmErc721
+ * @dev ERC721 token mapped from a native NEP-171 contract. Tokens are minted by the
+ * engine when they are bridged from NEAR, and burned when they exit back to NEAR.
+ */
+contract EvmErc721 is ERC721, AdminControlled {
+    string private _name;
+    string private _symbol;
+
+    constructor (string memory metadata_name, string memory metadata_symbol, address admin)
+        ERC721(metadata_name, metadata_symbol)
+        AdminControlled(admin, 0)
+    {
+        _name = metadata_name;
+        _symbol = metadata_symbol;
+    }
+
+    function name() public view override returns (string memory) {
+        return _name;
+    }
+
+    function symbol() public view override returns (string memory) {
+        return _symbol;
+    }
+
+    function setMetadata(string memory metadata_name, string memory metadata_symbol) external onlyAdmin {
+        _name = metadata_name;
+        _symbol = metadata_symbol;
+    }
+
+    function mint(address to, uint256 tokenId) public onlyAdmin {
+        _mint(to, tokenId);
+    }
+
+    function withdrawToNear(bytes memory recipient, uint256 tokenId) external {
+        require(_isApprovedOrOwner(_msgSender(), tokenId), "ERC721: transfer caller is not owner nor approved");
+        address owner = ownerOf(tokenId);
+        _burn(tokenId);
+
+        // Flag 0x12: ERC-721 exit carrying the address to refund on failure
+        bytes32 token_id_b = bytes32(tokenId);
+        bytes memory input = abi.encodePacked("\x12", owner, token_id_b, recipient);
+        uint input_size = 1 + 20 + 32 + recipient.length;
+
+        assembly {
+            let res := call(gas(), 0xe9217bc70b7ed1f598ddd3199e80b093fa71124f, 0, add(input, 32), input_size, 0, 32)
+        }
+    }
+}
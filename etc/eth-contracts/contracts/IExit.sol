@@ -4,5 +4,7 @@ pragma solidity ^0.8.0;
 interface IExit {
     function withdrawToNear(bytes memory recipient, uint256 amount) external;
 
+    function withdrawToNearAndCall(bytes memory recipient, uint256 amount, string memory message, string memory memo) external;
+
     function withdrawToEthereum(address recipient, uint256 amount) external;
 }
@@ -23,6 +23,10 @@ contract Tester {
         }
     }
 
+    function withdrawAndCall(string memory message) external {
+        erc20Token.withdrawToNearAndCall("target.aurora", 1, message, "");
+    }
+
     function withdrawAndFail(bool toNear) external {
         this.withdraw(toNear);
         require(false);